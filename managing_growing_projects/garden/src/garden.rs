@@ -1 +1,18 @@
-pub mod vegetables;
+pub mod beds;
+pub mod calendar;
+pub mod care;
+pub mod cli;
+pub mod companions;
+pub mod data;
+pub mod dates;
+pub mod growth;
+pub mod harvest;
+pub mod pests;
+pub mod plants;
+pub mod rotation;
+pub mod seeds;
+pub mod state;
+pub mod svg;
+#[cfg(test)]
+pub mod test_support;
+pub mod weather;
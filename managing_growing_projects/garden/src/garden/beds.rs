@@ -0,0 +1,763 @@
+use std::fmt::{self, Write};
+
+use crate::garden::dates::Date;
+use crate::garden::plants::Plant;
+
+// the grid resolution; every bed is divided into squares this size
+pub const CELL_CM: u32 = 10;
+
+// the largest bed we'll model, in either direction
+pub const MAX_SIDE_CM: u32 = 2_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Soil {
+    Loam,
+    Clay,
+    Sand,
+    Silt,
+    Peat,
+    Chalk,
+}
+
+impl Soil {
+    pub const ALL: [Soil; 6] = [
+        Soil::Loam,
+        Soil::Clay,
+        Soil::Sand,
+        Soil::Silt,
+        Soil::Peat,
+        Soil::Chalk,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Soil::Loam => "loam",
+            Soil::Clay => "clay",
+            Soil::Sand => "sand",
+            Soil::Silt => "silt",
+            Soil::Peat => "peat",
+            Soil::Chalk => "chalk",
+        }
+    }
+
+    pub fn parse(text: &str) -> Option<Soil> {
+        Soil::ALL
+            .into_iter()
+            .find(|soil| soil.name().eq_ignore_ascii_case(text.trim()))
+    }
+}
+
+impl fmt::Display for Soil {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+// one plant in a bed. x and y are grid cells, counted from the top left
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Planting {
+    pub id: u32,
+    pub plant: Plant,
+    pub x: u32,
+    pub y: u32,
+    // when it was sown or planted out; None while it's only planned
+    pub planted: Option<Date>,
+}
+
+impl Planting {
+    // centre to centre, in centimetres
+    pub fn distance_cm(&self, x: u32, y: u32) -> f64 {
+        let dx = f64::from(self.x.abs_diff(x) * CELL_CM);
+        let dy = f64::from(self.y.abs_diff(y) * CELL_CM);
+        dx.hypot(dy)
+    }
+
+    pub fn is_near(&self, x: u32, y: u32, range_cm: u32) -> bool {
+        // squared, to stay in whole numbers
+        let dx = u64::from(self.x.abs_diff(x) * CELL_CM);
+        let dy = u64::from(self.y.abs_diff(y) * CELL_CM);
+        dx * dx + dy * dy < u64::from(range_cm) * u64::from(range_cm)
+    }
+}
+
+// how far apart two plants need to be: each wants half its spacing to
+// itself, so a tomato (60) next to basil (25) needs 42.5, rounded up
+pub fn required_distance_cm(a: &Plant, b: &Plant) -> u32 {
+    (a.spacing_cm + b.spacing_cm).div_ceil(2)
+}
+
+// the id that follows `id`. it's kept below u32::MAX so there's always
+// one to hand out and a saved bed's next id loads back
+fn id_after(id: u32) -> Result<u32, BedError> {
+    id.checked_add(1)
+        .filter(|&next| next < u32::MAX)
+        .ok_or(BedError::IdTooLarge(id))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BedError {
+    BadSize {
+        width_cm: u32,
+        length_cm: u32,
+    },
+    OutOfBounds {
+        x: u32,
+        y: u32,
+    },
+    Occupied {
+        x: u32,
+        y: u32,
+        planting: u32,
+    },
+    TooClose {
+        plant: String,
+        other: String,
+        planting: u32,
+        needed_cm: u32,
+    },
+    UnknownPlanting(u32),
+    // the id leaves nothing for the next planting
+    IdTooLarge(u32),
+}
+
+impl fmt::Display for BedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BedError::BadSize {
+                width_cm,
+                length_cm,
+            } => write!(
+                f,
+                "a bed must be between {CELL_CM} and {MAX_SIDE_CM} cm each way, not {width_cm} x {length_cm}"
+            ),
+            BedError::OutOfBounds { x, y } => write!(f, "({x}, {y}) is outside the bed"),
+            BedError::Occupied { x, y, planting } => {
+                write!(f, "({x}, {y}) is taken by planting {planting}")
+            }
+            BedError::TooClose {
+                plant,
+                other,
+                planting,
+                needed_cm,
+            } => write!(
+                f,
+                "{plant} needs {needed_cm} cm from the {other} at planting {planting}"
+            ),
+            BedError::UnknownPlanting(id) => write!(f, "there is no planting {id}"),
+            BedError::IdTooLarge(id) => write!(f, "planting id {id} is too large"),
+        }
+    }
+}
+
+impl std::error::Error for BedError {}
+
+// two plantings that are closer than their spacing allows
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpacingViolation {
+    pub first: u32,
+    pub second: u32,
+    pub distance_cm: f64,
+    pub needed_cm: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bed {
+    pub name: String,
+    pub soil: Soil,
+    // in grid cells
+    columns: u32,
+    rows: u32,
+    plantings: Vec<Planting>,
+    next_id: u32,
+}
+
+impl Bed {
+    // sizes are rounded down to whole grid cells
+    pub fn new(name: &str, width_cm: u32, length_cm: u32, soil: Soil) -> Result<Bed, BedError> {
+        let sides = CELL_CM..=MAX_SIDE_CM;
+        if !sides.contains(&width_cm) || !sides.contains(&length_cm) {
+            return Err(BedError::BadSize {
+                width_cm,
+                length_cm,
+            });
+        }
+        Ok(Bed {
+            name: String::from(name),
+            soil,
+            columns: width_cm / CELL_CM,
+            rows: length_cm / CELL_CM,
+            plantings: Vec::new(),
+            next_id: 1,
+        })
+    }
+
+    pub fn columns(&self) -> u32 {
+        self.columns
+    }
+
+    pub fn rows(&self) -> u32 {
+        self.rows
+    }
+
+    pub fn width_cm(&self) -> u32 {
+        self.columns * CELL_CM
+    }
+
+    pub fn length_cm(&self) -> u32 {
+        self.rows * CELL_CM
+    }
+
+    pub fn area_m2(&self) -> f64 {
+        f64::from(self.width_cm()) * f64::from(self.length_cm()) / 10_000.0
+    }
+
+    pub fn plantings(&self) -> &[Planting] {
+        &self.plantings
+    }
+
+    pub fn planting(&self, id: u32) -> Option<&Planting> {
+        self.plantings.iter().find(|planting| planting.id == id)
+    }
+
+    pub fn planting_at(&self, x: u32, y: u32) -> Option<&Planting> {
+        self.plantings
+            .iter()
+            .find(|planting| planting.x == x && planting.y == y)
+    }
+
+    pub fn contains(&self, x: u32, y: u32) -> bool {
+        x < self.columns && y < self.rows
+    }
+
+    // where (x, y) is in a row by row list of the cells
+    fn cell(&self, x: u32, y: u32) -> usize {
+        (y * self.columns + x) as usize
+    }
+
+    // whether a plant could go at (x, y) given what's already there
+    pub fn check(&self, plant: &Plant, x: u32, y: u32) -> Result<(), BedError> {
+        if !self.contains(x, y) {
+            return Err(BedError::OutOfBounds { x, y });
+        }
+        if let Some(planting) = self.planting_at(x, y) {
+            return Err(BedError::Occupied {
+                x,
+                y,
+                planting: planting.id,
+            });
+        }
+        for planting in &self.plantings {
+            let needed_cm = required_distance_cm(plant, &planting.plant);
+            if planting.is_near(x, y, needed_cm) {
+                return Err(BedError::TooClose {
+                    plant: plant.name.clone(),
+                    other: planting.plant.name.clone(),
+                    planting: planting.id,
+                    needed_cm,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    // plants at (x, y) and returns the new planting's id
+    pub fn place(&mut self, plant: &Plant, x: u32, y: u32) -> Result<u32, BedError> {
+        self.check(plant, x, y)?;
+        self.insert(plant.clone(), x, y)
+    }
+
+    pub fn remove(&mut self, id: u32) -> Result<Planting, BedError> {
+        let index = self
+            .plantings
+            .iter()
+            .position(|planting| planting.id == id)
+            .ok_or(BedError::UnknownPlanting(id))?;
+        Ok(self.plantings.remove(index))
+    }
+
+    // every pair of plantings that is too close together, which can
+    // happen when a layout is loaded after the catalog's spacing changed
+    pub fn violations(&self) -> Vec<SpacingViolation> {
+        let mut violations = Vec::new();
+        for (index, first) in self.plantings.iter().enumerate() {
+            for second in &self.plantings[index + 1..] {
+                let needed_cm = required_distance_cm(&first.plant, &second.plant);
+                if first.is_near(second.x, second.y, needed_cm) {
+                    violations.push(SpacingViolation {
+                        first: first.id,
+                        second: second.id,
+                        distance_cm: first.distance_cm(second.x, second.y),
+                        needed_cm,
+                    });
+                }
+            }
+        }
+        violations
+    }
+
+    // adds a planting without checking spacing, for restoring a saved
+    // layout as it was; callers can look at violations() afterwards
+    pub fn insert(&mut self, plant: Plant, x: u32, y: u32) -> Result<u32, BedError> {
+        let id = self.next_id;
+        self.next_id = id_after(id)?;
+        self.plantings.push(Planting {
+            id,
+            plant,
+            x,
+            y,
+            planted: None,
+        });
+        Ok(id)
+    }
+
+    // the id the next planting will get
+    pub fn next_id(&self) -> u32 {
+        self.next_id
+    }
+
+    // puts back a saved planting under its old id, so harvests logged
+    // against it still line up. like insert, spacing isn't checked
+    pub fn restore(&mut self, planting: Planting) -> Result<(), BedError> {
+        if !self.contains(planting.x, planting.y) {
+            return Err(BedError::OutOfBounds {
+                x: planting.x,
+                y: planting.y,
+            });
+        }
+        if let Some(known) = self.planting(planting.id) {
+            return Err(BedError::Occupied {
+                x: known.x,
+                y: known.y,
+                planting: known.id,
+            });
+        }
+        self.next_id = self.next_id.max(id_after(planting.id)?);
+        self.plantings.push(planting);
+        Ok(())
+    }
+
+    // makes sure ids below `next_id` aren't handed out again, even the
+    // ones of plantings that have since been removed
+    pub fn reserve_ids(&mut self, next_id: u32) -> Result<(), BedError> {
+        if next_id == u32::MAX {
+            return Err(BedError::IdTooLarge(next_id));
+        }
+        self.next_id = self.next_id.max(next_id);
+        Ok(())
+    }
+
+    // records the day a planned planting went in the ground
+    pub fn plant_on(&mut self, id: u32, date: Date) -> Result<(), BedError> {
+        let planting = self
+            .plantings
+            .iter_mut()
+            .find(|planting| planting.id == id)
+            .ok_or(BedError::UnknownPlanting(id))?;
+        planting.planted = Some(date);
+        Ok(())
+    }
+
+    // a character grid of the bed: each planting's cell shows its letter,
+    // the ground it needs around it the same letter in lower case, and
+    // free ground a dot. a legend follows
+    pub fn render(&self) -> String {
+        let symbols = symbols(&self.plantings);
+        let mut grid = vec![vec!['.'; self.columns as usize]; self.rows as usize];
+        for (planting, symbol) in self.plantings.iter().zip(&symbols) {
+            let radius_cm = planting.plant.spacing_cm.div_ceil(2);
+            for (y, row) in grid.iter_mut().enumerate() {
+                for (x, cell) in row.iter_mut().enumerate() {
+                    if *cell == '.' && planting.is_near(x as u32, y as u32, radius_cm) {
+                        *cell = symbol.to_ascii_lowercase();
+                    }
+                }
+            }
+        }
+        for (planting, symbol) in self.plantings.iter().zip(&symbols) {
+            grid[planting.y as usize][planting.x as usize] = *symbol;
+        }
+
+        let mut text = format!(
+            "{} ({} x {} cm, {}, {} cm cells)\n",
+            self.name,
+            self.width_cm(),
+            self.length_cm(),
+            self.soil,
+            CELL_CM
+        );
+        let border = format!("+{}+\n", "-".repeat(self.columns as usize));
+        text.push_str(&border);
+        for row in grid {
+            writeln!(text, "|{}|", row.into_iter().collect::<String>()).unwrap();
+        }
+        text.push_str(&border);
+
+        let mut legend: Vec<(char, &Plant, usize)> = Vec::new();
+        for (planting, symbol) in self.plantings.iter().zip(&symbols) {
+            match legend.iter_mut().find(|(known, _, _)| known == symbol) {
+                Some((_, _, count)) => *count += 1,
+                None => legend.push((*symbol, &planting.plant, 1)),
+            }
+        }
+        for (symbol, plant, count) in legend {
+            writeln!(text, "{symbol} {plant} x{count}").unwrap();
+        }
+        text
+    }
+}
+
+// one capital letter per plant id, trying the letters of the id in turn
+// so tomato and thyme don't both end up as T
+fn symbols(plantings: &[Planting]) -> Vec<char> {
+    let mut assigned: Vec<(&str, char)> = Vec::new();
+    for planting in plantings {
+        let id = planting.plant.id.as_str();
+        if assigned.iter().any(|(known, _)| *known == id) {
+            continue;
+        }
+        let taken = |symbol: &char| assigned.iter().any(|(_, used)| used == symbol);
+        let symbol = id
+            .chars()
+            .filter(char::is_ascii_alphabetic)
+            .map(|letter| letter.to_ascii_uppercase())
+            .chain('A'..='Z')
+            .chain('0'..='9')
+            .find(|symbol| !taken(symbol))
+            .unwrap_or('#');
+        assigned.push((id, symbol));
+    }
+    plantings
+        .iter()
+        .map(|planting| {
+            assigned
+                .iter()
+                .find(|(id, _)| *id == planting.plant.id)
+                .map(|(_, symbol)| *symbol)
+                .unwrap()
+        })
+        .collect()
+}
+
+// how many of each crop the packer managed to fit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Packing {
+    pub counts: Vec<(String, usize)>,
+}
+
+impl Packing {
+    pub fn total(&self) -> usize {
+        self.counts.iter().map(|(_, count)| count).sum()
+    }
+}
+
+// fills the free space in a bed with the given crops, aiming for as many
+// plants as possible while keeping the mix even. it's greedy: crops take
+// turns, biggest first, each taking the first spot that fits. the grid
+// is scanned a few different ways and the fullest result is kept
+pub fn pack(bed: &mut Bed, crops: &[&Plant]) -> Packing {
+    let mut seen: Vec<&str> = Vec::new();
+    let mut crops: Vec<&Plant> = crops
+        .iter()
+        .copied()
+        .filter(|plant| {
+            let new = !seen.contains(&plant.id.as_str());
+            seen.push(&plant.id);
+            new
+        })
+        .collect();
+    crops.sort_by_key(|plant| std::cmp::Reverse(plant.spacing_cm));
+
+    let scans = [Scan::Rows, Scan::Columns, Scan::Staggered];
+    let mut best: Option<(Bed, Packing)> = None;
+    for scan in scans {
+        let mut attempt = bed.clone();
+        let packing = fill(&mut attempt, &crops, scan);
+        if best
+            .as_ref()
+            .is_none_or(|(_, best)| packing.total() > best.total())
+        {
+            best = Some((attempt, packing));
+        }
+    }
+    let (packed, packing) = best.unwrap();
+    *bed = packed;
+    packing
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Scan {
+    Rows,
+    Columns,
+    // every other row shifted by half the row, so plants sit in a
+    // triangular pattern like a seed drill would lay them
+    Staggered,
+}
+
+fn cells(bed: &Bed, scan: Scan) -> Vec<(u32, u32)> {
+    let mut cells = Vec::new();
+    match scan {
+        Scan::Rows | Scan::Staggered => {
+            for y in 0..bed.rows {
+                for x in 0..bed.columns {
+                    cells.push((x, y));
+                }
+            }
+        }
+        Scan::Columns => {
+            for x in 0..bed.columns {
+                for y in 0..bed.rows {
+                    cells.push((x, y));
+                }
+            }
+        }
+    }
+    if let Scan::Staggered = scan {
+        // odd rows start halfway along
+        let half = bed.columns / 2;
+        cells.sort_by_key(|&(x, y)| {
+            let x = if y % 2 == 1 {
+                (x + bed.columns - half) % bed.columns
+            } else {
+                x
+            };
+            (y, x)
+        });
+    }
+    cells
+}
+
+fn fill(bed: &mut Bed, crops: &[&Plant], scan: Scan) -> Packing {
+    let cells = cells(bed, scan);
+    // the plantings in each cell, so a spot is only tested against the
+    // ones close enough to matter rather than the whole bed
+    let mut grid = vec![Vec::new(); (bed.columns * bed.rows) as usize];
+    for (index, planting) in bed.plantings.iter().enumerate() {
+        grid[bed.cell(planting.x, planting.y)].push(index);
+    }
+    let widest_cm = bed
+        .plantings
+        .iter()
+        .map(|planting| &planting.plant)
+        .chain(crops.iter().copied())
+        .map(|plant| plant.spacing_cm)
+        .max()
+        .unwrap_or(0);
+    let mut counts: Vec<(String, usize)> =
+        crops.iter().map(|plant| (plant.id.clone(), 0)).collect();
+    // where each crop's scan picks up again. a cell that didn't fit
+    // won't fit later either, since plantings are only ever added
+    let mut resume = vec![0; crops.len()];
+    let mut stuck = vec![false; crops.len()];
+    while stuck.iter().any(|stuck| !stuck) {
+        for (index, plant) in crops.iter().enumerate() {
+            if stuck[index] {
+                continue;
+            }
+            // the farthest, in whole cells, any planting can be and still
+            // be too close
+            let reach = (plant.spacing_cm + widest_cm).div_ceil(2).saturating_sub(1) / CELL_CM;
+            let found = cells[resume[index]..]
+                .iter()
+                .position(|&(x, y)| fits(bed, &grid, plant, x, y, reach));
+            let Some(offset) = found else {
+                stuck[index] = true;
+                continue;
+            };
+            resume[index] += offset + 1;
+            let (x, y) = cells[resume[index] - 1];
+            if bed.insert((*plant).clone(), x, y).is_err() {
+                stuck[index] = true;
+                continue;
+            }
+            grid[bed.cell(x, y)].push(bed.plantings.len() - 1);
+            counts[index].1 += 1;
+        }
+    }
+    Packing { counts }
+}
+
+// what check() says about (x, y), looking only at plantings within
+// `reach` cells of it
+fn fits(bed: &Bed, grid: &[Vec<usize>], plant: &Plant, x: u32, y: u32, reach: u32) -> bool {
+    if !grid[bed.cell(x, y)].is_empty() {
+        return false;
+    }
+    let columns = x.saturating_sub(reach)..=(x + reach).min(bed.columns - 1);
+    for near_y in y.saturating_sub(reach)..=(y + reach).min(bed.rows - 1) {
+        for near_x in columns.clone() {
+            for &index in &grid[bed.cell(near_x, near_y)] {
+                let planting = &bed.plantings[index];
+                if planting.is_near(x, y, required_distance_cm(plant, &planting.plant)) {
+                    return false;
+                }
+            }
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::garden::plants::Catalog;
+
+    fn bed(width_cm: u32, length_cm: u32) -> Bed {
+        Bed::new("bed A", width_cm, length_cm, Soil::Loam).unwrap()
+    }
+
+    #[test]
+    fn test_sizes() {
+        let bed = bed(125, 240);
+        assert_eq!((bed.columns(), bed.rows()), (12, 24));
+        assert_eq!(bed.area_m2(), 1.2 * 2.4);
+        assert_eq!(
+            Bed::new("tiny", 5, 100, Soil::Clay),
+            Err(BedError::BadSize {
+                width_cm: 5,
+                length_cm: 100
+            })
+        );
+    }
+
+    #[test]
+    fn test_placement_respects_spacing() {
+        let catalog = Catalog::builtin();
+        let tomato = catalog.get("tomato").unwrap();
+        let basil = catalog.get("basil").unwrap();
+        let mut bed = bed(120, 120);
+
+        assert_eq!(bed.place(tomato, 3, 3), Ok(1));
+        assert_eq!(
+            bed.place(basil, 3, 3),
+            Err(BedError::Occupied {
+                x: 3,
+                y: 3,
+                planting: 1
+            })
+        );
+        // tomato and basil need 43 cm between them
+        let error = bed.place(basil, 7, 3).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Basil needs 43 cm from the Tomato at planting 1"
+        );
+        assert_eq!(bed.place(basil, 3, 8), Ok(2));
+        // 30 and 40 cm off the tomato is 50 cm away on the diagonal
+        assert_eq!(bed.place(basil, 6, 7), Ok(3));
+        assert_eq!(bed.place(basil, 7, 0), Ok(4));
+        assert_eq!(
+            bed.place(basil, 12, 0),
+            Err(BedError::OutOfBounds { x: 12, y: 0 })
+        );
+
+        assert_eq!(bed.remove(1).unwrap().plant.id, "tomato");
+        assert_eq!(bed.remove(1), Err(BedError::UnknownPlanting(1)));
+        assert_eq!(bed.place(basil, 3, 3), Ok(5));
+
+        let date = Date::new(2025, 5, 1).unwrap();
+        assert_eq!(bed.planting(5).unwrap().planted, None);
+        assert_eq!(bed.plant_on(5, date), Ok(()));
+        assert_eq!(bed.planting(5).unwrap().planted, Some(date));
+        assert_eq!(bed.plant_on(1, date), Err(BedError::UnknownPlanting(1)));
+    }
+
+    #[test]
+    fn test_ids_run_out_without_overflowing() {
+        let catalog = Catalog::builtin();
+        let radish = catalog.get("radish").unwrap();
+        let mut bed = bed(100, 100);
+        assert_eq!(
+            bed.reserve_ids(u32::MAX),
+            Err(BedError::IdTooLarge(u32::MAX))
+        );
+        bed.reserve_ids(u32::MAX - 2).unwrap();
+        assert_eq!(bed.place(radish, 1, 1), Ok(u32::MAX - 2));
+        assert_eq!(
+            bed.place(radish, 5, 5),
+            Err(BedError::IdTooLarge(u32::MAX - 1))
+        );
+    }
+
+    #[test]
+    fn test_violations() {
+        let catalog = Catalog::builtin();
+        let mut bed = bed(100, 100);
+        bed.insert(catalog.get("squash").unwrap().clone(), 2, 2)
+            .unwrap();
+        bed.insert(catalog.get("lettuce").unwrap().clone(), 5, 6)
+            .unwrap();
+        bed.insert(catalog.get("radish").unwrap().clone(), 9, 9)
+            .unwrap();
+        let violations = bed.violations();
+        assert_eq!(violations.len(), 1);
+        assert_eq!((violations[0].first, violations[0].second), (1, 2));
+        assert_eq!(violations[0].distance_cm, 50.0);
+        assert_eq!(violations[0].needed_cm, 58);
+    }
+
+    #[test]
+    fn test_render() {
+        let catalog = Catalog::builtin();
+        let mut bed = bed(90, 50);
+        bed.place(catalog.get("tomato").unwrap(), 2, 2).unwrap();
+        bed.place(catalog.get("thyme").unwrap(), 7, 1).unwrap();
+        bed.place(catalog.get("thyme").unwrap(), 7, 4).unwrap();
+        assert_eq!(
+            bed.render(),
+            "bed A (90 x 50 cm, loam, 10 cm cells)\n\
+             +---------+\n\
+             |ttttt..h.|\n\
+             |ttttt.hHh|\n\
+             |ttTtt..h.|\n\
+             |ttttt..h.|\n\
+             |ttttt.hHh|\n\
+             +---------+\n\
+             T Tomato 'Brandywine' (Solanum lycopersicum) x1\n\
+             H Thyme 'English' (Thymus vulgaris) x2\n"
+        );
+    }
+
+    #[test]
+    fn test_pack_fills_the_bed_without_violations() {
+        let catalog = Catalog::builtin();
+        let crops = [
+            catalog.get("tomato").unwrap(),
+            catalog.get("basil").unwrap(),
+            catalog.get("lettuce").unwrap(),
+        ];
+        let mut bed = bed(120, 240);
+        let packing = pack(&mut bed, &crops);
+
+        assert_eq!(packing.total(), bed.plantings().len());
+        assert!(bed.violations().is_empty());
+        // largest first, and every crop gets a share
+        assert_eq!(packing.counts[0].0, "tomato");
+        assert!(packing.counts.iter().all(|(_, count)| *count > 0));
+
+        // a full bed has no room for anything else
+        let more = pack(&mut bed, &[catalog.get("radish").unwrap()]);
+        assert!(more.total() > 0);
+        assert_eq!(pack(&mut bed, &crops).total(), 0);
+    }
+
+    #[test]
+    fn test_pack_takes_each_crop_once() {
+        let catalog = Catalog::builtin();
+        let tomato = catalog.get("tomato").unwrap();
+        // the same spacing as tomato, so it sorts between the two
+        let mut other = tomato.clone();
+        other.id = String::from("other");
+        let packing = pack(&mut bed(120, 120), &[tomato, &other, tomato]);
+        let ids: Vec<&str> = packing.counts.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(ids, ["tomato", "other"]);
+    }
+
+    #[test]
+    fn test_pack_a_bed_as_big_as_they_come() {
+        let catalog = Catalog::builtin();
+        let mut bed = bed(MAX_SIDE_CM, MAX_SIDE_CM);
+        let packing = pack(&mut bed, &[catalog.get("radish").unwrap()]);
+        // radishes want 5 cm, so every 10 cm cell takes one
+        assert_eq!(packing.total(), 200 * 200);
+    }
+}
@@ -0,0 +1,426 @@
+use std::fmt::{self, Write};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::garden::beds::Bed;
+use crate::garden::dates::Date;
+use crate::garden::plants::{Plant, Sowing};
+
+// the frost-free season for a location
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrostDates {
+    pub last_spring: Date,
+    pub first_fall: Date,
+}
+
+impl FrostDates {
+    pub fn new(last_spring: Date, first_fall: Date) -> Option<FrostDates> {
+        if last_spring < first_fall {
+            Some(FrostDates {
+                last_spring,
+                first_fall,
+            })
+        } else {
+            None
+        }
+    }
+
+    pub fn season_days(self) -> i64 {
+        self.last_spring.days_until(self.first_fall)
+    }
+
+    // a date counted in weeks from the last spring frost
+    pub fn week(self, week: i32) -> Date {
+        self.last_spring.add_days(i64::from(week) * 7)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Task {
+    StartIndoors,
+    Transplant,
+    DirectSow,
+    Harvest,
+}
+
+impl Task {
+    pub fn name(self) -> &'static str {
+        match self {
+            Task::StartIndoors => "start indoors",
+            Task::Transplant => "transplant",
+            Task::DirectSow => "direct sow",
+            Task::Harvest => "harvest",
+        }
+    }
+}
+
+impl fmt::Display for Task {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Event {
+    pub date: Date,
+    pub task: Task,
+    pub plant: Plant,
+    // which sowing this belongs to, starting at 1
+    pub succession: u32,
+}
+
+impl Event {
+    pub fn summary(&self) -> String {
+        let name = &self.plant.name;
+        let summary = match self.task {
+            Task::StartIndoors => format!("Start {name} indoors"),
+            Task::Transplant => format!("Transplant {name}"),
+            Task::DirectSow => format!("Sow {name}"),
+            Task::Harvest => format!("Harvest {name}"),
+        };
+        if self.succession > 1 {
+            format!("{summary} (sowing {})", self.succession)
+        } else {
+            summary
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Calendar {
+    pub frost: FrostDates,
+    // in date order
+    events: Vec<Event>,
+}
+
+impl Calendar {
+    // plans every plant's sowing, planting out and harvest. plants with a
+    // succession interval are sown again until the harvest would fall
+    // after the first autumn frost
+    pub fn build(frost: FrostDates, plants: &[&Plant]) -> Calendar {
+        let mut events = Vec::new();
+        for plant in plants {
+            let maturity = i64::from(plant.days_to_maturity);
+            // when the plant goes in the ground, and how old it is then
+            let (planted, age) = match plant.sowing {
+                Sowing::Direct { week } => (frost.week(week), 0),
+                Sowing::Indoors {
+                    weeks_before,
+                    transplant_week,
+                } => (
+                    frost.week(transplant_week),
+                    (i64::from(weeks_before) + i64::from(transplant_week)) * 7,
+                ),
+            };
+            let interval = plant.succession_days.map_or(0, i64::from);
+
+            let mut succession = 1;
+            loop {
+                let planted = planted.add_days((succession as i64 - 1) * interval);
+                let harvest = planted.add_days(maturity);
+                if succession > 1 && (interval == 0 || harvest > frost.first_fall) {
+                    break;
+                }
+                let mut event = |date, task| {
+                    events.push(Event {
+                        date,
+                        task,
+                        plant: (*plant).clone(),
+                        succession,
+                    })
+                };
+                match plant.sowing {
+                    Sowing::Direct { .. } => event(planted, Task::DirectSow),
+                    Sowing::Indoors { .. } => {
+                        event(planted.add_days(-age), Task::StartIndoors);
+                        event(planted, Task::Transplant);
+                    }
+                }
+                event(harvest, Task::Harvest);
+                succession += 1;
+            }
+        }
+        events.sort_by(|a, b| {
+            (a.date, a.task, &a.plant.id, a.succession).cmp(&(
+                b.date,
+                b.task,
+                &b.plant.id,
+                b.succession,
+            ))
+        });
+        Calendar { frost, events }
+    }
+
+    // a calendar for whatever is planted in the beds
+    pub fn for_beds(frost: FrostDates, beds: &[Bed]) -> Calendar {
+        let mut plants: Vec<&Plant> = Vec::new();
+        for planting in beds.iter().flat_map(|bed| bed.plantings()) {
+            if !plants.iter().any(|plant| plant.id == planting.plant.id) {
+                plants.push(&planting.plant);
+            }
+        }
+        Calendar::build(frost, &plants)
+    }
+
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    // events from `from` to `to`, inclusive
+    pub fn between(&self, from: Date, to: Date) -> impl Iterator<Item = &Event> {
+        self.events
+            .iter()
+            .filter(move |event| event.date >= from && event.date <= to)
+    }
+
+    // one line per event, for the terminal
+    pub fn agenda(&self) -> String {
+        let mut text = format!(
+            "last frost {}, first frost {}\n",
+            self.frost.last_spring, self.frost.first_fall
+        );
+        for event in &self.events {
+            writeln!(
+                text,
+                "{}  {:<14} {}",
+                event.date,
+                event.task,
+                event.summary()
+            )
+            .unwrap();
+        }
+        text
+    }
+
+    // an iCalendar file with an all-day event per task, see RFC 5545. every
+    // event is stamped with `created`, when the file was made
+    pub fn to_ics(&self, created: SystemTime) -> String {
+        let stamp = timestamp(created);
+        let mut lines = vec![
+            String::from("BEGIN:VCALENDAR"),
+            String::from("VERSION:2.0"),
+            String::from("PRODID:-//garden//planting calendar//EN"),
+            String::from("CALSCALE:GREGORIAN"),
+            String::from("X-WR-CALNAME:Planting calendar"),
+        ];
+        for event in &self.events {
+            let start = event.date.compact();
+            let mut description = format!(
+                "{}. last frost {}, first frost {}",
+                event.plant, self.frost.last_spring, self.frost.first_fall
+            );
+            if let Some(days) = event.plant.succession_days {
+                write!(description, ", sow again every {days} days").unwrap();
+            }
+            lines.extend([
+                String::from("BEGIN:VEVENT"),
+                format!(
+                    "UID:{start}-{}-{}-{}@garden",
+                    event.plant.id,
+                    event.task.name().replace(' ', "-"),
+                    event.succession
+                ),
+                format!("DTSTAMP:{stamp}"),
+                format!("DTSTART;VALUE=DATE:{start}"),
+                format!("DTEND;VALUE=DATE:{}", event.date.add_days(1).compact()),
+                format!("SUMMARY:{}", escape(&event.summary())),
+                format!("DESCRIPTION:{}", escape(&description)),
+                format!("CATEGORIES:{}", escape(event.task.name())),
+                String::from("END:VEVENT"),
+            ]);
+        }
+        lines.push(String::from("END:VCALENDAR"));
+
+        let mut ics = String::new();
+        for line in lines {
+            ics.push_str(&fold(&line));
+        }
+        ics
+    }
+
+    pub fn save_ics(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_ics(SystemTime::now()))
+    }
+}
+
+// a UTC date and time the way DTSTAMP wants it, "20250415T093000Z"
+fn timestamp(time: SystemTime) -> String {
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0);
+    let date = Date::from_days_since_epoch((seconds / 86_400) as i64);
+    let seconds = seconds % 86_400;
+    format!(
+        "{}T{:02}{:02}{:02}Z",
+        date.compact(),
+        seconds / 3_600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+// text values escape backslashes, commas, semicolons and newlines
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | ',' | ';' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push_str("\\n"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// lines end in CRLF and are split so none is longer than 75 bytes, each
+// continuation starting with a space
+fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + 8);
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            width = 1;
+        }
+        folded.push(c);
+        width += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::garden::beds::Soil;
+    use crate::garden::plants::Catalog;
+    use std::time::Duration;
+
+    fn frost() -> FrostDates {
+        FrostDates::new(
+            Date::new(2025, 4, 15).unwrap(),
+            Date::new(2025, 10, 15).unwrap(),
+        )
+        .unwrap()
+    }
+
+    fn dates(calendar: &Calendar, id: &str, task: Task) -> Vec<String> {
+        calendar
+            .events()
+            .iter()
+            .filter(|event| event.plant.id == id && event.task == task)
+            .map(|event| event.date.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_frost_dates() {
+        assert_eq!(frost().season_days(), 183);
+        assert_eq!(frost().week(-2), Date::new(2025, 4, 1).unwrap());
+        assert!(FrostDates::new(frost().first_fall, frost().last_spring).is_none());
+    }
+
+    #[test]
+    fn test_started_indoors() {
+        let catalog = Catalog::builtin();
+        let calendar = Calendar::build(frost(), &[catalog.get("tomato").unwrap()]);
+        // 7 weeks before the frost, out a week after, 85 days to ripen
+        assert_eq!(
+            dates(&calendar, "tomato", Task::StartIndoors),
+            ["2025-02-25"]
+        );
+        assert_eq!(dates(&calendar, "tomato", Task::Transplant), ["2025-04-22"]);
+        assert_eq!(dates(&calendar, "tomato", Task::Harvest), ["2025-07-16"]);
+        assert_eq!(calendar.events().len(), 3);
+    }
+
+    #[test]
+    fn test_successions_stop_before_the_fall_frost() {
+        let catalog = Catalog::builtin();
+        let calendar = Calendar::build(frost(), &[catalog.get("bean").unwrap()]);
+        let sowings = dates(&calendar, "bean", Task::DirectSow);
+        // every 21 days from 2025-04-22 while 50 days still fit
+        assert_eq!(sowings.len(), 7);
+        assert_eq!(sowings[0], "2025-04-22");
+        assert_eq!(sowings[6], "2025-08-26");
+        // the last one is ready on the day of the frost itself
+        let harvests = dates(&calendar, "bean", Task::Harvest);
+        assert_eq!(harvests.last().unwrap(), "2025-10-15");
+        assert_eq!(calendar.events()[1].summary(), "Sow Bush Bean (sowing 2)");
+    }
+
+    #[test]
+    fn test_calendar_for_beds_is_in_date_order() {
+        let catalog = Catalog::builtin();
+        let mut bed = Bed::new("bed A", 120, 120, Soil::Loam).unwrap();
+        bed.place(catalog.get("pepper").unwrap(), 2, 2).unwrap();
+        bed.place(catalog.get("pepper").unwrap(), 8, 2).unwrap();
+        bed.place(catalog.get("radish").unwrap(), 2, 9).unwrap();
+        let calendar = Calendar::for_beds(frost(), &[bed]);
+
+        assert_eq!(dates(&calendar, "pepper", Task::Transplant), ["2025-04-29"]);
+        assert!(
+            calendar
+                .events()
+                .windows(2)
+                .all(|pair| pair[0].date <= pair[1].date)
+        );
+        let april: Vec<&Event> = calendar
+            .between(
+                Date::new(2025, 4, 1).unwrap(),
+                Date::new(2025, 4, 30).unwrap(),
+            )
+            .collect();
+        assert!(april.iter().any(|event| event.task == Task::Transplant));
+        assert!(
+            calendar
+                .agenda()
+                .contains("2025-02-18  start indoors  Start Bell Pepper indoors")
+        );
+    }
+
+    #[test]
+    fn test_ics_export() {
+        let catalog = Catalog::builtin();
+        let calendar = Calendar::build(frost(), &[catalog.get("tomato").unwrap()]);
+        // 2025-05-01 09:30:15 UTC
+        let created = UNIX_EPOCH + Duration::from_secs(1_746_091_815);
+        let ics = calendar.to_ics(created);
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(ics.ends_with("END:VEVENT\r\nEND:VCALENDAR\r\n"));
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 3);
+        assert!(ics.contains(
+            "UID:20250422-tomato-transplant-1@garden\r\n\
+             DTSTAMP:20250501T093015Z\r\n\
+             DTSTART;VALUE=DATE:20250422\r\n\
+             DTEND;VALUE=DATE:20250423\r\n\
+             SUMMARY:Transplant Tomato\r\n\
+             DESCRIPTION:Tomato 'Brandywine' (Solanum lycopersicum). last frost 2025-04-\r\n \
+             15\\, first frost 2025-10-15\r\n"
+        ));
+        assert!(ics.split("\r\n").all(|line| line.len() <= 75));
+    }
+
+    #[test]
+    fn test_escape_and_fold() {
+        assert_eq!(escape("a, b; c\\d\ne"), "a\\, b\\; c\\\\d\\ne");
+        assert_eq!(fold("short"), "short\r\n");
+        let long = "x".repeat(160);
+        let folded = fold(&long);
+        assert_eq!(
+            folded,
+            format!(
+                "{}\r\n {}\r\n {}\r\n",
+                "x".repeat(75),
+                "x".repeat(74),
+                "x".repeat(11)
+            )
+        );
+    }
+}
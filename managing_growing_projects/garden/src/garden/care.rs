@@ -0,0 +1,448 @@
+use std::fmt::{self, Write};
+
+use crate::garden::beds::{Bed, Planting};
+use crate::garden::dates::Date;
+use crate::garden::growth::{Season, Stage};
+use crate::garden::plants::{Family, Plant, Water};
+use crate::garden::weather::Weather;
+
+// let the soil get this far behind before watering established plants;
+// seeds and seedlings can't wait as long
+const DRY_MM: f64 = 10.0;
+const SEEDBED_DRY_MM: f64 = 3.0;
+
+// rain beyond what the soil can hold for later runs off
+const SOIL_STORE_MM: f64 = 15.0;
+
+// tender plants get covered at or below this
+const FROST_C: f64 = 2.0;
+
+// leafy crops bolt when it gets this hot
+const BOLT_C: f64 = 30.0;
+
+// rain plus watering a plant wants in a week at 20C
+fn weekly_need_mm(water: Water) -> f64 {
+    match water {
+        Water::Low => 15.0,
+        Water::Medium => 25.0,
+        Water::High => 35.0,
+    }
+}
+
+// hungry crops that want feeding as they grow
+fn is_heavy_feeder(plant: &Plant) -> bool {
+    matches!(
+        plant.family,
+        Family::Nightshade | Family::Cucurbit | Family::Brassica | Family::Grass
+    )
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Chore {
+    Protect,
+    Water,
+    Fertilize,
+    Prune,
+    Harvest,
+}
+
+impl Chore {
+    pub fn name(self) -> &'static str {
+        match self {
+            Chore::Protect => "protect",
+            Chore::Water => "water",
+            Chore::Fertilize => "fertilize",
+            Chore::Prune => "prune",
+            Chore::Harvest => "harvest",
+        }
+    }
+}
+
+impl fmt::Display for Chore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Task {
+    pub bed: String,
+    pub chore: Chore,
+    pub plantings: Vec<u32>,
+    pub detail: String,
+}
+
+impl fmt::Display for Task {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:<10} {:<9} {}", self.bed, self.chore, self.detail)
+    }
+}
+
+// everything that needs doing in the beds on a day, most urgent first
+pub fn tasks_on(beds: &[Bed], weather: &Weather, date: Date) -> Vec<Task> {
+    let season = Season::for_beds(beds, weather, date);
+    let mut tasks = Vec::new();
+    for bed in beds {
+        let growing: Vec<(&Planting, Stage)> = growing(bed, &season, date);
+        if growing.is_empty() {
+            continue;
+        }
+        let today = weather.on(date);
+        let mut add = |chore, what: &str, plantings: Vec<&Planting>| {
+            if let Some(task) = task(bed, chore, what, &plantings) {
+                tasks.push(task);
+            }
+        };
+
+        if today.is_some_and(|today| today.min_c <= FROST_C) {
+            let tender = growing
+                .iter()
+                .filter(|(planting, _)| planting.plant.is_tender());
+            add(
+                Chore::Protect,
+                "cover against frost:",
+                tender.map(|(planting, _)| *planting).collect(),
+            );
+        }
+        if today.is_some_and(|today| today.max_c >= BOLT_C) {
+            let leafy = growing.iter().filter(|(planting, _)| {
+                matches!(planting.plant.family, Family::Aster | Family::Amaranth)
+            });
+            add(
+                Chore::Protect,
+                "shade to stop bolting:",
+                leafy.map(|(planting, _)| *planting).collect(),
+            );
+        }
+
+        if let Some(short_mm) = watering(bed, weather, &season, date) {
+            let litres = short_mm * bed.area_m2();
+            let what = format!("{litres:.0} L, the soil is {short_mm:.0} mm short:");
+            add(
+                Chore::Water,
+                &what,
+                growing.iter().map(|(planting, _)| *planting).collect(),
+            );
+        }
+
+        // a feed as the plant gets going and again as it starts to flower
+        let feed = growing.iter().filter(|(planting, stage)| {
+            is_heavy_feeder(&planting.plant)
+                && matches!(stage, Stage::Vegetative | Stage::Flowering)
+                && season.stage_on(planting, date.add_days(-1)) != Some(*stage)
+        });
+        add(
+            Chore::Fertilize,
+            "side-dress with compost:",
+            feed.map(|(planting, _)| *planting).collect(),
+        );
+
+        let pinch_out = growing.iter().filter(|(planting, stage)| {
+            planting.plant.family == Family::Nightshade
+                && matches!(
+                    stage,
+                    Stage::Vegetative | Stage::Flowering | Stage::Fruiting
+                )
+                && every(planting, date, 7)
+        });
+        add(
+            Chore::Prune,
+            "pinch out side shoots:",
+            pinch_out.map(|(planting, _)| *planting).collect(),
+        );
+        let pinch_back = growing.iter().filter(|(planting, stage)| {
+            planting.plant.family == Family::Mint
+                && *stage >= Stage::Vegetative
+                && every(planting, date, 14)
+        });
+        add(
+            Chore::Prune,
+            "pinch back the tips:",
+            pinch_back.map(|(planting, _)| *planting).collect(),
+        );
+
+        let ready = growing.iter().filter(|(_, stage)| *stage == Stage::Ready);
+        add(
+            Chore::Harvest,
+            "pick",
+            ready.map(|(planting, _)| *planting).collect(),
+        );
+    }
+    tasks.sort_by_key(|task| task.chore);
+    tasks
+}
+
+// the days from `from` to `to` that have something to do
+pub fn schedule(beds: &[Bed], weather: &Weather, from: Date, to: Date) -> Vec<(Date, Vec<Task>)> {
+    let mut days = Vec::new();
+    let mut date = from;
+    while date <= to {
+        let tasks = tasks_on(beds, weather, date);
+        if !tasks.is_empty() {
+            days.push((date, tasks));
+        }
+        date = date.add_days(1);
+    }
+    days
+}
+
+pub fn format_day(date: Date, tasks: &[Task]) -> String {
+    let mut text = format!("{date}\n");
+    if tasks.is_empty() {
+        text.push_str("  nothing to do\n");
+    }
+    for task in tasks {
+        writeln!(text, "  {task}").unwrap();
+    }
+    text
+}
+
+fn growing<'a>(bed: &'a Bed, season: &Season, date: Date) -> Vec<(&'a Planting, Stage)> {
+    bed.plantings()
+        .iter()
+        .filter_map(|planting| Some((planting, season.stage_on(planting, date)?)))
+        .filter(|(_, stage)| stage.is_growing())
+        .collect()
+}
+
+// whether it's a multiple of `days` since the planting went in
+fn every(planting: &Planting, date: Date, days: i64) -> bool {
+    planting
+        .planted
+        .map(|planted| planted.days_until(date))
+        .is_some_and(|age| age > 0 && age % days == 0)
+}
+
+// how many mm short the bed is if it needs watering today. keeps a running
+// balance from the day the first planting went in: the thirstiest plant
+// sets what's used each day, more in the heat and less in the cold, rain
+// tops it up, and each time it runs dry we assume the bed was watered
+fn watering(bed: &Bed, weather: &Weather, season: &Season, date: Date) -> Option<f64> {
+    let start = bed
+        .plantings()
+        .iter()
+        .filter_map(|planting| planting.planted)
+        .min()?;
+    let mut short_mm: f64 = 0.0;
+    let mut day = start;
+    while day <= date {
+        let growing = growing(bed, season, day);
+        if growing.is_empty() {
+            short_mm = 0.0;
+            day = day.add_days(1);
+            continue;
+        }
+        let need_mm = growing
+            .iter()
+            .map(|(planting, _)| weekly_need_mm(planting.plant.water))
+            .fold(0.0, f64::max)
+            / 7.0;
+        let observation = weather.on(day);
+        let heat = observation.map_or(1.0, |observation| {
+            (1.0 + 0.05 * (observation.mean_c() - 20.0)).clamp(0.4, 2.0)
+        });
+        let rain_mm = observation.map_or(0.0, |observation| observation.rain_mm);
+        short_mm = (short_mm + need_mm * heat - rain_mm).max(-SOIL_STORE_MM);
+
+        let seedlings = growing
+            .iter()
+            .any(|(_, stage)| *stage == Stage::Germinating);
+        let dry_mm = if seedlings { SEEDBED_DRY_MM } else { DRY_MM };
+        if short_mm >= dry_mm {
+            if day == date {
+                return Some(short_mm);
+            }
+            short_mm = 0.0;
+        }
+        day = day.add_days(1);
+    }
+    None
+}
+
+// a task covering some plantings, named once each, or None if there are none
+fn task(bed: &Bed, chore: Chore, what: &str, plantings: &[&Planting]) -> Option<Task> {
+    if plantings.is_empty() {
+        return None;
+    }
+    let mut names: Vec<&str> = Vec::new();
+    for planting in plantings {
+        if !names.contains(&planting.plant.name.as_str()) {
+            names.push(&planting.plant.name);
+        }
+    }
+    Some(Task {
+        bed: bed.name.clone(),
+        chore,
+        plantings: plantings.iter().map(|planting| planting.id).collect(),
+        detail: format!("{what} {}", names.join(", ")),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::garden::beds::Soil;
+    use crate::garden::plants::Catalog;
+    use crate::garden::weather::Observation;
+
+    fn day(month: u8, day: u8) -> Date {
+        Date::new(2025, month, day).unwrap()
+    }
+
+    // a month of mild dry weather from May 1st
+    fn dry_spell() -> Weather {
+        let mut weather = Weather::new();
+        for offset in 0..60 {
+            weather.add(Observation {
+                date: day(5, 1).add_days(offset),
+                min_c: 12.0,
+                max_c: 28.0,
+                rain_mm: 0.0,
+            });
+        }
+        weather
+    }
+
+    fn bed(plants: &[(&str, u32, u32)], planted: Date) -> Bed {
+        let catalog = Catalog::builtin();
+        let mut bed = Bed::new("bed A", 200, 100, Soil::Loam).unwrap();
+        for (id, x, y) in plants {
+            let planting = bed.place(catalog.get(id).unwrap(), *x, *y).unwrap();
+            bed.plant_on(planting, planted).unwrap();
+        }
+        bed
+    }
+
+    fn chores(tasks: &[Task]) -> Vec<Chore> {
+        tasks.iter().map(|task| task.chore).collect()
+    }
+
+    #[test]
+    fn test_watering_follows_the_weather() {
+        let beds = [bed(&[("tomato", 3, 3)], day(5, 1))];
+        let dry = dry_spell();
+        let days: Vec<Date> = schedule(&beds, &dry, day(5, 1), day(5, 20))
+            .into_iter()
+            .filter(|(_, tasks)| tasks.iter().any(|task| task.chore == Chore::Water))
+            .map(|(date, _)| date)
+            .collect();
+        // 5 mm a day: daily while the seeds germinate, every other day after
+        assert_eq!(days[..9], (1..=9).map(|n| day(5, n)).collect::<Vec<_>>());
+        assert_eq!(
+            days[9..],
+            [day(5, 11), day(5, 13), day(5, 15), day(5, 17), day(5, 19)]
+        );
+
+        // the same weeks with a good soak every third day need none
+        let mut wet = dry.clone();
+        for observation in dry.observations().iter().step_by(3) {
+            wet.add(Observation {
+                rain_mm: 20.0,
+                ..*observation
+            });
+        }
+        let watered = schedule(&beds, &wet, day(5, 1), day(5, 31))
+            .iter()
+            .flat_map(|(_, tasks)| tasks)
+            .filter(|task| task.chore == Chore::Water)
+            .count();
+        assert_eq!(watered, 0);
+    }
+
+    #[test]
+    fn test_water_task_detail() {
+        let beds = [bed(&[("tomato", 3, 3)], day(5, 1))];
+        let water = |date| {
+            tasks_on(&beds, &dry_spell(), date)
+                .into_iter()
+                .find(|task| task.chore == Chore::Water)
+        };
+        assert_eq!(
+            water(day(5, 19)).unwrap().to_string(),
+            "bed A      water     20 L, the soil is 10 mm short: Tomato"
+        );
+        assert_eq!(water(day(5, 20)), None);
+    }
+
+    #[test]
+    fn test_care_through_a_tomato_season() {
+        let beds = [bed(&[("tomato", 3, 3), ("basil", 9, 3)], day(5, 1))];
+        let weather = Weather::new();
+
+        // 85 days: vegetative from day 9, flowering from day 43
+        let tasks = tasks_on(&beds, &weather, day(5, 10));
+        assert!(
+            tasks
+                .iter()
+                .any(|task| task.chore == Chore::Fertilize && task.plantings == [1]),
+            "{tasks:?}"
+        );
+        let tasks = tasks_on(&beds, &weather, day(5, 15));
+        assert!(
+            tasks
+                .iter()
+                .any(|task| task.detail == "pinch back the tips: Basil" && task.plantings == [2])
+        );
+        let tasks = tasks_on(&beds, &weather, day(5, 22));
+        assert!(
+            tasks
+                .iter()
+                .any(|task| task.detail == "pinch out side shoots: Tomato")
+        );
+
+        // basil is ready after 60 days and over by the time the tomato is
+        let harvests = |date| {
+            tasks_on(&beds, &weather, date)
+                .into_iter()
+                .filter(|task| task.chore == Chore::Harvest)
+                .map(|task| (task.detail, task.plantings))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(harvests(day(7, 5)), [("pick Basil".to_string(), vec![2])]);
+        assert_eq!(harvests(day(7, 30)), [("pick Tomato".to_string(), vec![1])]);
+        assert!(tasks_on(&beds, &weather, day(12, 1)).is_empty());
+    }
+
+    #[test]
+    fn test_frost_and_heat() {
+        let beds = [bed(
+            &[("tomato", 3, 3), ("pea", 9, 3), ("lettuce", 15, 3)],
+            day(5, 1),
+        )];
+        let mut weather = Weather::new();
+        weather.add(Observation {
+            date: day(5, 5),
+            min_c: -1.0,
+            max_c: 9.0,
+            rain_mm: 0.0,
+        });
+        weather.add(Observation {
+            date: day(5, 6),
+            min_c: 20.0,
+            max_c: 33.0,
+            rain_mm: 0.0,
+        });
+        let frost = tasks_on(&beds, &weather, day(5, 5));
+        assert_eq!(
+            frost[0].to_string(),
+            "bed A      protect   cover against frost: Tomato"
+        );
+        let heat = tasks_on(&beds, &weather, day(5, 6));
+        assert_eq!(heat[0].detail, "shade to stop bolting: Lettuce");
+        assert_eq!(chores(&heat)[..1], [Chore::Protect]);
+    }
+
+    #[test]
+    fn test_format_day() {
+        let beds = [bed(&[("radish", 3, 3)], day(5, 1))];
+        let text = format_day(day(5, 27), &tasks_on(&beds, &Weather::new(), day(5, 27)));
+        // with no weather on record we assume it stayed dry
+        assert_eq!(
+            text,
+            "2025-05-27\n  \
+             bed A      water     21 L, the soil is 11 mm short: Radish\n  \
+             bed A      harvest   pick Radish\n"
+        );
+        assert_eq!(format_day(day(5, 1), &[]), "2025-05-01\n  nothing to do\n");
+    }
+}
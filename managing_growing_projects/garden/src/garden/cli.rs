@@ -0,0 +1,1212 @@
+use std::error::Error;
+use std::fmt::{self, Write};
+use std::io;
+
+use crate::garden::beds::{Bed, BedError, CELL_CM, Soil};
+use crate::garden::calendar::{Calendar, FrostDates};
+use crate::garden::care::{format_day, tasks_on};
+use crate::garden::companions::{self, Rules};
+use crate::garden::dates::{Date, YEARS};
+use crate::garden::growth::Season;
+use crate::garden::harvest::Amount;
+use crate::garden::pests::{Problems, Severity, Sighting, Treatment};
+use crate::garden::plants::{Catalog, Family};
+use crate::garden::rotation::{History, Rotation};
+use crate::garden::seeds::{self, DEAD_BELOW, Viability};
+use crate::garden::state::Garden;
+use crate::garden::svg::{self, DEFAULT_SCALE};
+use crate::garden::weather::Weather;
+
+pub const HELP: &str = "\
+commands:
+  plants                                 list what we can grow
+  bed <name> <width cm> <length cm> [soil]
+                                         add a bed, loam unless a soil is given
+  plant <bed> <plant> <x> <y> [YYYY-MM-DD]
+                                         plant in a bed; x and y count 10 cm
+                                         cells from the top left corner, and
+                                         the date defaults to today
+  remove <bed> <planting>                take a planting out
+  show beds                              list the beds
+  show bed <name>                        draw a bed and what's in it
+  svg [bed]                              the beds, or one of them, drawn to
+                                         scale as an SVG image
+  tasks [today|YYYY-MM-DD]               what needs doing in the beds
+  ready [today|YYYY-MM-DD]               what will be worth picking that day
+  grow <bed> <from> <to> [step days]     the bed drawn every week, or every
+                                         step days, as the weather grows it
+  rotation [gap years]                   what's grown in each bed by year,
+                                         and families back too soon, three
+                                         years apart unless told otherwise
+  rotation plan [year] [gap years]       a bed for each of last year's
+                                         families, next year unless a year
+                                         is given
+  calendar <last frost> <first frost> [--ics file]
+                                         when to sow, plant out and pick
+                                         what's in the beds, also written as
+                                         an iCalendar file if one is given
+  harvest <bed> <planting> <amount> [notes..]
+                                         log a picking, the amount as 850g,
+                                         1.2kg or a count
+  report                                 yields by variety and season
+  spotted <bed> <planting|bed> <problem> [severity] [notes..]
+                                         note a pest or disease on a planting
+                                         or the whole bed, moderate unless
+                                         light or severe is given
+  treated <bed> <planting|bed> <problem> <what was done..>
+                                         note a treatment
+  pests [bed]                            alerts from the last two weeks, and
+                                         a bed's history if one is given
+  problems [plant]                       pests and diseases and how to treat
+                                         them, all or a plant's
+  seeds                                  list the seed packets and how well
+                                         they should come up this year
+  seeds add <plant> <year> <count> [source..]
+                                         file a packet packed for a year
+  seeds check <last frost> <first frost>
+                                         sowings in the beds' calendar we're
+                                         out of seed for, or whose seed is
+                                         likely dead
+  help                                   show this";
+
+#[derive(Debug)]
+pub enum CliError {
+    UnknownCommand(String),
+    Usage(&'static str),
+    UnknownBed(String),
+    UnknownPlant(String),
+    UnknownProblem(String),
+    DuplicateBed(String),
+    BadName(String),
+    BadNumber {
+        what: &'static str,
+        text: String,
+    },
+    BadDate(String),
+    // every packet number has been used
+    NoPacketNumbers,
+    // the last spring frost has to come before the first fall one
+    FrostOrder {
+        last_spring: Date,
+        first_fall: Date,
+    },
+    BadAmount(String),
+    BadSoil(String),
+    OutOfBounds {
+        bed: String,
+        x: u32,
+        y: u32,
+        columns: u32,
+        rows: u32,
+    },
+    Bed(BedError),
+    Write {
+        path: String,
+        error: io::Error,
+    },
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::UnknownCommand(command) => {
+                write!(f, "unknown command '{command}', try 'help'")
+            }
+            CliError::Usage(usage) => write!(f, "usage: {usage}"),
+            CliError::UnknownBed(name) => write!(f, "there is no bed called '{name}'"),
+            CliError::UnknownPlant(id) => {
+                write!(f, "'{id}' is not a plant we know, 'plants' lists them")
+            }
+            CliError::UnknownProblem(id) => {
+                write!(f, "'{id}' is not a problem we know, 'problems' lists them")
+            }
+            CliError::DuplicateBed(name) => write!(f, "there is already a bed called '{name}'"),
+            CliError::BadName(name) => {
+                write!(
+                    f,
+                    "'{name}' can't be a bed name, it can't have commas in it"
+                )
+            }
+            CliError::BadNumber { what, text } => {
+                write!(f, "the {what} should be a whole number, not '{text}'")
+            }
+            CliError::BadDate(text) => write!(f, "'{text}' is not a YYYY-MM-DD date"),
+            CliError::NoPacketNumbers => write!(f, "there are no packet numbers left"),
+            CliError::FrostOrder {
+                last_spring,
+                first_fall,
+            } => write!(
+                f,
+                "the last spring frost, {last_spring}, should come before the first fall frost, {first_fall}"
+            ),
+            CliError::BadAmount(text) => {
+                write!(f, "'{text}' is not an amount, use 850g, 1.2kg or a count")
+            }
+            CliError::BadSoil(text) => write!(f, "'{text}' is not a soil we know"),
+            CliError::OutOfBounds {
+                bed,
+                x,
+                y,
+                columns,
+                rows,
+            } => write!(
+                f,
+                "({x}, {y}) is outside {bed}, which has x 0 to {} and y 0 to {} in {CELL_CM} cm cells",
+                columns - 1,
+                rows - 1
+            ),
+            CliError::Bed(error) => error.fmt(f),
+            CliError::Write { path, error } => write!(f, "could not write {path}: {error}"),
+        }
+    }
+}
+
+impl Error for CliError {}
+
+impl From<BedError> for CliError {
+    fn from(error: BedError) -> CliError {
+        CliError::Bed(error)
+    }
+}
+
+// what a command needs besides the garden itself
+#[derive(Debug, Clone)]
+pub struct Context {
+    pub catalog: Catalog,
+    pub problems: Problems,
+    pub viability: Viability,
+    pub companions: Rules,
+    pub weather: Weather,
+    pub today: Date,
+}
+
+// what to print, and whether the garden needs saving
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    pub text: String,
+    pub changed: bool,
+}
+
+impl Outcome {
+    fn shown(text: String) -> Outcome {
+        Outcome {
+            text,
+            changed: false,
+        }
+    }
+
+    fn changed(text: String) -> Outcome {
+        Outcome {
+            text,
+            changed: true,
+        }
+    }
+}
+
+// runs one command line, already split into words by the shell
+pub fn run(garden: &mut Garden, context: &Context, args: &[String]) -> Result<Outcome, CliError> {
+    let Some((command, args)) = args.split_first() else {
+        return Ok(Outcome::shown(format!("{HELP}\n")));
+    };
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    match command.to_lowercase().as_str() {
+        "help" => Ok(Outcome::shown(format!("{HELP}\n"))),
+        "plants" => Ok(Outcome::shown(show_plants(&context.catalog))),
+        "bed" => add_bed(garden, &args),
+        "plant" => plant(garden, context, &args),
+        "remove" => remove(garden, &args),
+        "show" => show(garden, context, &args),
+        "svg" => draw(garden, &args),
+        "tasks" => tasks(garden, context, &args),
+        "ready" => ready(garden, context, &args),
+        "grow" => grow(garden, context, &args),
+        "calendar" => calendar(garden, &args),
+        "rotation" => rotation(garden, context, &args),
+        "harvest" => harvest(garden, context, &args),
+        "report" => Ok(Outcome::shown(report(garden))),
+        "spotted" => spotted(garden, context, &args),
+        "treated" => treated(garden, context, &args),
+        "pests" => pests(garden, context, &args),
+        "problems" => problems(context, &args),
+        "seeds" => seed_packets(garden, context, &args),
+        _ => Err(CliError::UnknownCommand(command.clone())),
+    }
+}
+
+fn show_plants(catalog: &Catalog) -> String {
+    let mut text = String::new();
+    for plant in catalog.plants() {
+        writeln!(
+            text,
+            "{:<14} {:<10} {:>4} cm {:>4} days  zones {:<5} {}",
+            plant.id, plant.family, plant.spacing_cm, plant.days_to_maturity, plant.zones, plant
+        )
+        .unwrap();
+    }
+    text
+}
+
+fn add_bed(garden: &mut Garden, args: &[&str]) -> Result<Outcome, CliError> {
+    const USAGE: &str = "bed <name> <width cm> <length cm> [soil]";
+    let (name, width_cm, length_cm, soil) = match args {
+        [name, width_cm, length_cm] => (*name, *width_cm, *length_cm, "loam"),
+        [name, width_cm, length_cm, soil] => (*name, *width_cm, *length_cm, *soil),
+        _ => return Err(CliError::Usage(USAGE)),
+    };
+    if name.contains(',') {
+        return Err(CliError::BadName(String::from(name)));
+    }
+    if garden.bed(name).is_some() {
+        return Err(CliError::DuplicateBed(String::from(name)));
+    }
+    let soil = Soil::parse(soil).ok_or(CliError::BadSoil(String::from(soil)))?;
+    let bed = Bed::new(
+        name,
+        number(width_cm, "width")?,
+        number(length_cm, "length")?,
+        soil,
+    )?;
+    let text = format!(
+        "added {} ({} x {} cm, {}), {} x {} cells\n",
+        bed.name,
+        bed.width_cm(),
+        bed.length_cm(),
+        bed.soil,
+        bed.columns(),
+        bed.rows()
+    );
+    garden.beds.push(bed);
+    Ok(Outcome::changed(text))
+}
+
+fn plant(garden: &mut Garden, context: &Context, args: &[&str]) -> Result<Outcome, CliError> {
+    const USAGE: &str = "plant <bed> <plant> <x> <y> [YYYY-MM-DD]";
+    let (bed, plant, x, y, date) = match args {
+        [bed, plant, x, y] => (*bed, *plant, *x, *y, context.today),
+        [bed, plant, x, y, date] => (*bed, *plant, *x, *y, parse_date(date)?),
+        _ => return Err(CliError::Usage(USAGE)),
+    };
+    let plant = context
+        .catalog
+        .get(plant)
+        .ok_or(CliError::UnknownPlant(String::from(plant)))?;
+    let (x, y) = (number(x, "x")?, number(y, "y")?);
+    let bed = find_bed_mut(garden, bed)?;
+    if !bed.contains(x, y) {
+        return Err(CliError::OutOfBounds {
+            bed: bed.name.clone(),
+            x,
+            y,
+            columns: bed.columns(),
+            rows: bed.rows(),
+        });
+    }
+    let id = bed.place(plant, x, y)?;
+    bed.plant_on(id, date)?;
+    let mut text = format!(
+        "planted {plant} in {} at ({x}, {y}) on {date}, planting {id}\n",
+        bed.name
+    );
+    // only the new planting's conflicts, the rest were warned about already
+    let analysis = companions::analyze(bed, &context.companions);
+    for conflict in analysis
+        .conflicts
+        .iter()
+        .filter(|conflict| conflict.first == id || conflict.second == id)
+    {
+        writeln!(text, "warning: {}", conflict.explanation).unwrap();
+    }
+    let (name, planting) = (bed.name.clone(), bed.planting(id).unwrap().clone());
+    garden.rotation.record_planting(&name, &planting);
+    // only once we keep track of seed at all
+    if !garden.seeds.is_empty() {
+        match garden.seeds.sow(plant, &context.viability, date.year(), 1) {
+            Ok(used) => {
+                for (packet, seeds) in &used.taken {
+                    writeln!(text, "used {seeds} seed(s) from packet {packet}").unwrap();
+                }
+            }
+            Err(error) => writeln!(text, "warning: {error}").unwrap(),
+        }
+    }
+    Ok(Outcome::changed(text))
+}
+
+fn remove(garden: &mut Garden, args: &[&str]) -> Result<Outcome, CliError> {
+    let [bed, id] = args else {
+        return Err(CliError::Usage("remove <bed> <planting>"));
+    };
+    let id = number(id, "planting")?;
+    let bed = find_bed_mut(garden, bed)?;
+    let planting = bed.remove(id)?;
+    Ok(Outcome::changed(format!(
+        "took out planting {id}, {}, from {}\n",
+        planting.plant.name, bed.name
+    )))
+}
+
+fn show(garden: &Garden, context: &Context, args: &[&str]) -> Result<Outcome, CliError> {
+    const USAGE: &str = "show beds | show bed <name>";
+    match args {
+        ["beds"] => {
+            if garden.beds.is_empty() {
+                return Ok(Outcome::shown(String::from(
+                    "no beds yet, add one with 'bed'\n",
+                )));
+            }
+            let mut text = String::new();
+            for bed in &garden.beds {
+                writeln!(
+                    text,
+                    "{:<12} {:>4} x {:<4} cm  {:<6} {} planting(s)",
+                    bed.name,
+                    bed.width_cm(),
+                    bed.length_cm(),
+                    bed.soil,
+                    bed.plantings().len()
+                )
+                .unwrap();
+            }
+            Ok(Outcome::shown(text))
+        }
+        ["bed", name] => {
+            let bed = garden
+                .bed(name)
+                .ok_or(CliError::UnknownBed(String::from(*name)))?;
+            let season = Season::for_beds(&garden.beds, &context.weather, context.today);
+            let mut text = bed.render();
+            for planting in bed.plantings() {
+                let state = match (planting.planted, season.stage_on(planting, context.today)) {
+                    (Some(planted), Some(stage)) => format!("planted {planted}, {stage}"),
+                    (Some(planted), None) => format!("to be planted {planted}"),
+                    (None, _) => String::from("planned"),
+                };
+                writeln!(
+                    text,
+                    "{:>3} {} at ({}, {}), {state}",
+                    planting.id, planting.plant, planting.x, planting.y
+                )
+                .unwrap();
+            }
+            Ok(Outcome::shown(text))
+        }
+        _ => Err(CliError::Usage(USAGE)),
+    }
+}
+
+fn draw(garden: &Garden, args: &[&str]) -> Result<Outcome, CliError> {
+    match args {
+        [] => Ok(Outcome::shown(svg::garden_svg(&garden.beds, DEFAULT_SCALE))),
+        [name] => {
+            let bed = garden
+                .bed(name)
+                .ok_or(CliError::UnknownBed(String::from(*name)))?;
+            Ok(Outcome::shown(svg::bed_svg(bed, DEFAULT_SCALE)))
+        }
+        _ => Err(CliError::Usage("svg [bed]")),
+    }
+}
+
+fn tasks(garden: &Garden, context: &Context, args: &[&str]) -> Result<Outcome, CliError> {
+    let date = match args {
+        [] | ["today"] => context.today,
+        [date] => parse_date(date)?,
+        _ => return Err(CliError::Usage("tasks [today|YYYY-MM-DD]")),
+    };
+    let tasks = tasks_on(&garden.beds, &context.weather, date);
+    Ok(Outcome::shown(format_day(date, &tasks)))
+}
+
+fn rotation(garden: &Garden, context: &Context, args: &[&str]) -> Result<Outcome, CliError> {
+    const USAGE: &str = "rotation [gap years] | rotation plan [year] [gap years]";
+    let bad_number = |what, text: &str| CliError::BadNumber {
+        what,
+        text: String::from(text),
+    };
+    // parsed wide so a huge number is out of range rather than not a number
+    let year = |text: &str| match text.parse::<i64>() {
+        Ok(year) => i32::try_from(year)
+            .ok()
+            .filter(|year| YEARS.contains(year))
+            .ok_or(CliError::Usage(USAGE)),
+        Err(_) => Err(bad_number("year", text)),
+    };
+    // anything past a century may as well be never
+    let gap = |text: &str| match text.parse::<i64>() {
+        Ok(years) => i32::try_from(years)
+            .ok()
+            .filter(|years| (1..=100).contains(years))
+            .map(Rotation::new)
+            .ok_or(CliError::Usage(USAGE)),
+        Err(_) => Err(bad_number("gap in years", text)),
+    };
+    let history = &garden.rotation;
+    let (year, rotation) = match args {
+        [] => {
+            return Ok(Outcome::shown(rotation_history(
+                history,
+                Rotation::default(),
+            )));
+        }
+        [years] if *years != "plan" => {
+            return Ok(Outcome::shown(rotation_history(history, gap(years)?)));
+        }
+        ["plan"] => (context.today.year().saturating_add(1), Rotation::default()),
+        ["plan", next] => (year(next)?, Rotation::default()),
+        ["plan", next, years] => (year(next)?, gap(years)?),
+        _ => return Err(CliError::Usage(USAGE)),
+    };
+    let mut families: Vec<Family> = history
+        .records()
+        .iter()
+        .filter(|record| record.year == year - 1)
+        .map(|record| record.family)
+        .collect();
+    families.sort();
+    families.dedup();
+    if garden.beds.is_empty() || families.is_empty() {
+        return Ok(Outcome::shown(format!(
+            "nothing was grown in {} to plan {year} from\n",
+            year - 1
+        )));
+    }
+    let beds: Vec<&str> = garden.beds.iter().map(|bed| bed.name.as_str()).collect();
+    let proposal = rotation.propose(history, &beds, &families, year);
+    let mut text = format!("{year}\n");
+    for (bed, family) in &proposal.assignments {
+        writeln!(text, "  {bed:<12} {family}").unwrap();
+    }
+    for family in &proposal.unplaced {
+        writeln!(
+            text,
+            "no bed can take {family} without breaking the rotation"
+        )
+        .unwrap();
+    }
+    Ok(Outcome::shown(text))
+}
+
+fn rotation_history(history: &History, rotation: Rotation) -> String {
+    if history.records().is_empty() {
+        return String::from("nothing planted yet\n");
+    }
+    let mut text = String::new();
+    for bed in history.beds() {
+        let mut years: Vec<i32> = history
+            .records()
+            .iter()
+            .filter(|record| record.bed == bed)
+            .map(|record| record.year)
+            .collect();
+        years.sort();
+        years.dedup();
+        let grown: Vec<String> = years
+            .iter()
+            .map(|&year| {
+                let families: Vec<&str> = history
+                    .families(bed, year)
+                    .into_iter()
+                    .map(Family::name)
+                    .collect();
+                format!("{year} {}", families.join(" and "))
+            })
+            .collect();
+        writeln!(text, "{bed:<12} {}", grown.join(", ")).unwrap();
+    }
+    for violation in rotation.violations(history) {
+        writeln!(text, "too soon: {violation}").unwrap();
+    }
+    text
+}
+
+fn calendar(garden: &Garden, args: &[&str]) -> Result<Outcome, CliError> {
+    const USAGE: &str = "calendar <last frost> <first frost> [--ics file]";
+    let (last_spring, first_fall, ics) = match args {
+        [last_spring, first_fall] => (*last_spring, *first_fall, None),
+        [last_spring, first_fall, "--ics", path] => (*last_spring, *first_fall, Some(*path)),
+        _ => return Err(CliError::Usage(USAGE)),
+    };
+    let frost = frost_dates(last_spring, first_fall)?;
+    let calendar = Calendar::for_beds(frost, &garden.beds);
+    let mut text = calendar.agenda();
+    if let Some(path) = ics {
+        calendar.save_ics(path).map_err(|error| CliError::Write {
+            path: String::from(path),
+            error,
+        })?;
+        writeln!(text, "wrote {} event(s) to {path}", calendar.events().len()).unwrap();
+    }
+    Ok(Outcome::shown(text))
+}
+
+fn ready(garden: &Garden, context: &Context, args: &[&str]) -> Result<Outcome, CliError> {
+    let date = match args {
+        [] | ["today"] => context.today,
+        [date] => parse_date(date)?,
+        _ => return Err(CliError::Usage("ready [today|YYYY-MM-DD]")),
+    };
+    let season = Season::for_beds(&garden.beds, &context.weather, date);
+    let ready = season.ready_on(&garden.beds, date);
+    if ready.is_empty() {
+        return Ok(Outcome::shown(format!("nothing is ready on {date}\n")));
+    }
+    let mut text = format!("ready on {date}\n");
+    for (bed, planting) in ready {
+        writeln!(
+            text,
+            "  {:<10} {:>3} {}",
+            bed.name, planting.id, planting.plant
+        )
+        .unwrap();
+    }
+    Ok(Outcome::shown(text))
+}
+
+fn grow(garden: &Garden, context: &Context, args: &[&str]) -> Result<Outcome, CliError> {
+    const USAGE: &str = "grow <bed> <from> <to> [step days]";
+    let (name, from, to, step) = match args {
+        [name, from, to] => (*name, *from, *to, 7),
+        [name, from, to, step] => (*name, *from, *to, number(step, "step in days")?),
+        _ => return Err(CliError::Usage(USAGE)),
+    };
+    let bed = garden
+        .bed(name)
+        .ok_or(CliError::UnknownBed(String::from(name)))?;
+    let (from, to) = (parse_date(from)?, parse_date(to)?);
+    let season = Season::for_beds(&garden.beds, &context.weather, to);
+    Ok(Outcome::shown(
+        season.animate(bed, from, to, step).join("\n"),
+    ))
+}
+
+fn harvest(garden: &mut Garden, context: &Context, args: &[&str]) -> Result<Outcome, CliError> {
+    let [bed, id, amount, notes @ ..] = args else {
+        return Err(CliError::Usage(
+            "harvest <bed> <planting> <amount> [notes..]",
+        ));
+    };
+    let id = number(id, "planting")?;
+    let amount = Amount::parse(amount).ok_or(CliError::BadAmount(String::from(*amount)))?;
+    let bed = garden
+        .beds
+        .iter()
+        .find(|known| known.name.eq_ignore_ascii_case(bed))
+        .ok_or(CliError::UnknownBed(String::from(*bed)))?;
+    garden
+        .journal
+        .log(bed, id, context.today, amount, &notes.join(" "))?;
+    let name = &bed.planting(id).unwrap().plant.name;
+    Ok(Outcome::changed(format!(
+        "logged {amount} of {name} from {} planting {id} on {}\n",
+        bed.name, context.today
+    )))
+}
+
+fn report(garden: &Garden) -> String {
+    if garden.journal.harvests().is_empty() {
+        return String::from("no harvests logged yet\n");
+    }
+    garden.journal.report()
+}
+
+fn spotted(garden: &mut Garden, context: &Context, args: &[&str]) -> Result<Outcome, CliError> {
+    let [bed, planting, problem, rest @ ..] = args else {
+        return Err(CliError::Usage(
+            "spotted <bed> <planting|bed> <problem> [severity] [notes..]",
+        ));
+    };
+    let (bed, planting, problem) = target(garden, context, bed, planting, problem)?;
+    let (severity, notes) = match rest.split_first() {
+        Some((word, notes)) if Severity::parse(word).is_some() => {
+            (Severity::parse(word).unwrap(), notes)
+        }
+        _ => (Severity::Moderate, rest),
+    };
+    garden.pests.spot(Sighting {
+        date: context.today,
+        bed: bed.clone(),
+        planting,
+        problem: problem.clone(),
+        severity,
+        notes: notes.join(" "),
+    });
+    let mut text = format!("noted {severity} {problem} in {bed} on {}\n", context.today);
+    for alert in garden
+        .pests
+        .alerts(&garden.beds, &context.problems, context.today)
+        .iter()
+        .filter(|alert| alert.bed() == bed)
+    {
+        writeln!(text, "alert: {alert}").unwrap();
+    }
+    Ok(Outcome::changed(text))
+}
+
+fn treated(garden: &mut Garden, context: &Context, args: &[&str]) -> Result<Outcome, CliError> {
+    let [bed, planting, problem, action @ ..] = args else {
+        return Err(CliError::Usage(
+            "treated <bed> <planting|bed> <problem> <what was done..>",
+        ));
+    };
+    if action.is_empty() {
+        return Err(CliError::Usage(
+            "treated <bed> <planting|bed> <problem> <what was done..>",
+        ));
+    }
+    let (bed, planting, problem) = target(garden, context, bed, planting, problem)?;
+    let text = format!("noted the treatment for {problem} in {bed}\n");
+    garden.pests.treat(Treatment {
+        date: context.today,
+        bed,
+        planting,
+        problem,
+        action: action.join(" "),
+    });
+    Ok(Outcome::changed(text))
+}
+
+// the bed's own name, the planting ("bed" for all of it) and the problem id
+// a sighting or treatment is about, checked against the garden
+fn target(
+    garden: &Garden,
+    context: &Context,
+    bed: &str,
+    planting: &str,
+    problem: &str,
+) -> Result<(String, Option<u32>, String), CliError> {
+    let bed = garden
+        .bed(bed)
+        .ok_or(CliError::UnknownBed(String::from(bed)))?;
+    let planting = match planting {
+        "bed" => None,
+        id => {
+            let id = number(id, "planting")?;
+            bed.planting(id).ok_or(BedError::UnknownPlanting(id))?;
+            Some(id)
+        }
+    };
+    let problem = context
+        .problems
+        .get(problem)
+        .ok_or(CliError::UnknownProblem(String::from(problem)))?;
+    Ok((bed.name.clone(), planting, problem.id.clone()))
+}
+
+fn pests(garden: &Garden, context: &Context, args: &[&str]) -> Result<Outcome, CliError> {
+    let bed = match args {
+        [] => None,
+        [name] => Some(
+            garden
+                .bed(name)
+                .ok_or(CliError::UnknownBed(String::from(*name)))?,
+        ),
+        _ => return Err(CliError::Usage("pests [bed]")),
+    };
+    let alerts = garden
+        .pests
+        .alerts(&garden.beds, &context.problems, context.today);
+    let mut text = String::new();
+    for alert in &alerts {
+        writeln!(text, "alert: {alert}").unwrap();
+    }
+    if let Some(bed) = bed {
+        text.push_str(&garden.pests.history(&bed.name));
+    }
+    if text.is_empty() {
+        text.push_str("nothing to worry about\n");
+    }
+    Ok(Outcome::shown(text))
+}
+
+fn problems(context: &Context, args: &[&str]) -> Result<Outcome, CliError> {
+    let problems = match args {
+        [] => context.problems.problems().iter().collect(),
+        [plant] => {
+            let plant = context
+                .catalog
+                .get(plant)
+                .ok_or(CliError::UnknownPlant(String::from(*plant)))?;
+            context.problems.for_plant(plant)
+        }
+        _ => return Err(CliError::Usage("problems [plant]")),
+    };
+    let mut text = String::new();
+    for problem in problems {
+        writeln!(
+            text,
+            "{:<16} {:<26} {}",
+            problem.id, problem.name, problem.kind
+        )
+        .unwrap();
+        for treatment in &problem.treatments {
+            writeln!(text, "    - {treatment}").unwrap();
+        }
+    }
+    Ok(Outcome::shown(text))
+}
+
+fn seed_packets(
+    garden: &mut Garden,
+    context: &Context,
+    args: &[&str],
+) -> Result<Outcome, CliError> {
+    const USAGE: &str = "seeds | seeds add <plant> <year> <count> [source..] | seeds check <last frost> <first frost>";
+    match args {
+        [] => {
+            if garden.seeds.is_empty() {
+                return Ok(Outcome::shown(String::from(
+                    "no seed packets yet, add one with 'seeds add'\n",
+                )));
+            }
+            let year = context.today.year();
+            let mut text = String::new();
+            for packet in garden.seeds.packets() {
+                let plant = context.catalog.get(&packet.plant);
+                let germination = plant.map_or(0.0, |plant| {
+                    context
+                        .viability
+                        .germination(&plant.species, packet.age(year))
+                });
+                let name = plant.map_or(packet.plant.as_str(), |plant| &plant.name);
+                let dead = if germination < DEAD_BELOW {
+                    ", likely dead"
+                } else {
+                    ""
+                };
+                let line = format!(
+                    "{:>3} {:<14} {:<20} {} {:>5} left  ~{:.0}% come up{dead}  {}",
+                    packet.id,
+                    name,
+                    packet.variety,
+                    packet.year,
+                    packet.quantity,
+                    germination * 100.0,
+                    packet.source
+                );
+                writeln!(text, "{}", line.trim_end()).unwrap();
+            }
+            Ok(Outcome::shown(text))
+        }
+        ["add", plant, year, count, source @ ..] => {
+            let plant = context
+                .catalog
+                .get(plant)
+                .ok_or(CliError::UnknownPlant(String::from(*plant)))?;
+            let year = parse_year(year)?;
+            let count = number(count, "seed count")?;
+            let id = garden
+                .seeds
+                .add(plant, &source.join(" "), year, count)
+                .ok_or(CliError::NoPacketNumbers)?;
+            Ok(Outcome::changed(format!(
+                "filed {count} {} seeds from {year} as packet {id}\n",
+                plant.name
+            )))
+        }
+        ["check", last_spring, first_fall] => {
+            let frost = frost_dates(last_spring, first_fall)?;
+            let calendar = Calendar::for_beds(frost, &garden.beds);
+            let warnings = seeds::check(&calendar, &garden.seeds, &context.viability);
+            if warnings.is_empty() {
+                return Ok(Outcome::shown(String::from(
+                    "there's good seed for everything\n",
+                )));
+            }
+            let mut text = String::new();
+            for warning in warnings {
+                writeln!(text, "{warning}").unwrap();
+            }
+            Ok(Outcome::shown(text))
+        }
+        _ => Err(CliError::Usage(USAGE)),
+    }
+}
+
+fn find_bed_mut<'a>(garden: &'a mut Garden, name: &str) -> Result<&'a mut Bed, CliError> {
+    garden
+        .bed_mut(name)
+        .ok_or(CliError::UnknownBed(String::from(name)))
+}
+
+fn number(text: &str, what: &'static str) -> Result<u32, CliError> {
+    text.parse().map_err(|_| CliError::BadNumber {
+        what,
+        text: String::from(text),
+    })
+}
+
+fn parse_year(text: &str) -> Result<i32, CliError> {
+    text.parse()
+        .ok()
+        .filter(|year| YEARS.contains(year))
+        .ok_or(CliError::BadNumber {
+            what: "year",
+            text: String::from(text),
+        })
+}
+
+fn parse_date(text: &str) -> Result<Date, CliError> {
+    Date::parse(text).ok_or(CliError::BadDate(String::from(text)))
+}
+
+fn frost_dates(last_spring: &str, first_fall: &str) -> Result<FrostDates, CliError> {
+    let (last_spring, first_fall) = (parse_date(last_spring)?, parse_date(first_fall)?);
+    FrostDates::new(last_spring, first_fall).ok_or(CliError::FrostOrder {
+        last_spring,
+        first_fall,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs;
+    use std::process;
+
+    fn context() -> Context {
+        Context {
+            catalog: Catalog::builtin(),
+            problems: Problems::builtin(),
+            viability: Viability::builtin(),
+            companions: Rules::builtin(),
+            weather: Weather::new(),
+            today: Date::new(2025, 7, 1).unwrap(),
+        }
+    }
+
+    fn run_line(garden: &mut Garden, line: &str) -> Result<Outcome, CliError> {
+        let args: Vec<String> = line.split_whitespace().map(String::from).collect();
+        run(garden, &context(), &args)
+    }
+
+    fn text(garden: &mut Garden, line: &str) -> String {
+        run_line(garden, line).unwrap().text
+    }
+
+    fn error(garden: &mut Garden, line: &str) -> String {
+        run_line(garden, line).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn test_a_season_from_the_command_line() {
+        let mut garden = Garden::new();
+        assert_eq!(
+            text(&mut garden, "bed South 100 50 clay"),
+            "added South (100 x 50 cm, clay), 10 x 5 cells\n"
+        );
+        let planted = run_line(&mut garden, "plant south tomato 3 2 2025-05-01").unwrap();
+        assert!(planted.changed);
+        assert_eq!(
+            planted.text,
+            "planted Tomato 'Brandywine' (Solanum lycopersicum) in South at (3, 2) on 2025-05-01, planting 1\n"
+        );
+        text(&mut garden, "plant South radish 8 4");
+        assert_eq!(
+            text(&mut garden, "show beds"),
+            "South         100 x 50   cm  clay   2 planting(s)\n"
+        );
+        let shown = run_line(&mut garden, "show bed South").unwrap();
+        assert!(!shown.changed);
+        assert!(shown.text.ends_with(
+            "  1 Tomato 'Brandywine' (Solanum lycopersicum) at (3, 2), planted 2025-05-01, flowering\n  \
+             2 Radish 'Cherry Belle' (Raphanus sativus) at (8, 4), planted 2025-07-01, germinating\n"
+        ));
+        assert!(text(&mut garden, "svg south").contains(">South (100 x 50 cm, clay)</text>"));
+        assert!(text(&mut garden, "tasks today").starts_with("2025-07-01\n"));
+        assert_eq!(
+            text(&mut garden, "tasks 2025-07-31"),
+            "2025-07-31\n  \
+             South      water     5 L, the soil is 10 mm short: Tomato, Radish\n  \
+             South      harvest   pick Tomato, Radish\n"
+        );
+
+        assert_eq!(
+            text(&mut garden, "harvest South 1 1.2kg first of the year"),
+            "logged 1200 g of Tomato from South planting 1 on 2025-07-01\n"
+        );
+        assert_eq!(garden.journal.harvests()[0].notes, "first of the year");
+        assert!(text(&mut garden, "report").starts_with("Solanum lycopersicum\n"));
+        assert_eq!(
+            text(&mut garden, "remove South 2"),
+            "took out planting 2, Radish, from South\n"
+        );
+        assert_eq!(garden.beds[0].plantings().len(), 1);
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut garden = Garden::new();
+        text(&mut garden, "bed A 100 50");
+        assert_eq!(
+            error(&mut garden, "plant A tomato 10 2"),
+            "(10, 2) is outside A, which has x 0 to 9 and y 0 to 4 in 10 cm cells"
+        );
+        assert_eq!(
+            error(&mut garden, "plant A tomato -1 2"),
+            "the x should be a whole number, not '-1'"
+        );
+        assert_eq!(
+            error(&mut garden, "plant A triffid 1 2"),
+            "'triffid' is not a plant we know, 'plants' lists them"
+        );
+        assert_eq!(
+            error(&mut garden, "plant B tomato 1 2"),
+            "there is no bed called 'B'"
+        );
+        text(&mut garden, "plant A tomato 3 2");
+        assert_eq!(
+            error(&mut garden, "plant A basil 5 2"),
+            "Basil needs 43 cm from the Tomato at planting 1"
+        );
+        assert_eq!(error(&mut garden, "remove A 9"), "there is no planting 9");
+        assert_eq!(
+            error(&mut garden, "harvest A 1 heaps"),
+            "'heaps' is not an amount, use 850g, 1.2kg or a count"
+        );
+        assert_eq!(
+            error(&mut garden, "bed a 100 100"),
+            "there is already a bed called 'a'"
+        );
+        assert_eq!(
+            error(&mut garden, "show"),
+            "usage: show beds | show bed <name>"
+        );
+        assert_eq!(
+            error(&mut garden, "dig"),
+            "unknown command 'dig', try 'help'"
+        );
+        assert_eq!(
+            error(&mut garden, "tasks 07-01"),
+            "'07-01' is not a YYYY-MM-DD date"
+        );
+    }
+
+    #[test]
+    fn test_planting_warns_about_bad_neighbours() {
+        let mut garden = Garden::new();
+        text(&mut garden, "bed A 200 100");
+        text(&mut garden, "plant A bean 3 3");
+        assert_eq!(
+            text(&mut garden, "plant A garlic 5 3"),
+            "planted Garlic 'Music' (Allium sativum) in A at (5, 3) on 2025-07-01, planting 2\n\
+             warning: Bush Bean (planting 1) and Garlic (planting 2) are 20 cm apart: onions and garlic stunt the growth of beans and peas\n"
+        );
+        // the bean and garlic aren't brought up again
+        assert!(
+            text(&mut garden, "plant A basil 15 3")
+                .lines()
+                .all(|line| !line.starts_with("warning"))
+        );
+    }
+
+    #[test]
+    fn test_ready_and_grow() {
+        let mut garden = Garden::new();
+        text(&mut garden, "bed A 100 50");
+        text(&mut garden, "plant A radish 1 1 2025-05-01");
+        text(&mut garden, "plant A tomato 6 2 2025-05-01");
+        assert_eq!(
+            text(&mut garden, "ready 2025-05-10"),
+            "nothing is ready on 2025-05-10\n"
+        );
+        // typical weather, so the catalog's 25 days
+        assert_eq!(
+            text(&mut garden, "ready 2025-05-26"),
+            "ready on 2025-05-26\n  \
+             A            1 Radish 'Cherry Belle' (Raphanus sativus)\n"
+        );
+        let frames = text(&mut garden, "grow a 2025-05-01 2025-05-29 14");
+        assert_eq!(frames.matches("A on 2025-").count(), 3);
+        assert!(frames.starts_with("A on 2025-05-01\n+----------+\n"));
+        assert!(frames.contains("\n\nA on 2025-05-15\n"));
+        assert!(frames.ends_with("  2 v Tomato         vegetative   33%\n"));
+        assert_eq!(
+            error(&mut garden, "grow B 2025-05-01 2025-05-29"),
+            "there is no bed called 'B'"
+        );
+    }
+
+    #[test]
+    fn test_pests() {
+        let mut garden = Garden::new();
+        text(&mut garden, "bed A 200 100");
+        text(&mut garden, "plant A tomato 3 3");
+        text(&mut garden, "plant A pepper 9 3");
+        assert_eq!(
+            text(&mut garden, "spotted A 1 aphids severe all over the tips"),
+            "noted severe aphids in A on 2025-07-01\n\
+             alert: A: Aphids on planting 1 could reach planting(s) 2\n"
+        );
+        assert_eq!(garden.pests.sightings()[0].notes, "all over the tips");
+        text(&mut garden, "treated A 1 aphids insecticidal soap");
+        assert_eq!(
+            text(&mut garden, "pests a"),
+            "alert: A: Aphids on planting 1 could reach planting(s) 2\n\
+             2025-07-01 seen     aphids on planting 1, severe: all over the tips\n\
+             2025-07-01 treated  aphids on planting 1: insecticidal soap\n"
+        );
+        assert!(text(&mut garden, "problems pepper").contains("late-blight"));
+        assert_eq!(
+            error(&mut garden, "spotted A 1 gremlins"),
+            "'gremlins' is not a problem we know, 'problems' lists them"
+        );
+        assert_eq!(
+            error(&mut garden, "spotted A 7 aphids"),
+            "there is no planting 7"
+        );
+        assert!(
+            run_line(&mut garden, "spotted A bed slugs")
+                .unwrap()
+                .changed
+        );
+    }
+
+    #[test]
+    fn test_seeds() {
+        let mut garden = Garden::new();
+        text(&mut garden, "bed A 300 150");
+        // nothing to take seed from until we keep packets
+        assert_eq!(
+            text(&mut garden, "plant A tomato 3 3 2025-05-01"),
+            "planted Tomato 'Brandywine' (Solanum lycopersicum) in A at (3, 3) on 2025-05-01, planting 1\n"
+        );
+        assert_eq!(
+            text(
+                &mut garden,
+                "seeds add tomato 2024 3 Baker Creek, spring order"
+            ),
+            "filed 3 Tomato seeds from 2024 as packet 1\n"
+        );
+        text(&mut garden, "seeds add onion 2021 100");
+        assert_eq!(
+            text(&mut garden, "seeds"),
+            "  1 Tomato         Brandywine           2024     3 left  ~85% come up  Baker Creek, spring order\n  \
+             2 Onion          Yellow Sweet Spanish 2021   100 left  ~9% come up, likely dead\n"
+        );
+        assert!(
+            text(&mut garden, "plant A tomato 12 3 2025-05-01")
+                .ends_with("planting 2\nused 2 seed(s) from packet 1\n")
+        );
+        assert!(
+            text(&mut garden, "plant A tomato 3 12 2025-05-01")
+                .ends_with("used 1 seed(s) from packet 1\n")
+        );
+        assert!(
+            text(&mut garden, "plant A basil 9 8 2025-05-01")
+                .ends_with("warning: there's no Basil seed left\n")
+        );
+        text(&mut garden, "plant A onion 20 8 2025-05-01");
+        assert_eq!(
+            text(&mut garden, "seeds check 2025-04-15 2025-10-15"),
+            "2025-02-04 Start Onion indoors: the Onion seed left is from 2021 and likely dead\n\
+             2025-02-25 Start Tomato indoors: there's no Tomato seed left\n\
+             2025-03-04 Start Basil indoors: there's no Basil seed left\n"
+        );
+        assert_eq!(
+            error(&mut garden, "seeds add triffid 2024 10"),
+            "'triffid' is not a plant we know, 'plants' lists them"
+        );
+        assert_eq!(
+            error(&mut garden, "seeds add bean 4000000000 5"),
+            "the year should be a whole number, not '4000000000'"
+        );
+        assert_eq!(
+            error(&mut garden, "seeds add bean 0 5"),
+            "the year should be a whole number, not '0'"
+        );
+
+        let catalog = Catalog::builtin();
+        let mut full =
+            Garden::parse("packet, 4294967294, tomato, 2024, 10, Roma, swap", &catalog).unwrap();
+        assert_eq!(
+            error(&mut full, "seeds add tomato 2024 5"),
+            "there are no packet numbers left"
+        );
+    }
+
+    #[test]
+    fn test_calendar() {
+        let mut garden = Garden::new();
+        text(&mut garden, "bed A 200 100");
+        text(&mut garden, "plant A tomato 3 3 2025-05-01");
+        let shown = run_line(&mut garden, "calendar 2025-04-15 2025-10-15").unwrap();
+        assert!(!shown.changed);
+        assert_eq!(
+            shown.text,
+            "last frost 2025-04-15, first frost 2025-10-15\n\
+             2025-02-25  start indoors  Start Tomato indoors\n\
+             2025-04-22  transplant     Transplant Tomato\n\
+             2025-07-16  harvest        Harvest Tomato\n"
+        );
+
+        let path = env::temp_dir().join(format!("garden-calendar-{}.ics", process::id()));
+        let line = format!("calendar 2025-04-15 2025-10-15 --ics {}", path.display());
+        let shown = text(&mut garden, &line);
+        let ics = fs::read_to_string(&path);
+        fs::remove_file(&path).unwrap();
+        assert!(shown.ends_with(&format!("wrote 3 event(s) to {}\n", path.display())));
+        let ics = ics.unwrap();
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 3);
+        // stamped when it was written, not on the day of each event
+        assert!(!ics.contains("DTSTAMP:20250422"));
+
+        assert_eq!(
+            error(&mut garden, "calendar 2025-10-15 2025-04-15"),
+            "the last spring frost, 2025-10-15, should come before the first fall frost, 2025-04-15"
+        );
+        assert_eq!(
+            error(&mut garden, "calendar 2025-04-15 2025-10-15 --ical x"),
+            "usage: calendar <last frost> <first frost> [--ics file]"
+        );
+    }
+
+    #[test]
+    fn test_rotation() {
+        let mut garden = Garden::new();
+        text(&mut garden, "bed A 200 100");
+        text(&mut garden, "bed B 200 100");
+        assert_eq!(text(&mut garden, "rotation"), "nothing planted yet\n");
+        text(&mut garden, "plant A tomato 3 3 2024-05-01");
+        text(&mut garden, "plant B bean 3 3 2024-05-01");
+        text(&mut garden, "plant A pepper 3 9 2025-05-01");
+        text(&mut garden, "plant A asparagus 15 5 2025-05-01");
+        text(&mut garden, "plant B cabbage 12 3 2025-05-01");
+        assert_eq!(
+            text(&mut garden, "rotation"),
+            "A            2024 nightshade, 2025 nightshade\n\
+             B            2024 legume, 2025 brassica\n\
+             too soon: A: nightshade in 2025 after nightshade in 2024, only 1 year(s) apart\n"
+        );
+        assert!(!text(&mut garden, "rotation 1").contains("too soon"));
+        // the nightshades have been in A two years running, so they move
+        assert_eq!(
+            text(&mut garden, "rotation plan"),
+            "2026\n  A            brassica\n  B            nightshade\n"
+        );
+        // with brassicas in both beds this year they can't go anywhere
+        text(&mut garden, "plant A kale 9 8 2025-05-01");
+        assert_eq!(
+            text(&mut garden, "rotation plan"),
+            "2026\n  B            nightshade\n\
+             no bed can take brassica without breaking the rotation\n"
+        );
+        assert_eq!(
+            text(&mut garden, "rotation plan 2025"),
+            "2025\n  A            legume\n  B            nightshade\n"
+        );
+        assert_eq!(
+            text(&mut garden, "rotation plan 2030"),
+            "nothing was grown in 2029 to plan 2030 from\n"
+        );
+        assert_eq!(
+            error(&mut garden, "rotation plan soon"),
+            "the year should be a whole number, not 'soon'"
+        );
+        let usage = "usage: rotation [gap years] | rotation plan [year] [gap years]";
+        assert_eq!(error(&mut garden, "rotation plan 2147483648"), usage);
+        assert_eq!(error(&mut garden, "rotation plan 3000000000"), usage);
+        assert_eq!(error(&mut garden, "rotation plan 2026 0"), usage);
+        assert_eq!(error(&mut garden, "rotation 4294967295"), usage);
+    }
+}
@@ -0,0 +1,448 @@
+use std::fmt::{self, Write};
+use std::fs;
+use std::path::Path;
+
+use crate::garden::beds::{Bed, Planting};
+use crate::garden::data::DataError;
+use crate::garden::plants::{Catalog, Family, Plant};
+
+// the rules we ship with, see data/companions.csv for the format
+const BUILTIN: &str = include_str!("../../data/companions.csv");
+
+// plants further apart than this, centre to centre, don't affect each other
+pub const NEIGHBOUR_CM: u32 = 60;
+
+// what a good neighbour adds to a planting's score, and a bad one takes off
+const BENEFIT: i32 = 1;
+const HARM: i32 = -2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Relation {
+    Beneficial,
+    Harmful,
+}
+
+impl Relation {
+    pub fn name(self) -> &'static str {
+        match self {
+            Relation::Beneficial => "beneficial",
+            Relation::Harmful => "harmful",
+        }
+    }
+
+    pub fn parse(text: &str) -> Option<Relation> {
+        [Relation::Beneficial, Relation::Harmful]
+            .into_iter()
+            .find(|relation| relation.name().eq_ignore_ascii_case(text.trim()))
+    }
+
+    fn score(self) -> i32 {
+        match self {
+            Relation::Beneficial => BENEFIT,
+            Relation::Harmful => HARM,
+        }
+    }
+}
+
+impl fmt::Display for Relation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+// one side of a rule: a particular plant, or a whole family
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Subject {
+    Plant(String),
+    Family(Family),
+}
+
+impl Subject {
+    // a plant id, or "family:<name>"
+    pub fn parse(text: &str) -> Option<Subject> {
+        let text = text.trim();
+        match text.strip_prefix("family:") {
+            Some(family) => Family::parse(family).map(Subject::Family),
+            None if !text.is_empty() && !text.contains(char::is_whitespace) => {
+                Some(Subject::Plant(text.to_lowercase()))
+            }
+            None => None,
+        }
+    }
+
+    pub fn matches(&self, plant: &Plant) -> bool {
+        match self {
+            Subject::Plant(id) => plant.id == *id,
+            Subject::Family(family) => plant.family == *family,
+        }
+    }
+}
+
+impl fmt::Display for Subject {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Subject::Plant(id) => f.pad(id),
+            Subject::Family(family) => f.pad(&format!("{family} family")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    pub first: Subject,
+    pub second: Subject,
+    pub relation: Relation,
+    pub reason: String,
+}
+
+impl Rule {
+    // rules work both ways round
+    pub fn applies(&self, a: &Plant, b: &Plant) -> bool {
+        (self.first.matches(a) && self.second.matches(b))
+            || (self.first.matches(b) && self.second.matches(a))
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Rules {
+    rules: Vec<Rule>,
+}
+
+impl Rules {
+    pub fn new() -> Rules {
+        Rules { rules: Vec::new() }
+    }
+
+    // the usual folk wisdom, for what's in the built-in catalog
+    pub fn builtin() -> Rules {
+        Rules::parse(BUILTIN).expect("the built-in companion rules parse")
+    }
+
+    // reads rules in the data/companions.csv format
+    pub fn parse(text: &str) -> Result<Rules, DataError> {
+        let mut rules = Rules::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let rule = parse_rule(line).map_err(|reason| DataError::Parse {
+                line: index + 1,
+                reason,
+            })?;
+            rules.rules.push(rule);
+        }
+        Ok(rules)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Rules, DataError> {
+        Rules::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn add(&mut self, first: Subject, second: Subject, relation: Relation, reason: &str) {
+        self.rules.push(Rule {
+            first,
+            second,
+            relation,
+            reason: String::from(reason),
+        });
+    }
+
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    // every rule about a pair of plants
+    pub fn between<'a>(&'a self, a: &Plant, b: &Plant) -> impl Iterator<Item = &'a Rule> {
+        self.rules.iter().filter(move |rule| rule.applies(a, b))
+    }
+
+    // plants from the catalog that do well next to `plant`, with why.
+    // anything that is also harmful to it is left out
+    pub fn companions_for<'a>(
+        &'a self,
+        plant: &Plant,
+        catalog: &'a Catalog,
+    ) -> Vec<(&'a Plant, &'a str)> {
+        let mut companions = Vec::new();
+        for other in catalog.plants() {
+            if other.id == plant.id {
+                continue;
+            }
+            let rules: Vec<&Rule> = self.between(plant, other).collect();
+            if rules.iter().any(|rule| rule.relation == Relation::Harmful) {
+                continue;
+            }
+            if let Some(rule) = rules.first() {
+                companions.push((other, rule.reason.as_str()));
+            }
+        }
+        companions
+    }
+
+    // plants from the catalog to keep away from `plant`, with why
+    pub fn antagonists_for<'a>(
+        &'a self,
+        plant: &Plant,
+        catalog: &'a Catalog,
+    ) -> Vec<(&'a Plant, &'a str)> {
+        catalog
+            .plants()
+            .iter()
+            .filter(|other| other.id != plant.id)
+            .filter_map(|other| {
+                self.between(plant, other)
+                    .find(|rule| rule.relation == Relation::Harmful)
+                    .map(|rule| (other, rule.reason.as_str()))
+            })
+            .collect()
+    }
+}
+
+fn parse_rule(line: &str) -> Result<Rule, String> {
+    let fields: Vec<&str> = line.splitn(4, ',').map(str::trim).collect();
+    let [first, second, relation, reason] = fields[..] else {
+        return Err(format!("expected 4 fields, found {}", fields.len()));
+    };
+    let subject =
+        |text: &str| Subject::parse(text).ok_or(format!("'{text}' is not a plant id or family"));
+    if reason.is_empty() {
+        return Err(String::from("a rule needs a reason"));
+    }
+    Ok(Rule {
+        first: subject(first)?,
+        second: subject(second)?,
+        relation: Relation::parse(relation).ok_or(format!("unknown relation '{relation}'"))?,
+        reason: String::from(reason),
+    })
+}
+
+// a neighbour that one of the rules has something to say about
+#[derive(Debug, Clone, PartialEq)]
+pub struct Influence {
+    pub neighbour: u32,
+    pub relation: Relation,
+    pub reason: String,
+}
+
+// how well a planting gets on with what's around it
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlacementScore {
+    pub planting: u32,
+    pub score: i32,
+    pub influences: Vec<Influence>,
+}
+
+// two neighbours that shouldn't be together
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    pub first: u32,
+    pub second: u32,
+    pub distance_cm: f64,
+    pub explanation: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Analysis {
+    pub bed: String,
+    pub placements: Vec<PlacementScore>,
+    pub conflicts: Vec<Conflict>,
+}
+
+impl Analysis {
+    pub fn score(&self) -> i32 {
+        self.placements
+            .iter()
+            .map(|placement| placement.score)
+            .sum()
+    }
+
+    pub fn report(&self) -> String {
+        let mut text = format!("{} companion score {:+}\n", self.bed, self.score());
+        for placement in &self.placements {
+            write!(
+                text,
+                "  planting {} {:+}",
+                placement.planting, placement.score
+            )
+            .unwrap();
+            let neighbours: Vec<String> = placement
+                .influences
+                .iter()
+                .map(|influence| format!("{} {}", influence.relation, influence.neighbour))
+                .collect();
+            if !neighbours.is_empty() {
+                write!(text, " ({})", neighbours.join(", ")).unwrap();
+            }
+            text.push('\n');
+        }
+        if !self.conflicts.is_empty() {
+            text.push_str("conflicts:\n");
+            for conflict in &self.conflicts {
+                writeln!(text, "  {}", conflict.explanation).unwrap();
+            }
+        }
+        text
+    }
+}
+
+// scores every planting in a bed against its neighbours and lists the
+// pairs that are in conflict
+pub fn analyze(bed: &Bed, rules: &Rules) -> Analysis {
+    let plantings = bed.plantings();
+    let mut placements = Vec::new();
+    let mut conflicts = Vec::new();
+    for planting in plantings {
+        let mut placement = PlacementScore {
+            planting: planting.id,
+            score: 0,
+            influences: Vec::new(),
+        };
+        for neighbour in neighbours(planting, plantings) {
+            for rule in rules.between(&planting.plant, &neighbour.plant) {
+                placement.score += rule.relation.score();
+                placement.influences.push(Influence {
+                    neighbour: neighbour.id,
+                    relation: rule.relation,
+                    reason: rule.reason.clone(),
+                });
+                // each pair once, from the lower id
+                if rule.relation == Relation::Harmful && planting.id < neighbour.id {
+                    let distance_cm = planting.distance_cm(neighbour.x, neighbour.y);
+                    conflicts.push(Conflict {
+                        first: planting.id,
+                        second: neighbour.id,
+                        distance_cm,
+                        explanation: format!(
+                            "{} (planting {}) and {} (planting {}) are {distance_cm:.0} cm apart: {}",
+                            planting.plant.name,
+                            planting.id,
+                            neighbour.plant.name,
+                            neighbour.id,
+                            rule.reason
+                        ),
+                    });
+                }
+            }
+        }
+        placements.push(placement);
+    }
+    Analysis {
+        bed: bed.name.clone(),
+        placements,
+        conflicts,
+    }
+}
+
+fn neighbours<'a>(
+    planting: &'a Planting,
+    plantings: &'a [Planting],
+) -> impl Iterator<Item = &'a Planting> {
+    plantings.iter().filter(move |other| {
+        other.id != planting.id && other.is_near(planting.x, planting.y, NEIGHBOUR_CM + 1)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::garden::beds::Soil;
+
+    #[test]
+    fn test_rules_match_plants_and_families_either_way_round() {
+        let catalog = Catalog::builtin();
+        let rules = Rules::builtin();
+        let bean = catalog.get("bean").unwrap();
+        let garlic = catalog.get("garlic").unwrap();
+        let harmful: Vec<&Rule> = rules.between(garlic, bean).collect();
+        assert_eq!(harmful.len(), 1);
+        assert_eq!(harmful[0].relation, Relation::Harmful);
+        assert_eq!(rules.between(bean, catalog.get("beet").unwrap()).count(), 0);
+        assert_eq!(Subject::Family(Family::Legume).to_string(), "legume family");
+    }
+
+    #[test]
+    fn test_parse_rules() {
+        let rules = Rules::parse(
+            "# ours\n\
+             family:mint, cabbage, beneficial, mint keeps flea beetles off, mostly\n",
+        )
+        .unwrap();
+        assert_eq!(
+            rules.rules()[0],
+            Rule {
+                first: Subject::Family(Family::Mint),
+                second: Subject::Plant(String::from("cabbage")),
+                relation: Relation::Beneficial,
+                reason: String::from("mint keeps flea beetles off, mostly"),
+            }
+        );
+        let error = Rules::parse("\nfamily:rose, tomato, harmful, thorns").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 2: 'family:rose' is not a plant id or family"
+        );
+        let error = Rules::parse("tomato, basil, friendly, smells nice").unwrap_err();
+        assert_eq!(error.to_string(), "line 1: unknown relation 'friendly'");
+    }
+
+    #[test]
+    fn test_suggestions() {
+        let catalog = Catalog::builtin();
+        let rules = Rules::builtin();
+        let tomato = catalog.get("tomato").unwrap();
+
+        let companions: Vec<&str> = rules
+            .companions_for(tomato, &catalog)
+            .iter()
+            .map(|(plant, _)| plant.id.as_str())
+            .collect();
+        assert_eq!(companions, ["basil", "chives"]);
+
+        let antagonists: Vec<&str> = rules
+            .antagonists_for(tomato, &catalog)
+            .iter()
+            .map(|(plant, _)| plant.id.as_str())
+            .collect();
+        assert_eq!(
+            antagonists,
+            ["broccoli", "cabbage", "corn", "kale", "potato", "radish"]
+        );
+    }
+
+    #[test]
+    fn test_analyze_a_bed() {
+        let catalog = Catalog::builtin();
+        let mut bed = Bed::new("bed A", 200, 100, Soil::Loam).unwrap();
+        let tomato = bed.place(catalog.get("tomato").unwrap(), 3, 3).unwrap();
+        let basil = bed.place(catalog.get("basil").unwrap(), 8, 3).unwrap();
+        let bean = bed.place(catalog.get("bean").unwrap(), 12, 3).unwrap();
+        let onion = bed.place(catalog.get("onion").unwrap(), 12, 6).unwrap();
+        // well away from everything
+        bed.place(catalog.get("potato").unwrap(), 19, 9).unwrap();
+
+        let analysis = analyze(&bed, &Rules::builtin());
+        let scores: Vec<i32> = analysis
+            .placements
+            .iter()
+            .map(|placement| placement.score)
+            .collect();
+        assert_eq!(scores, [1, 1, -2, -2, 0]);
+        assert_eq!(analysis.score(), -2);
+
+        assert_eq!(analysis.conflicts.len(), 1);
+        let conflict = &analysis.conflicts[0];
+        assert_eq!((conflict.first, conflict.second), (bean, onion));
+        assert_eq!(
+            conflict.explanation,
+            "Bush Bean (planting 3) and Onion (planting 4) are 30 cm apart: \
+             onions and garlic stunt the growth of beans and peas"
+        );
+        assert_eq!(analysis.placements[0].influences[0].neighbour, basil);
+        assert_eq!(analysis.placements[1].influences[0].neighbour, tomato);
+
+        let report = analysis.report();
+        assert!(report.starts_with("bed A companion score -2\n  planting 1 +1 (beneficial 2)\n"));
+        assert!(report.ends_with("conflicts:\n  Bush Bean (planting 3) and Onion (planting 4) are 30 cm apart: onions and garlic stunt the growth of beans and peas\n"));
+    }
+}
@@ -0,0 +1,29 @@
+use std::fmt;
+use std::io;
+
+// what can go wrong reading one of our line-per-record data files:
+// the catalog, the saved garden, weather, harvests, pests, seeds,
+// companion rules and rotation history
+#[derive(Debug)]
+pub enum DataError {
+    Io(io::Error),
+    // line numbers start at 1
+    Parse { line: usize, reason: String },
+}
+
+impl fmt::Display for DataError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DataError::Io(error) => write!(f, "could not read the file: {error}"),
+            DataError::Parse { line, reason } => write!(f, "line {line}: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for DataError {}
+
+impl From<io::Error> for DataError {
+    fn from(error: io::Error) -> DataError {
+        DataError::Io(error)
+    }
+}
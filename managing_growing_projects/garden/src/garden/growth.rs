@@ -0,0 +1,444 @@
+use std::fmt::{self, Write};
+
+use crate::garden::beds::{Bed, Planting};
+use crate::garden::dates::Date;
+use crate::garden::plants::Plant;
+use crate::garden::weather::{Observation, Weather};
+
+// how long a crop stays worth picking, as a share of its days to maturity
+pub const HARVEST_WINDOW: f64 = 0.3;
+
+// frost tender crops need more warmth before they grow at all
+pub const WARM_BASE_C: f64 = 10.0;
+pub const COOL_BASE_C: f64 = 5.0;
+
+// nothing grows any faster above this
+pub const CEILING_C: f64 = 30.0;
+
+// days to maturity in the catalog assume days averaging this warm, and so
+// do we for any day we have no numbers for
+pub const TYPICAL_MEAN_C: f64 = 20.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Stage {
+    Germinating,
+    Vegetative,
+    Flowering,
+    Fruiting,
+    Ready,
+    Spent,
+}
+
+impl Stage {
+    pub const ALL: [Stage; 6] = [
+        Stage::Germinating,
+        Stage::Vegetative,
+        Stage::Flowering,
+        Stage::Fruiting,
+        Stage::Ready,
+        Stage::Spent,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Stage::Germinating => "germinating",
+            Stage::Vegetative => "vegetative",
+            Stage::Flowering => "flowering",
+            Stage::Fruiting => "fruiting",
+            Stage::Ready => "ready",
+            Stage::Spent => "spent",
+        }
+    }
+
+    // progress is how far along to maturity a plant is, 1.0 being ready.
+    // perennials are never spent, they just wait for next year
+    pub fn from_progress(progress: f64, perennial: bool) -> Stage {
+        match progress {
+            p if p < 0.1 => Stage::Germinating,
+            p if p < 0.5 => Stage::Vegetative,
+            p if p < 0.75 => Stage::Flowering,
+            p if p < 1.0 => Stage::Fruiting,
+            p if p < 1.0 + HARVEST_WINDOW || perennial => Stage::Ready,
+            _ => Stage::Spent,
+        }
+    }
+
+    pub fn is_growing(self) -> bool {
+        self != Stage::Spent
+    }
+
+    pub fn symbol(self) -> char {
+        match self {
+            Stage::Germinating => ',',
+            Stage::Vegetative => 'v',
+            Stage::Flowering => '*',
+            Stage::Fruiting => 'o',
+            Stage::Ready => '@',
+            Stage::Spent => 'x',
+        }
+    }
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+pub fn base_c(plant: &Plant) -> f64 {
+    if plant.is_tender() {
+        WARM_BASE_C
+    } else {
+        COOL_BASE_C
+    }
+}
+
+// the warmth a day gave above `base_c`, averaging the high and the low once
+// both are held between the base and the ceiling
+pub fn degree_days(observation: &Observation, base_c: f64) -> f64 {
+    let high = observation.max_c.clamp(base_c, CEILING_C);
+    let low = observation.min_c.clamp(base_c, CEILING_C);
+    (high + low) / 2.0 - base_c
+}
+
+fn typical_degree_days(base_c: f64) -> f64 {
+    TYPICAL_MEAN_C - base_c
+}
+
+pub fn degree_days_to_maturity(plant: &Plant) -> f64 {
+    f64::from(plant.days_to_maturity) * typical_degree_days(base_c(plant))
+}
+
+// running degree day totals over a stretch of weather, so a planting's
+// progress on any date is a subtraction. days missing from the weather, and
+// days before or after the stretch, count as typical ones
+#[derive(Debug, Clone, PartialEq)]
+pub struct Season {
+    start: Date,
+    // warmth before each day from the start, for cool and warm crops
+    cool: Vec<f64>,
+    warm: Vec<f64>,
+}
+
+impl Season {
+    pub fn new(weather: &Weather, from: Date, to: Date) -> Season {
+        let mut season = Season {
+            start: from,
+            cool: vec![0.0],
+            warm: vec![0.0],
+        };
+        let mut date = from;
+        while date <= to {
+            for (totals, base_c) in [
+                (&mut season.cool, COOL_BASE_C),
+                (&mut season.warm, WARM_BASE_C),
+            ] {
+                let today = weather
+                    .on(date)
+                    .map_or(typical_degree_days(base_c), |observation| {
+                        degree_days(observation, base_c)
+                    });
+                totals.push(totals[totals.len() - 1] + today);
+            }
+            date = date.add_days(1);
+        }
+        season
+    }
+
+    // from the first planting in any of the beds up to `to`
+    pub fn for_beds(beds: &[Bed], weather: &Weather, to: Date) -> Season {
+        let from = beds
+            .iter()
+            .flat_map(|bed| bed.plantings())
+            .filter_map(|planting| planting.planted)
+            .min()
+            .unwrap_or(to);
+        Season::new(weather, from, to)
+    }
+
+    // warmth a plant got on the days before `date`
+    fn total(&self, plant: &Plant, date: Date) -> f64 {
+        let (totals, base_c) = if plant.is_tender() {
+            (&self.warm, WARM_BASE_C)
+        } else {
+            (&self.cool, COOL_BASE_C)
+        };
+        let days = self.start.days_until(date);
+        let known = totals.len() as i64 - 1;
+        let typical = typical_degree_days(base_c);
+        if days < 0 {
+            days as f64 * typical
+        } else if days > known {
+            totals[known as usize] + (days - known) as f64 * typical
+        } else {
+            totals[days as usize]
+        }
+    }
+
+    // the warmth a plant got from `from` up to the day before `to`
+    pub fn degree_days(&self, plant: &Plant, from: Date, to: Date) -> f64 {
+        self.total(plant, to) - self.total(plant, from)
+    }
+
+    // how far to maturity a planting is on a date, 1.0 being ready. None if
+    // it isn't in the ground yet
+    pub fn progress(&self, planting: &Planting, date: Date) -> Option<f64> {
+        let planted = planting.planted.filter(|planted| *planted <= date)?;
+        let warmth = self.degree_days(&planting.plant, planted, date);
+        Some(warmth / degree_days_to_maturity(&planting.plant))
+    }
+
+    pub fn stage_on(&self, planting: &Planting, date: Date) -> Option<Stage> {
+        let progress = self.progress(planting, date)?;
+        Some(Stage::from_progress(
+            progress,
+            planting.plant.is_perennial(),
+        ))
+    }
+
+    // the first day the planting is worth picking. past the end of the
+    // weather every day counts, so this always ends
+    pub fn ready_date(&self, planting: &Planting) -> Option<Date> {
+        let mut date = planting.planted?;
+        while self.progress(planting, date)? < 1.0 {
+            date = date.add_days(1);
+        }
+        Some(date)
+    }
+
+    // what's worth picking on a date, bed by bed
+    pub fn ready_on<'a>(&self, beds: &'a [Bed], date: Date) -> Vec<(&'a Bed, &'a Planting)> {
+        beds.iter()
+            .flat_map(|bed| bed.plantings().iter().map(move |planting| (bed, planting)))
+            .filter(|(_, planting)| self.stage_on(planting, date) == Some(Stage::Ready))
+            .collect()
+    }
+
+    // the bed on one day: each planting drawn as its stage, with a key
+    pub fn frame(&self, bed: &Bed, date: Date) -> String {
+        let mut grid = vec![vec!['.'; bed.columns() as usize]; bed.rows() as usize];
+        let mut key = String::new();
+        for planting in bed.plantings() {
+            let (symbol, state) = match self.progress(planting, date) {
+                Some(progress) => {
+                    let stage = Stage::from_progress(progress, planting.plant.is_perennial());
+                    let percent = (progress * 100.0).min(999.0);
+                    (stage.symbol(), format!("{stage:<11} {percent:>3.0}%"))
+                }
+                None => ('_', "not planted".to_string()),
+            };
+            grid[planting.y as usize][planting.x as usize] = symbol;
+            writeln!(
+                key,
+                "{:>3} {symbol} {:<14} {state}",
+                planting.id, planting.plant.name
+            )
+            .unwrap();
+        }
+
+        let mut text = format!("{} on {date}\n", bed.name);
+        let border = format!("+{}+\n", "-".repeat(bed.columns() as usize));
+        text.push_str(&border);
+        for row in grid {
+            writeln!(text, "|{}|", row.into_iter().collect::<String>()).unwrap();
+        }
+        text.push_str(&border);
+        text.push_str(&key);
+        text
+    }
+
+    // frames every `step_days` from `from` to `to`, to page through a season
+    pub fn animate(&self, bed: &Bed, from: Date, to: Date, step_days: u32) -> Vec<String> {
+        let mut frames = Vec::new();
+        let mut date = from;
+        while date <= to {
+            frames.push(self.frame(bed, date));
+            date = date.add_days(i64::from(step_days.max(1)));
+        }
+        frames
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::garden::beds::Soil;
+    use crate::garden::plants::Catalog;
+
+    fn day(month: u8, day: u8) -> Date {
+        Date::new(2025, month, day).unwrap()
+    }
+
+    // the same weather every day for two months from May 1st
+    fn steady(min_c: f64, max_c: f64) -> Weather {
+        let mut weather = Weather::new();
+        for offset in 0..61 {
+            weather.add(Observation {
+                date: day(5, 1).add_days(offset),
+                min_c,
+                max_c,
+                rain_mm: 0.0,
+            });
+        }
+        weather
+    }
+
+    fn bed(plants: &[(&str, u32, u32)]) -> Bed {
+        let catalog = Catalog::builtin();
+        let mut bed = Bed::new("bed A", 100, 50, Soil::Loam).unwrap();
+        for (id, x, y) in plants {
+            let planting = bed.place(catalog.get(id).unwrap(), *x, *y).unwrap();
+            bed.plant_on(planting, day(5, 1)).unwrap();
+        }
+        bed
+    }
+
+    #[test]
+    fn test_stages_on_typical_days() {
+        let catalog = Catalog::builtin();
+        let mut bed = Bed::new("A", 100, 100, Soil::Loam).unwrap();
+        let radish = bed.place(catalog.get("radish").unwrap(), 1, 1).unwrap();
+        let planted = Date::new(2025, 5, 1).unwrap();
+        // with no weather every day is typical, so the catalog's days hold
+        let season = Season::new(&Weather::new(), planted, planted);
+        assert_eq!(
+            season.stage_on(bed.planting(radish).unwrap(), planted),
+            None
+        );
+        bed.plant_on(radish, planted).unwrap();
+
+        // 25 days to maturity and a week to pick them
+        let planting = bed.planting(radish).unwrap();
+        let stages: Vec<Option<Stage>> = [-1, 0, 3, 13, 19, 25, 32, 33]
+            .into_iter()
+            .map(|days| season.stage_on(planting, planted.add_days(days)))
+            .collect();
+        assert_eq!(
+            stages,
+            [
+                None,
+                Some(Stage::Germinating),
+                Some(Stage::Vegetative),
+                Some(Stage::Flowering),
+                Some(Stage::Fruiting),
+                Some(Stage::Ready),
+                Some(Stage::Ready),
+                Some(Stage::Spent),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_perennials_stay_ready() {
+        assert_eq!(Stage::from_progress(3.0, true), Stage::Ready);
+        assert_eq!(Stage::from_progress(3.0, false), Stage::Spent);
+    }
+
+    #[test]
+    fn test_degree_days() {
+        let observation = |min_c, max_c| Observation {
+            date: day(5, 1),
+            min_c,
+            max_c,
+            rain_mm: 0.0,
+        };
+        assert_eq!(degree_days(&observation(12.0, 28.0), WARM_BASE_C), 10.0);
+        assert_eq!(degree_days(&observation(12.0, 28.0), COOL_BASE_C), 15.0);
+        // the heat above 30C doesn't count, nor the cold below the base
+        assert_eq!(degree_days(&observation(25.0, 38.0), WARM_BASE_C), 17.5);
+        assert_eq!(degree_days(&observation(-2.0, 8.0), WARM_BASE_C), 0.0);
+        assert_eq!(degree_days(&observation(-2.0, 8.0), COOL_BASE_C), 1.5);
+
+        let catalog = Catalog::builtin();
+        assert_eq!(
+            degree_days_to_maturity(catalog.get("tomato").unwrap()),
+            850.0
+        );
+        assert_eq!(
+            degree_days_to_maturity(catalog.get("radish").unwrap()),
+            375.0
+        );
+    }
+
+    #[test]
+    fn test_warmth_sets_the_pace() {
+        let bed = bed(&[("tomato", 3, 2), ("lettuce", 8, 2)]);
+        let tomato = bed.planting(1).unwrap();
+        let lettuce = bed.planting(2).unwrap();
+
+        // typical days keep to the catalog
+        let typical = Season::new(&steady(12.0, 28.0), day(5, 1), day(6, 30));
+        assert_eq!(typical.ready_date(tomato), Some(day(7, 25)));
+        assert_eq!(typical.ready_date(lettuce), Some(day(6, 25)));
+
+        // a hot spell brings the tomato on by a month, a cold one holds
+        // back the lettuce
+        let hot = Season::new(&steady(20.0, 30.0), day(5, 1), day(6, 30));
+        assert_eq!(hot.ready_date(tomato), Some(day(6, 27)));
+        let cold = Season::new(&steady(4.0, 16.0), day(5, 1), day(6, 30));
+        assert_eq!(cold.ready_date(lettuce), Some(day(8, 3)));
+        assert_eq!(cold.stage_on(tomato, day(6, 30)), Some(Stage::Vegetative));
+
+        // before planting there's nothing to say
+        assert_eq!(typical.progress(tomato, day(4, 30)), None);
+        assert_eq!(typical.progress(tomato, day(5, 1)), Some(0.0));
+    }
+
+    #[test]
+    fn test_ready_on() {
+        let beds = [bed(&[
+            ("tomato", 3, 2),
+            ("lettuce", 8, 2),
+            ("radish", 0, 4),
+        ])];
+        let season = Season::for_beds(&beds, &steady(20.0, 30.0), day(6, 30));
+        let ready = |date| -> Vec<&str> {
+            season
+                .ready_on(&beds, date)
+                .into_iter()
+                .map(|(_, planting)| planting.plant.id.as_str())
+                .collect()
+        };
+        assert_eq!(ready(day(5, 15)), Vec::<&str>::new());
+        assert_eq!(ready(day(5, 25)), ["radish"]);
+        assert_eq!(ready(day(6, 28)), ["tomato"]);
+    }
+
+    #[test]
+    fn test_animate() {
+        let mut bed = bed(&[("tomato", 3, 2)]);
+        let radish = bed
+            .place(Catalog::builtin().get("radish").unwrap(), 8, 3)
+            .unwrap();
+        bed.plant_on(radish, day(5, 15)).unwrap();
+        let season = Season::new(&Weather::new(), day(5, 1), day(5, 1));
+        let frames = season.animate(&bed, day(5, 1), day(6, 30), 30);
+        assert_eq!(frames.len(), 3);
+        assert_eq!(
+            frames[0],
+            "bed A on 2025-05-01\n\
+             +----------+\n\
+             |..........|\n\
+             |..........|\n\
+             |...,......|\n\
+             |........_.|\n\
+             |..........|\n\
+             +----------+\n  \
+             1 , Tomato         germinating   0%\n  \
+             2 _ Radish         not planted\n"
+        );
+        assert_eq!(
+            frames[2],
+            "bed A on 2025-06-30\n\
+             +----------+\n\
+             |..........|\n\
+             |..........|\n\
+             |...*......|\n\
+             |........x.|\n\
+             |..........|\n\
+             +----------+\n  \
+             1 * Tomato         flowering    71%\n  \
+             2 x Radish         spent       184%\n"
+        );
+    }
+}
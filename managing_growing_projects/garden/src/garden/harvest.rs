@@ -0,0 +1,595 @@
+use std::fmt::{self, Write};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::garden::beds::{Bed, BedError};
+use crate::garden::data::DataError;
+use crate::garden::dates::Date;
+
+// what came off a plant: weighed, or counted for things like cabbages
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Amount {
+    Grams(u32),
+    Count(u32),
+}
+
+impl Amount {
+    // "850 g", "1.2 kg" or a plain count like "12"
+    pub fn parse(text: &str) -> Option<Amount> {
+        let text = text.trim().to_lowercase();
+        let amount = if let Some(kilos) = text.strip_suffix("kg") {
+            let kilos: f64 = kilos.trim().parse().ok()?;
+            if !kilos.is_finite() || kilos < 0.0 || kilos > f64::from(u32::MAX) / 1000.0 {
+                return None;
+            }
+            Amount::Grams((kilos * 1000.0).round() as u32)
+        } else if let Some(grams) = text.strip_suffix('g') {
+            Amount::Grams(grams.trim().parse().ok()?)
+        } else {
+            Amount::Count(text.parse().ok()?)
+        };
+        (amount != Amount::Grams(0) && amount != Amount::Count(0)).then_some(amount)
+    }
+}
+
+impl fmt::Display for Amount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Amount::Grams(grams) => write!(f, "{grams} g"),
+            Amount::Count(count) => write!(f, "{count}"),
+        }
+    }
+}
+
+// one picking. the plant's details are copied in so old seasons still
+// add up after the beds and the catalog have moved on
+#[derive(Debug, Clone, PartialEq)]
+pub struct Harvest {
+    pub date: Date,
+    pub bed: String,
+    pub planting: u32,
+    pub plant: String,
+    pub species: String,
+    pub variety: String,
+    // the room each plant was given
+    pub spacing_cm: u32,
+    pub amount: Amount,
+    pub notes: String,
+}
+
+impl Harvest {
+    // one line of the journal file, see Journal::parse
+    pub fn parse(line: &str) -> Result<Harvest, String> {
+        let fields: Vec<&str> = line.splitn(9, ',').map(str::trim).collect();
+        let [
+            date,
+            bed,
+            planting,
+            plant,
+            species,
+            variety,
+            spacing_cm,
+            amount,
+            notes,
+        ] = fields[..]
+        else {
+            return Err(format!("expected 9 fields, found {}", fields.len()));
+        };
+        if bed.is_empty() || plant.is_empty() {
+            return Err(String::from("the bed and plant can't be empty"));
+        }
+        Ok(Harvest {
+            date: Date::parse(date).ok_or(format!("'{date}' is not a YYYY-MM-DD date"))?,
+            bed: String::from(bed),
+            planting: planting
+                .parse()
+                .map_err(|_| format!("'{planting}' is not a planting number"))?,
+            plant: String::from(plant),
+            species: String::from(species),
+            variety: String::from(variety),
+            spacing_cm: spacing_cm
+                .parse()
+                .ok()
+                .filter(|spacing| *spacing > 0)
+                .ok_or(format!("'{spacing_cm}' is not a spacing in cm"))?,
+            amount: Amount::parse(amount).ok_or(format!("'{amount}' is not a weight or count"))?,
+            notes: String::from(notes),
+        })
+    }
+
+    pub fn to_csv(&self) -> String {
+        format!(
+            "{}, {}, {}, {}, {}, {}, {}, {}, {}",
+            self.date,
+            self.bed,
+            self.planting,
+            self.plant,
+            self.species,
+            self.variety,
+            self.spacing_cm,
+            self.amount,
+            self.notes
+        )
+    }
+
+    pub fn year(&self) -> i32 {
+        self.date.year()
+    }
+}
+
+// weights and counts added up separately, since they don't mix
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Yield {
+    pub grams: f64,
+    pub count: f64,
+}
+
+impl Yield {
+    pub fn add(&mut self, amount: Amount) {
+        match amount {
+            Amount::Grams(grams) => self.grams += f64::from(grams),
+            Amount::Count(count) => self.count += f64::from(count),
+        }
+    }
+
+    pub fn divide(self, by: f64) -> Yield {
+        if by <= 0.0 {
+            return Yield::default();
+        }
+        Yield {
+            grams: self.grams / by,
+            count: self.count / by,
+        }
+    }
+}
+
+impl fmt::Display for Yield {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if self.grams >= 1000.0 {
+            parts.push(format!("{:.1} kg", self.grams / 1000.0));
+        } else if self.grams > 0.0 {
+            parts.push(format!("{:.0} g", self.grams));
+        }
+        if self.count > 0.0 {
+            let places = if self.count.fract() == 0.0 { 0 } else { 1 };
+            parts.push(format!("{:.places$}", self.count));
+        }
+        if parts.is_empty() {
+            parts.push(String::from("-"));
+        }
+        f.pad(&parts.join(" + "))
+    }
+}
+
+// everything one planting gave in a season
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlantingYield {
+    pub year: i32,
+    pub bed: String,
+    pub planting: u32,
+    pub plant: String,
+    pub species: String,
+    pub variety: String,
+    pub spacing_cm: u32,
+    pub harvests: usize,
+    pub total: Yield,
+}
+
+// how a variety did in a season, over all the plants of it
+#[derive(Debug, Clone, PartialEq)]
+pub struct VarietyYield {
+    pub year: i32,
+    pub plant: String,
+    pub species: String,
+    pub variety: String,
+    pub plants: usize,
+    pub area_m2: f64,
+    pub harvests: usize,
+    pub total: Yield,
+}
+
+impl VarietyYield {
+    pub fn per_plant(&self) -> Yield {
+        self.total.divide(self.plants as f64)
+    }
+
+    pub fn per_m2(&self) -> Yield {
+        self.total.divide(self.area_m2)
+    }
+}
+
+// the harvest log, oldest first
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Journal {
+    harvests: Vec<Harvest>,
+}
+
+impl Journal {
+    pub fn new() -> Journal {
+        Journal {
+            harvests: Vec::new(),
+        }
+    }
+
+    // reads "date, bed, planting, plant, species, variety, spacing_cm,
+    // amount, notes" lines, ignoring blanks and # comments. the notes run
+    // to the end of the line and may have commas in them
+    pub fn parse(text: &str) -> Result<Journal, DataError> {
+        let mut journal = Journal::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let harvest = Harvest::parse(line).map_err(|reason| DataError::Parse {
+                line: index + 1,
+                reason,
+            })?;
+            journal.add(harvest);
+        }
+        Ok(journal)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Journal, DataError> {
+        Journal::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut text = String::from(
+            "# date, bed, planting, plant, species, variety, spacing_cm, amount, notes\n",
+        );
+        for harvest in &self.harvests {
+            writeln!(text, "{}", harvest.to_csv()).unwrap();
+        }
+        text
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_csv())
+    }
+
+    // keeps the log in date order, later entries for a day after earlier ones
+    pub fn add(&mut self, harvest: Harvest) {
+        let index = self
+            .harvests
+            .partition_point(|known| known.date <= harvest.date);
+        self.harvests.insert(index, harvest);
+    }
+
+    // notes a picking from a planting in a bed
+    pub fn log(
+        &mut self,
+        bed: &Bed,
+        planting: u32,
+        date: Date,
+        amount: Amount,
+        notes: &str,
+    ) -> Result<(), BedError> {
+        let planting = bed
+            .planting(planting)
+            .ok_or(BedError::UnknownPlanting(planting))?;
+        self.add(Harvest {
+            date,
+            bed: bed.name.clone(),
+            planting: planting.id,
+            plant: planting.plant.id.clone(),
+            species: planting.plant.species.clone(),
+            variety: planting.plant.variety.clone(),
+            spacing_cm: planting.plant.spacing_cm,
+            amount,
+            // one line per harvest in the file
+            notes: notes
+                .lines()
+                .collect::<Vec<_>>()
+                .join(" ")
+                .trim()
+                .to_string(),
+        });
+        Ok(())
+    }
+
+    pub fn harvests(&self) -> &[Harvest] {
+        &self.harvests
+    }
+
+    pub fn years(&self) -> Vec<i32> {
+        let mut years: Vec<i32> = self.harvests.iter().map(Harvest::year).collect();
+        years.dedup();
+        years
+    }
+
+    // totals for each planting, in the order they were first picked
+    pub fn by_planting(&self) -> Vec<PlantingYield> {
+        let mut yields: Vec<PlantingYield> = Vec::new();
+        for harvest in &self.harvests {
+            let known = yields.iter_mut().find(|known| {
+                known.year == harvest.year()
+                    && known.bed == harvest.bed
+                    && known.planting == harvest.planting
+            });
+            let entry = match known {
+                Some(entry) => entry,
+                None => {
+                    yields.push(PlantingYield {
+                        year: harvest.year(),
+                        bed: harvest.bed.clone(),
+                        planting: harvest.planting,
+                        plant: harvest.plant.clone(),
+                        species: harvest.species.clone(),
+                        variety: harvest.variety.clone(),
+                        spacing_cm: harvest.spacing_cm,
+                        harvests: 0,
+                        total: Yield::default(),
+                    });
+                    yields.last_mut().unwrap()
+                }
+            };
+            entry.harvests += 1;
+            entry.total.add(harvest.amount);
+        }
+        yields
+    }
+
+    // totals for each variety and season, by species then variety then year
+    pub fn by_variety(&self) -> Vec<VarietyYield> {
+        let mut yields: Vec<VarietyYield> = Vec::new();
+        for planting in self.by_planting() {
+            let known = yields.iter_mut().find(|known| {
+                known.year == planting.year
+                    && known.plant == planting.plant
+                    && known.variety == planting.variety
+            });
+            let entry = match known {
+                Some(entry) => entry,
+                None => {
+                    yields.push(VarietyYield {
+                        year: planting.year,
+                        plant: planting.plant.clone(),
+                        species: planting.species.clone(),
+                        variety: planting.variety.clone(),
+                        plants: 0,
+                        area_m2: 0.0,
+                        harvests: 0,
+                        total: Yield::default(),
+                    });
+                    yields.last_mut().unwrap()
+                }
+            };
+            entry.plants += 1;
+            entry.area_m2 += f64::from(planting.spacing_cm).powi(2) / 10_000.0;
+            entry.harvests += planting.harvests;
+            entry.total.grams += planting.total.grams;
+            entry.total.count += planting.total.count;
+        }
+        yields.sort_by(|a, b| {
+            (&a.species, &a.variety, a.year).cmp(&(&b.species, &b.variety, b.year))
+        });
+        yields
+    }
+
+    // varieties side by side within each species, the best yield per m2
+    // over all seasons first, so it's plain which ones earn their space
+    pub fn report(&self) -> String {
+        let yields = self.by_variety();
+        let mut text = String::new();
+        let mut species: Vec<&str> = yields.iter().map(|known| known.species.as_str()).collect();
+        species.dedup();
+        for name in species {
+            writeln!(text, "{name}").unwrap();
+            let rows: Vec<&VarietyYield> = yields
+                .iter()
+                .filter(|known| known.species == name)
+                .collect();
+            let mut varieties: Vec<(&str, Yield)> = Vec::new();
+            for row in &rows {
+                if varieties.iter().all(|(variety, _)| *variety != row.variety) {
+                    let (total, area_m2) = rows
+                        .iter()
+                        .filter(|other| other.variety == row.variety)
+                        .fold((Yield::default(), 0.0), |(mut total, area_m2), other| {
+                            total.grams += other.total.grams;
+                            total.count += other.total.count;
+                            (total, area_m2 + other.area_m2)
+                        });
+                    varieties.push((&row.variety, total.divide(area_m2)));
+                }
+            }
+            varieties.sort_by(|(_, a), (_, b)| {
+                b.grams
+                    .total_cmp(&a.grams)
+                    .then(b.count.total_cmp(&a.count))
+            });
+            for (variety, _) in varieties {
+                for row in rows.iter().filter(|row| row.variety == variety) {
+                    writeln!(
+                        text,
+                        "  {:<20} {} {:>3} plant(s) {:>10} total {:>10} each {:>10} per m2",
+                        row.variety,
+                        row.year,
+                        row.plants,
+                        row.total,
+                        row.per_plant(),
+                        row.per_m2()
+                    )
+                    .unwrap();
+                }
+            }
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::garden::beds::Soil;
+    use crate::garden::plants::Catalog;
+
+    fn day(year: i32, month: u8, day: u8) -> Date {
+        Date::new(year, month, day).unwrap()
+    }
+
+    // two seasons of tomatoes: Brandywine both years, Sungold the second
+    fn journal() -> Journal {
+        let catalog = Catalog::builtin();
+        let mut bed = Bed::new("south", 240, 120, Soil::Loam).unwrap();
+        let brandywine = bed.place(catalog.get("tomato").unwrap(), 3, 3).unwrap();
+        let sungold = bed
+            .place(catalog.get("cherry-tomato").unwrap(), 10, 3)
+            .unwrap();
+        let cabbage = bed.place(catalog.get("cabbage").unwrap(), 18, 6).unwrap();
+
+        let mut journal = Journal::new();
+        let grams = Amount::Grams;
+        journal
+            .log(&bed, brandywine, day(2024, 8, 1), grams(1200), "")
+            .unwrap();
+        journal
+            .log(
+                &bed,
+                brandywine,
+                day(2024, 8, 20),
+                grams(1800),
+                "split, after rain",
+            )
+            .unwrap();
+        journal
+            .log(&bed, brandywine, day(2025, 8, 5), grams(2100), "")
+            .unwrap();
+        journal
+            .log(&bed, sungold, day(2025, 7, 25), grams(2600), "sweet")
+            .unwrap();
+        journal
+            .log(&bed, cabbage, day(2025, 7, 1), Amount::Count(1), "")
+            .unwrap();
+        journal
+    }
+
+    #[test]
+    fn test_amounts() {
+        assert_eq!(Amount::parse("850 g"), Some(Amount::Grams(850)));
+        assert_eq!(Amount::parse("1.25kg"), Some(Amount::Grams(1250)));
+        assert_eq!(Amount::parse(" 12 "), Some(Amount::Count(12)));
+        assert_eq!(Amount::parse("0 g"), None);
+        assert_eq!(Amount::parse("-1 kg"), None);
+        assert_eq!(Amount::parse("lots"), None);
+        assert_eq!(Amount::Grams(850).to_string(), "850 g");
+    }
+
+    #[test]
+    fn test_log() {
+        let journal = journal();
+        let dates: Vec<Date> = journal
+            .harvests()
+            .iter()
+            .map(|harvest| harvest.date)
+            .collect();
+        assert_eq!(
+            dates,
+            [
+                day(2024, 8, 1),
+                day(2024, 8, 20),
+                day(2025, 7, 1),
+                day(2025, 7, 25),
+                day(2025, 8, 5)
+            ]
+        );
+        assert_eq!(journal.years(), [2024, 2025]);
+
+        let bed = Bed::new("south", 100, 100, Soil::Loam).unwrap();
+        let mut empty = Journal::new();
+        let logged = empty.log(&bed, 7, day(2025, 8, 1), Amount::Count(1), "");
+        assert!(matches!(logged, Err(BedError::UnknownPlanting(7))));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let journal = journal();
+        let text = journal.to_csv();
+        assert!(text.contains(
+            "2024-08-20, south, 1, tomato, Solanum lycopersicum, Brandywine, 60, 1800 g, split, after rain\n"
+        ));
+        assert_eq!(Journal::parse(&text).unwrap(), journal);
+
+        let error = Journal::parse("2025-08-01, south, 1, tomato, x, y, 60, heaps,").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 1: 'heaps' is not a weight or count"
+        );
+    }
+
+    #[test]
+    fn test_yields() {
+        let journal = journal();
+        let plantings = journal.by_planting();
+        assert_eq!(plantings.len(), 4);
+        assert_eq!(plantings[0].harvests, 2);
+        assert_eq!(plantings[0].total.grams, 3000.0);
+
+        let varieties = journal.by_variety();
+        let names: Vec<(&str, i32)> = varieties
+            .iter()
+            .map(|known| (known.variety.as_str(), known.year))
+            .collect();
+        assert_eq!(
+            names,
+            [
+                ("Golden Acre", 2025),
+                ("Brandywine", 2024),
+                ("Brandywine", 2025),
+                ("Sungold", 2025)
+            ]
+        );
+        // a 60 cm tomato takes 0.36 m2
+        assert_eq!(varieties[1].per_plant().grams, 3000.0);
+        assert!((varieties[1].per_m2().grams - 3000.0 / 0.36).abs() < 1e-9);
+        assert_eq!(varieties[0].per_plant().to_string(), "1");
+    }
+
+    #[test]
+    fn test_varieties_under_one_id_stay_apart() {
+        let catalog = Catalog::builtin();
+        let brandywine = catalog.get("tomato").unwrap();
+        // as a --plants file overriding tomato would give us
+        let mut roma = brandywine.clone();
+        roma.variety = String::from("Roma");
+        let mut bed = Bed::new("south", 240, 120, Soil::Loam).unwrap();
+        let first = bed.place(brandywine, 3, 3).unwrap();
+        let second = bed.place(&roma, 12, 3).unwrap();
+
+        let mut journal = Journal::new();
+        journal
+            .log(&bed, first, day(2025, 8, 1), Amount::Grams(1000), "")
+            .unwrap();
+        journal
+            .log(&bed, second, day(2025, 8, 1), Amount::Grams(400), "")
+            .unwrap();
+        let varieties: Vec<(String, f64)> = journal
+            .by_variety()
+            .into_iter()
+            .map(|known| (known.variety, known.total.grams))
+            .collect();
+        assert_eq!(
+            varieties,
+            [
+                (String::from("Brandywine"), 1000.0),
+                (String::from("Roma"), 400.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_report() {
+        assert_eq!(
+            journal().report(),
+            "Brassica oleracea\n  \
+             Golden Acre          2025   1 plant(s)          1 total          1 each        4.9 per m2\n\
+             Solanum lycopersicum\n  \
+             Sungold              2025   1 plant(s)     2.6 kg total     2.6 kg each    12.8 kg per m2\n  \
+             Brandywine           2024   1 plant(s)     3.0 kg total     3.0 kg each     8.3 kg per m2\n  \
+             Brandywine           2025   1 plant(s)     2.1 kg total     2.1 kg each     5.8 kg per m2\n"
+        );
+    }
+}
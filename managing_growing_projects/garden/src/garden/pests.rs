@@ -0,0 +1,771 @@
+use std::fmt::{self, Write};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::garden::beds::{Bed, Planting};
+use crate::garden::data::DataError;
+use crate::garden::dates::Date;
+use crate::garden::plants::{Family, Plant};
+
+// the problems we know about, see data/problems.csv for the format
+const BUILTIN: &str = include_str!("../../data/problems.csv");
+
+// sightings in a bed this close together are the same outbreak
+pub const OUTBREAK_DAYS: i64 = 30;
+
+// alerts are about what's been seen in the last two weeks
+pub const ALERT_DAYS: i64 = 14;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Kind {
+    Pest,
+    Disease,
+}
+
+impl Kind {
+    pub fn name(self) -> &'static str {
+        match self {
+            Kind::Pest => "pest",
+            Kind::Disease => "disease",
+        }
+    }
+
+    pub fn parse(text: &str) -> Option<Kind> {
+        [Kind::Pest, Kind::Disease]
+            .into_iter()
+            .find(|kind| kind.name().eq_ignore_ascii_case(text.trim()))
+    }
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Light,
+    Moderate,
+    Severe,
+}
+
+impl Severity {
+    pub const ALL: [Severity; 3] = [Severity::Light, Severity::Moderate, Severity::Severe];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Severity::Light => "light",
+            Severity::Moderate => "moderate",
+            Severity::Severe => "severe",
+        }
+    }
+
+    pub fn parse(text: &str) -> Option<Severity> {
+        Severity::ALL
+            .into_iter()
+            .find(|severity| severity.name().eq_ignore_ascii_case(text.trim()))
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Problem {
+    pub id: String,
+    pub name: String,
+    pub kind: Kind,
+    pub families: Vec<Family>,
+    // how far it gets from plant to plant on its own
+    pub reach_cm: u32,
+    // how long it waits in a bed's soil for its crops to come back
+    pub lingers_years: u32,
+    pub treatments: Vec<String>,
+}
+
+impl Problem {
+    pub fn affects(&self, plant: &Plant) -> bool {
+        self.families.contains(&plant.family)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Problems {
+    problems: Vec<Problem>,
+}
+
+impl Problems {
+    pub fn new() -> Problems {
+        Problems {
+            problems: Vec::new(),
+        }
+    }
+
+    pub fn builtin() -> Problems {
+        Problems::parse(BUILTIN).expect("the built-in problems parse")
+    }
+
+    // reads problems in the data/problems.csv format
+    pub fn parse(text: &str) -> Result<Problems, DataError> {
+        let mut problems = Problems::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let problem = parse_problem(line).map_err(|reason| DataError::Parse {
+                line: index + 1,
+                reason,
+            })?;
+            problems.add(problem);
+        }
+        Ok(problems)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Problems, DataError> {
+        Problems::parse(&fs::read_to_string(path)?)
+    }
+
+    // adds a problem, replacing any with the same id
+    pub fn add(&mut self, problem: Problem) {
+        match self
+            .problems
+            .iter_mut()
+            .find(|known| known.id == problem.id)
+        {
+            Some(known) => *known = problem,
+            None => self.problems.push(problem),
+        }
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Problem> {
+        self.problems
+            .iter()
+            .find(|problem| problem.id.eq_ignore_ascii_case(id.trim()))
+    }
+
+    pub fn problems(&self) -> &[Problem] {
+        &self.problems
+    }
+
+    // what to keep an eye out for on a plant
+    pub fn for_plant(&self, plant: &Plant) -> Vec<&Problem> {
+        self.problems
+            .iter()
+            .filter(|problem| problem.affects(plant))
+            .collect()
+    }
+}
+
+fn parse_problem(line: &str) -> Result<Problem, String> {
+    let fields: Vec<&str> = line.splitn(7, ',').map(str::trim).collect();
+    let [
+        id,
+        name,
+        kind,
+        families,
+        reach_cm,
+        lingers_years,
+        treatments,
+    ] = fields[..]
+    else {
+        return Err(format!("expected 7 fields, found {}", fields.len()));
+    };
+    if id.is_empty() || id.contains(char::is_whitespace) {
+        return Err(format!("'{id}' is not a problem id"));
+    }
+    let families = if families.eq_ignore_ascii_case("any") {
+        Family::ALL.to_vec()
+    } else {
+        families
+            .split_whitespace()
+            .map(|family| Family::parse(family).ok_or(format!("unknown family '{family}'")))
+            .collect::<Result<Vec<Family>, String>>()?
+    };
+    if families.is_empty() {
+        return Err(format!("{id} doesn't say which families it goes for"));
+    }
+    let number = |text: &str, what: &str| {
+        text.parse::<u32>()
+            .map_err(|_| format!("{what} '{text}' is not a whole number"))
+    };
+    Ok(Problem {
+        id: id.to_lowercase(),
+        name: String::from(name),
+        kind: Kind::parse(kind).ok_or(format!("'{kind}' is not pest or disease"))?,
+        families,
+        reach_cm: number(reach_cm, "reach")?,
+        lingers_years: number(lingers_years, "years in the soil")?,
+        treatments: treatments
+            .split(';')
+            .map(str::trim)
+            .filter(|treatment| !treatment.is_empty())
+            .map(String::from)
+            .collect(),
+    })
+}
+
+// a problem seen on a planting, or across a whole bed when planting is None
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sighting {
+    pub date: Date,
+    pub bed: String,
+    pub planting: Option<u32>,
+    pub problem: String,
+    pub severity: Severity,
+    pub notes: String,
+}
+
+// something done about a problem
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Treatment {
+    pub date: Date,
+    pub bed: String,
+    pub planting: Option<u32>,
+    pub problem: String,
+    pub action: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Alert {
+    // back in a bed after an earlier outbreak
+    Recurring {
+        bed: String,
+        problem: String,
+        seen: Date,
+        previous: Date,
+    },
+    // turned up on a planting after its neighbour had it
+    Spreading {
+        bed: String,
+        problem: String,
+        from: u32,
+        to: u32,
+    },
+    // neighbours in reach of a planting that has it, and could catch it
+    AtRisk {
+        bed: String,
+        problem: String,
+        source: u32,
+        plantings: Vec<u32>,
+    },
+}
+
+impl Alert {
+    pub fn bed(&self) -> &str {
+        match self {
+            Alert::Recurring { bed, .. }
+            | Alert::Spreading { bed, .. }
+            | Alert::AtRisk { bed, .. } => bed,
+        }
+    }
+}
+
+impl fmt::Display for Alert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Alert::Recurring {
+                bed,
+                problem,
+                seen,
+                previous,
+            } => write!(f, "{bed}: {problem} is back, seen {seen} after {previous}"),
+            Alert::Spreading {
+                bed,
+                problem,
+                from,
+                to,
+            } => write!(
+                f,
+                "{bed}: {problem} has spread from planting {from} to planting {to}"
+            ),
+            Alert::AtRisk {
+                bed,
+                problem,
+                source,
+                plantings,
+            } => {
+                let plantings: Vec<String> = plantings.iter().map(u32::to_string).collect();
+                write!(
+                    f,
+                    "{bed}: {problem} on planting {source} could reach planting(s) {}",
+                    plantings.join(", ")
+                )
+            }
+        }
+    }
+}
+
+// the bed, problem and planting a sighting is about, if they're all known
+fn locate<'a>(
+    sighting: &Sighting,
+    beds: &'a [Bed],
+    problems: &'a Problems,
+) -> Option<(&'a Bed, &'a Problem, &'a Planting)> {
+    let bed = beds.iter().find(|bed| bed.name == sighting.bed)?;
+    let problem = problems.get(&sighting.problem)?;
+    let planting = bed.planting(sighting.planting?)?;
+    Some((bed, problem, planting))
+}
+
+// what's been seen and done, oldest first
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Tracker {
+    sightings: Vec<Sighting>,
+    treatments: Vec<Treatment>,
+}
+
+impl Tracker {
+    pub fn new() -> Tracker {
+        Tracker {
+            sightings: Vec::new(),
+            treatments: Vec::new(),
+        }
+    }
+
+    // reads lines written by to_csv, ignoring blanks and # comments
+    pub fn parse(text: &str) -> Result<Tracker, DataError> {
+        let mut tracker = Tracker::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            tracker
+                .parse_line(line)
+                .map_err(|reason| DataError::Parse {
+                    line: index + 1,
+                    reason,
+                })?;
+        }
+        Ok(tracker)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Tracker, DataError> {
+        Tracker::parse(&fs::read_to_string(path)?)
+    }
+
+    // one of
+    //   seen, <date>, <bed>, <planting or ->, <problem>, <severity>, <notes>
+    //   treated, <date>, <bed>, <planting or ->, <problem>, <what was done>
+    // the notes and what was done may have commas in them
+    pub fn parse_line(&mut self, line: &str) -> Result<(), String> {
+        let fields: Vec<&str> = line.splitn(6, ',').map(str::trim).collect();
+        let [kind, date, bed, planting, problem, rest] = fields[..] else {
+            return Err(format!(
+                "expected at least 6 fields, found {}",
+                fields.len()
+            ));
+        };
+        let date = Date::parse(date).ok_or(format!("'{date}' is not a YYYY-MM-DD date"))?;
+        let planting = match planting {
+            "-" => None,
+            id => Some(
+                id.parse()
+                    .map_err(|_| format!("'{id}' is not a planting number"))?,
+            ),
+        };
+        let (bed, problem) = (String::from(bed), problem.to_lowercase());
+        match kind {
+            "seen" => {
+                let (severity, notes) = rest.split_once(',').unwrap_or((rest, ""));
+                let severity = Severity::parse(severity)
+                    .ok_or(format!("'{severity}' is not light, moderate or severe"))?;
+                self.spot(Sighting {
+                    date,
+                    bed,
+                    planting,
+                    problem,
+                    severity,
+                    notes: String::from(notes.trim()),
+                });
+            }
+            "treated" => self.treat(Treatment {
+                date,
+                bed,
+                planting,
+                problem,
+                action: String::from(rest),
+            }),
+            _ => return Err(format!("expected 'seen' or 'treated', found '{kind}'")),
+        }
+        Ok(())
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut text = String::from("# seen or treated, date, bed, planting, problem, ...\n");
+        let planting =
+            |planting: Option<u32>| planting.map_or(String::from("-"), |id| id.to_string());
+        for sighting in &self.sightings {
+            writeln!(
+                text,
+                "seen, {}, {}, {}, {}, {}, {}",
+                sighting.date,
+                sighting.bed,
+                planting(sighting.planting),
+                sighting.problem,
+                sighting.severity,
+                sighting.notes
+            )
+            .unwrap();
+        }
+        for treatment in &self.treatments {
+            writeln!(
+                text,
+                "treated, {}, {}, {}, {}, {}",
+                treatment.date,
+                treatment.bed,
+                planting(treatment.planting),
+                treatment.problem,
+                treatment.action
+            )
+            .unwrap();
+        }
+        text
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_csv())
+    }
+
+    pub fn spot(&mut self, sighting: Sighting) {
+        let index = self
+            .sightings
+            .partition_point(|known| known.date <= sighting.date);
+        self.sightings.insert(index, sighting);
+    }
+
+    pub fn treat(&mut self, treatment: Treatment) {
+        let index = self
+            .treatments
+            .partition_point(|known| known.date <= treatment.date);
+        self.treatments.insert(index, treatment);
+    }
+
+    pub fn sightings(&self) -> &[Sighting] {
+        &self.sightings
+    }
+
+    pub fn treatments(&self) -> &[Treatment] {
+        &self.treatments
+    }
+
+    // what was done about a problem in a bed, to see what worked last time
+    pub fn treatments_for(&self, bed: &str, problem: &str) -> Vec<&Treatment> {
+        self.treatments
+            .iter()
+            .filter(|treatment| treatment.bed == bed && treatment.problem == problem)
+            .collect()
+    }
+
+    // everything seen and done in a bed, by date
+    pub fn history(&self, bed: &str) -> String {
+        let mut lines: Vec<(Date, String)> = Vec::new();
+        let on = |planting: Option<u32>| {
+            planting.map_or(String::from("the whole bed"), |id| format!("planting {id}"))
+        };
+        for sighting in self.sightings.iter().filter(|sighting| sighting.bed == bed) {
+            let mut line = format!(
+                "{} seen     {} on {}, {}",
+                sighting.date,
+                sighting.problem,
+                on(sighting.planting),
+                sighting.severity
+            );
+            if !sighting.notes.is_empty() {
+                write!(line, ": {}", sighting.notes).unwrap();
+            }
+            lines.push((sighting.date, line));
+        }
+        for treatment in self
+            .treatments
+            .iter()
+            .filter(|treatment| treatment.bed == bed)
+        {
+            let line = format!(
+                "{} treated  {} on {}: {}",
+                treatment.date,
+                treatment.problem,
+                on(treatment.planting),
+                treatment.action
+            );
+            lines.push((treatment.date, line));
+        }
+        // stable, so a sighting comes before what was done about it that day
+        lines.sort_by_key(|(date, _)| *date);
+        let mut text = String::new();
+        for (_, line) in lines {
+            writeln!(text, "{line}").unwrap();
+        }
+        text
+    }
+
+    // problems back in a bed, spreading between neighbours, or close
+    // enough to spread, going by the last ALERT_DAYS of sightings
+    pub fn alerts(&self, beds: &[Bed], problems: &Problems, today: Date) -> Vec<Alert> {
+        let recent: Vec<&Sighting> = self
+            .sightings
+            .iter()
+            .filter(|sighting| {
+                sighting.date <= today && sighting.date.days_until(today) < ALERT_DAYS
+            })
+            .collect();
+        let mut alerts: Vec<Alert> = Vec::new();
+        let mut add = |alert: Alert| {
+            if !alerts.contains(&alert) {
+                alerts.push(alert);
+            }
+        };
+
+        // the latest sighting is enough to say it's back
+        let mut recurring: Vec<(&str, &str)> = Vec::new();
+        for sighting in recent.iter().rev() {
+            let key = (sighting.bed.as_str(), sighting.problem.as_str());
+            if recurring.contains(&key) {
+                continue;
+            }
+            if let Some(alert) = self.recurrence(sighting, problems) {
+                recurring.push(key);
+                add(alert);
+            }
+        }
+
+        for sighting in &recent {
+            let Some((bed, problem, planting)) = locate(sighting, beds, problems) else {
+                continue;
+            };
+            for from in self.spread_from(sighting, bed, problem, planting.id) {
+                add(Alert::Spreading {
+                    bed: bed.name.clone(),
+                    problem: problem.name.clone(),
+                    from,
+                    to: planting.id,
+                });
+            }
+        }
+
+        for sighting in &recent {
+            let Some((bed, problem, source)) = locate(sighting, beds, problems) else {
+                continue;
+            };
+            let plantings: Vec<u32> = bed
+                .plantings()
+                .iter()
+                .filter(|neighbour| {
+                    neighbour.id != source.id
+                        && problem.affects(&neighbour.plant)
+                        && source.is_near(neighbour.x, neighbour.y, problem.reach_cm + 1)
+                        && !self.has(&bed.name, neighbour.id, &problem.id, today)
+                })
+                .map(|neighbour| neighbour.id)
+                .collect();
+            if !plantings.is_empty() {
+                add(Alert::AtRisk {
+                    bed: bed.name.clone(),
+                    problem: problem.name.clone(),
+                    source: source.id,
+                    plantings,
+                });
+            }
+        }
+        alerts
+    }
+
+    // an earlier outbreak in the same bed the problem could have come back
+    // from: in an earlier season if it overwinters in the soil for long
+    // enough, or earlier in the same one
+    fn recurrence(&self, sighting: &Sighting, problems: &Problems) -> Option<Alert> {
+        let problem = problems.get(&sighting.problem);
+        let lingers_years = problem.map_or(0, |problem| problem.lingers_years as i32);
+        let previous = self
+            .sightings
+            .iter()
+            .filter(|earlier| {
+                earlier.bed == sighting.bed
+                    && earlier.problem == sighting.problem
+                    && earlier.date.days_until(sighting.date) >= OUTBREAK_DAYS
+                    && sighting.date.year() - earlier.date.year() <= lingers_years
+            })
+            .map(|earlier| earlier.date)
+            .max()?;
+        Some(Alert::Recurring {
+            bed: sighting.bed.clone(),
+            problem: problem.map_or(sighting.problem.clone(), |problem| problem.name.clone()),
+            seen: sighting.date,
+            previous,
+        })
+    }
+
+    // neighbours within reach that had the problem earlier in this outbreak
+    fn spread_from(&self, sighting: &Sighting, bed: &Bed, problem: &Problem, to: u32) -> Vec<u32> {
+        let target = bed.planting(to);
+        let mut sources = Vec::new();
+        for earlier in &self.sightings {
+            let Some(from) = earlier.planting else {
+                continue;
+            };
+            let days = earlier.date.days_until(sighting.date);
+            let near = bed
+                .planting(from)
+                .zip(target)
+                .is_some_and(|(source, target)| {
+                    source.is_near(target.x, target.y, problem.reach_cm + 1)
+                });
+            if earlier.bed == sighting.bed
+                && earlier.problem == sighting.problem
+                && from != to
+                && (1..=OUTBREAK_DAYS).contains(&days)
+                && near
+                && !sources.contains(&from)
+            {
+                sources.push(from);
+            }
+        }
+        sources
+    }
+
+    // whether a planting has been seen with a problem in the current outbreak
+    fn has(&self, bed: &str, planting: u32, problem: &str, today: Date) -> bool {
+        self.sightings.iter().any(|sighting| {
+            sighting.bed == bed
+                && sighting.planting == Some(planting)
+                && sighting.problem == problem
+                && sighting.date.days_until(today) <= OUTBREAK_DAYS
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::garden::beds::Soil;
+    use crate::garden::plants::Catalog;
+
+    fn day(year: i32, month: u8, day: u8) -> Date {
+        Date::new(year, month, day).unwrap()
+    }
+
+    fn seen(date: Date, planting: Option<u32>, problem: &str) -> Sighting {
+        Sighting {
+            date,
+            bed: String::from("A"),
+            planting,
+            problem: String::from(problem),
+            severity: Severity::Moderate,
+            notes: String::new(),
+        }
+    }
+
+    // tomato, pepper, basil and a cabbage, 50 to 60 cm apart
+    fn bed() -> Bed {
+        let catalog = Catalog::builtin();
+        let mut bed = Bed::new("A", 200, 100, Soil::Loam).unwrap();
+        for (id, x, y) in [
+            ("tomato", 3, 3),
+            ("pepper", 9, 3),
+            ("basil", 3, 8),
+            ("cabbage", 15, 3),
+        ] {
+            bed.place(catalog.get(id).unwrap(), x, y).unwrap();
+        }
+        bed
+    }
+
+    #[test]
+    fn test_builtin_problems() {
+        let problems = Problems::builtin();
+        let clubroot = problems.get("Clubroot").unwrap();
+        assert_eq!(clubroot.kind, Kind::Disease);
+        assert_eq!(clubroot.families, [Family::Brassica]);
+        assert_eq!(clubroot.lingers_years, 7);
+        assert_eq!(clubroot.treatments.len(), 3);
+
+        let catalog = Catalog::builtin();
+        let ids: Vec<&str> = problems
+            .for_plant(catalog.get("tomato").unwrap())
+            .into_iter()
+            .map(|problem| problem.id.as_str())
+            .collect();
+        assert_eq!(
+            ids,
+            [
+                "aphids",
+                "flea-beetle",
+                "slugs",
+                "late-blight",
+                "early-blight",
+                "damping-off"
+            ]
+        );
+
+        let error = Problems::parse("mites, Mites, pest, fruit, 10, 0, spray").unwrap_err();
+        assert_eq!(error.to_string(), "line 1: unknown family 'fruit'");
+    }
+
+    #[test]
+    fn test_alerts() {
+        let mut tracker = Tracker::new();
+        tracker.spot(seen(day(2024, 6, 10), None, "clubroot"));
+        tracker.spot(seen(day(2024, 6, 20), Some(1), "aphids"));
+        tracker.spot(seen(day(2025, 6, 1), Some(4), "clubroot"));
+        tracker.spot(seen(day(2025, 6, 5), Some(1), "aphids"));
+        tracker.spot(seen(day(2025, 6, 9), Some(2), "aphids"));
+
+        let alerts = tracker.alerts(&[bed()], &Problems::builtin(), day(2025, 6, 10));
+        let lines: Vec<String> = alerts.iter().map(Alert::to_string).collect();
+        // aphids don't overwinter in the bed, so last year's don't count
+        assert_eq!(
+            lines,
+            [
+                "A: Clubroot is back, seen 2025-06-01 after 2024-06-10",
+                "A: Aphids has spread from planting 1 to planting 2",
+                "A: Aphids on planting 1 could reach planting(s) 3",
+                "A: Aphids on planting 2 could reach planting(s) 4",
+            ]
+        );
+
+        // a month on it's all old news
+        assert_eq!(
+            tracker.alerts(&[bed()], &Problems::builtin(), day(2025, 7, 10)),
+            []
+        );
+    }
+
+    #[test]
+    fn test_round_trip_and_history() {
+        let mut tracker = Tracker::new();
+        tracker.spot(Sighting {
+            notes: String::from("under the leaves, mostly"),
+            ..seen(day(2025, 6, 5), Some(1), "aphids")
+        });
+        tracker.treat(Treatment {
+            date: day(2025, 6, 5),
+            bed: String::from("A"),
+            planting: Some(1),
+            problem: String::from("aphids"),
+            action: String::from("jet of water, then soap"),
+        });
+        tracker.spot(seen(day(2025, 6, 1), None, "slugs"));
+
+        let text = tracker.to_csv();
+        assert_eq!(Tracker::parse(&text).unwrap(), tracker);
+        assert_eq!(tracker.treatments_for("A", "aphids").len(), 1);
+        assert_eq!(
+            tracker.history("A"),
+            "2025-06-01 seen     slugs on the whole bed, moderate\n\
+             2025-06-05 seen     aphids on planting 1, moderate: under the leaves, mostly\n\
+             2025-06-05 treated  aphids on planting 1: jet of water, then soap\n"
+        );
+
+        let error = Tracker::parse("seen, 2025-06-01, A, -, slugs, awful").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 1: 'awful' is not light, moderate or severe"
+        );
+    }
+}
@@ -0,0 +1,561 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use crate::garden::data::DataError;
+
+// the catalog we ship with, see data/plants.csv for the format
+const BUILTIN: &str = include_str!("../../data/plants.csv");
+
+// botanical families, which is what crop rotation and pests care about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Family {
+    Allium,
+    Amaranth,
+    Asparagus,
+    Aster,
+    Brassica,
+    Cucurbit,
+    Grass,
+    Legume,
+    Mint,
+    Nightshade,
+    Umbellifer,
+}
+
+impl Family {
+    pub const ALL: [Family; 11] = [
+        Family::Allium,
+        Family::Amaranth,
+        Family::Asparagus,
+        Family::Aster,
+        Family::Brassica,
+        Family::Cucurbit,
+        Family::Grass,
+        Family::Legume,
+        Family::Mint,
+        Family::Nightshade,
+        Family::Umbellifer,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Family::Allium => "allium",
+            Family::Amaranth => "amaranth",
+            Family::Asparagus => "asparagus",
+            Family::Aster => "aster",
+            Family::Brassica => "brassica",
+            Family::Cucurbit => "cucurbit",
+            Family::Grass => "grass",
+            Family::Legume => "legume",
+            Family::Mint => "mint",
+            Family::Nightshade => "nightshade",
+            Family::Umbellifer => "umbellifer",
+        }
+    }
+
+    pub fn parse(text: &str) -> Option<Family> {
+        Family::ALL
+            .into_iter()
+            .find(|family| family.name().eq_ignore_ascii_case(text.trim()))
+    }
+}
+
+impl fmt::Display for Family {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Kind {
+    Vegetable,
+    Herb,
+}
+
+impl Kind {
+    pub const ALL: [Kind; 2] = [Kind::Vegetable, Kind::Herb];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Kind::Vegetable => "vegetable",
+            Kind::Herb => "herb",
+        }
+    }
+
+    pub fn parse(text: &str) -> Option<Kind> {
+        Kind::ALL
+            .into_iter()
+            .find(|kind| kind.name().eq_ignore_ascii_case(text.trim()))
+    }
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+// hours of direct sun a plant wants: full is 6 or more, partial 3 to 6
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Sun {
+    Full,
+    Partial,
+    Shade,
+}
+
+impl Sun {
+    pub const ALL: [Sun; 3] = [Sun::Full, Sun::Partial, Sun::Shade];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Sun::Full => "full",
+            Sun::Partial => "partial",
+            Sun::Shade => "shade",
+        }
+    }
+
+    pub fn parse(text: &str) -> Option<Sun> {
+        Sun::ALL
+            .into_iter()
+            .find(|sun| sun.name().eq_ignore_ascii_case(text.trim()))
+    }
+}
+
+impl fmt::Display for Sun {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Water {
+    Low,
+    Medium,
+    High,
+}
+
+impl Water {
+    pub const ALL: [Water; 3] = [Water::Low, Water::Medium, Water::High];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Water::Low => "low",
+            Water::Medium => "medium",
+            Water::High => "high",
+        }
+    }
+
+    pub fn parse(text: &str) -> Option<Water> {
+        Water::ALL
+            .into_iter()
+            .find(|water| water.name().eq_ignore_ascii_case(text.trim()))
+    }
+}
+
+impl fmt::Display for Water {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Lifecycle {
+    Annual,
+    Biennial,
+    Perennial,
+}
+
+impl Lifecycle {
+    pub const ALL: [Lifecycle; 3] = [Lifecycle::Annual, Lifecycle::Biennial, Lifecycle::Perennial];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Lifecycle::Annual => "annual",
+            Lifecycle::Biennial => "biennial",
+            Lifecycle::Perennial => "perennial",
+        }
+    }
+
+    pub fn parse(text: &str) -> Option<Lifecycle> {
+        Lifecycle::ALL
+            .into_iter()
+            .find(|lifecycle| lifecycle.name().eq_ignore_ascii_case(text.trim()))
+    }
+}
+
+impl fmt::Display for Lifecycle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+// the USDA hardiness zones a plant survives in, inclusive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Zones {
+    pub min: u8,
+    pub max: u8,
+}
+
+impl Zones {
+    pub fn new(min: u8, max: u8) -> Option<Zones> {
+        if (1..=13).contains(&min) && (min..=13).contains(&max) {
+            Some(Zones { min, max })
+        } else {
+            None
+        }
+    }
+
+    // parses "3-9", or a single zone like "10"
+    pub fn parse(text: &str) -> Option<Zones> {
+        let text = text.trim();
+        match text.split_once('-') {
+            Some((min, max)) => Zones::new(min.trim().parse().ok()?, max.trim().parse().ok()?),
+            None => {
+                let zone = text.parse().ok()?;
+                Zones::new(zone, zone)
+            }
+        }
+    }
+
+    pub fn contains(self, zone: u8) -> bool {
+        (self.min..=self.max).contains(&zone)
+    }
+}
+
+impl fmt::Display for Zones {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.min == self.max {
+            f.pad(&self.min.to_string())
+        } else {
+            f.pad(&format!("{}-{}", self.min, self.max))
+        }
+    }
+}
+
+// how a plant gets going, counted in weeks from the last spring frost;
+// negative weeks are before it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Sowing {
+    // sown where it will grow
+    Direct {
+        week: i32,
+    },
+    // started inside `weeks_before` the frost and planted out in `transplant_week`
+    Indoors {
+        weeks_before: u32,
+        transplant_week: i32,
+    },
+}
+
+impl Sowing {
+    // parses "direct -4" or "indoors 8 +1"
+    pub fn parse(text: &str) -> Option<Sowing> {
+        let words: Vec<&str> = text.split_whitespace().collect();
+        match words[..] {
+            [method, week] if method.eq_ignore_ascii_case("direct") => Some(Sowing::Direct {
+                week: week.parse().ok()?,
+            }),
+            [method, weeks_before, transplant_week] if method.eq_ignore_ascii_case("indoors") => {
+                Some(Sowing::Indoors {
+                    weeks_before: weeks_before.parse().ok()?,
+                    transplant_week: transplant_week.parse().ok()?,
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Sowing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Sowing::Direct { week } => write!(f, "direct {week:+}"),
+            Sowing::Indoors {
+                weeks_before,
+                transplant_week,
+            } => write!(f, "indoors {weeks_before} {transplant_week:+}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Plant {
+    // short key used everywhere else, e.g. "cherry-tomato"
+    pub id: String,
+    pub name: String,
+    pub species: String,
+    pub variety: String,
+    pub family: Family,
+    pub kind: Kind,
+    // distance to keep between plants, centre to centre
+    pub spacing_cm: u32,
+    // from sowing, or from transplanting for things usually started indoors
+    pub days_to_maturity: u32,
+    pub sun: Sun,
+    pub water: Water,
+    pub lifecycle: Lifecycle,
+    pub zones: Zones,
+    pub sowing: Sowing,
+    // days between sowings for a steady supply, if it's worth repeating
+    pub succession_days: Option<u32>,
+}
+
+impl Plant {
+    pub fn is_perennial(&self) -> bool {
+        self.lifecycle == Lifecycle::Perennial
+    }
+
+    pub fn grows_in(&self, zone: u8) -> bool {
+        self.zones.contains(zone)
+    }
+
+    // plants put out after the last frost can't take one
+    pub fn is_tender(&self) -> bool {
+        match self.sowing {
+            Sowing::Direct { week } => week > 0,
+            Sowing::Indoors {
+                transplant_week, ..
+            } => transplant_week > 0,
+        }
+    }
+}
+
+impl fmt::Display for Plant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} '{}' ({})", self.name, self.variety, self.species)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Catalog {
+    plants: Vec<Plant>,
+}
+
+impl Catalog {
+    pub fn new() -> Catalog {
+        Catalog { plants: Vec::new() }
+    }
+
+    // the common vegetables and herbs we know about out of the box
+    pub fn builtin() -> Catalog {
+        Catalog::parse(BUILTIN).expect("the built-in catalog parses")
+    }
+
+    // reads plants in the data/plants.csv format: comma separated, one
+    // plant per line, blank lines and lines starting with # ignored
+    pub fn parse(text: &str) -> Result<Catalog, DataError> {
+        let mut catalog = Catalog::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let plant = parse_plant(line).map_err(|reason| DataError::Parse {
+                line: index + 1,
+                reason,
+            })?;
+            catalog.add(plant);
+        }
+        Ok(catalog)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Catalog, DataError> {
+        Catalog::parse(&fs::read_to_string(path)?)
+    }
+
+    // adds a plant, replacing any plant with the same id
+    pub fn add(&mut self, plant: Plant) {
+        match self.plants.iter_mut().find(|known| known.id == plant.id) {
+            Some(known) => *known = plant,
+            None => self.plants.push(plant),
+        }
+    }
+
+    // adds every plant from another catalog, theirs winning on a clash
+    pub fn extend(&mut self, other: Catalog) {
+        for plant in other.plants {
+            self.add(plant);
+        }
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Plant> {
+        let id = id.trim();
+        self.plants
+            .iter()
+            .find(|plant| plant.id.eq_ignore_ascii_case(id))
+    }
+
+    pub fn plants(&self) -> &[Plant] {
+        &self.plants
+    }
+
+    pub fn by_family(&self, family: Family) -> impl Iterator<Item = &Plant> {
+        self.plants
+            .iter()
+            .filter(move |plant| plant.family == family)
+    }
+
+    // the plants whose zones take in `zone`. for an annual those are where it
+    // can be grown through a season, not where it lives through winter
+    pub fn for_zone(&self, zone: u8) -> impl Iterator<Item = &Plant> {
+        self.plants.iter().filter(move |plant| plant.grows_in(zone))
+    }
+
+    pub fn len(&self) -> usize {
+        self.plants.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.plants.is_empty()
+    }
+}
+
+fn parse_plant(line: &str) -> Result<Plant, String> {
+    let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+    let [
+        id,
+        name,
+        species,
+        variety,
+        family,
+        kind,
+        spacing,
+        days,
+        sun,
+        water,
+        lifecycle,
+        zones,
+        sowing,
+        succession,
+    ] = fields[..]
+    else {
+        return Err(format!("expected 14 fields, found {}", fields.len()));
+    };
+    if id.is_empty() || id.contains(char::is_whitespace) {
+        return Err(format!("'{id}' is not a usable plant id"));
+    }
+    Ok(Plant {
+        id: id.to_lowercase(),
+        name: String::from(name),
+        species: String::from(species),
+        variety: String::from(variety),
+        family: Family::parse(family).ok_or(format!("unknown family '{family}'"))?,
+        kind: Kind::parse(kind).ok_or(format!("unknown kind '{kind}'"))?,
+        spacing_cm: parse_positive(spacing, "spacing")?,
+        days_to_maturity: parse_positive(days, "days to maturity")?,
+        sun: Sun::parse(sun).ok_or(format!("unknown sun '{sun}'"))?,
+        water: Water::parse(water).ok_or(format!("unknown water '{water}'"))?,
+        lifecycle: Lifecycle::parse(lifecycle).ok_or(format!("unknown lifecycle '{lifecycle}'"))?,
+        zones: Zones::parse(zones).ok_or(format!("bad hardiness zones '{zones}'"))?,
+        sowing: Sowing::parse(sowing).ok_or(format!("bad sowing '{sowing}'"))?,
+        succession_days: match succession {
+            "-" => None,
+            days => Some(parse_positive(days, "succession days")?),
+        },
+    })
+}
+
+fn parse_positive(text: &str, what: &str) -> Result<u32, String> {
+    match text.parse() {
+        Ok(value) if value > 0 => Ok(value),
+        _ => Err(format!(
+            "{what} must be a positive whole number, not '{text}'"
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_catalog() {
+        let catalog = Catalog::builtin();
+        assert!(catalog.len() >= 25);
+
+        let tomato = catalog.get("Tomato").unwrap();
+        assert_eq!(tomato.family, Family::Nightshade);
+        assert_eq!(tomato.spacing_cm, 60);
+        assert_eq!(
+            tomato.to_string(),
+            "Tomato 'Brandywine' (Solanum lycopersicum)"
+        );
+
+        let asparagus = catalog.get("asparagus").unwrap();
+        assert!(asparagus.is_perennial());
+        assert!(asparagus.grows_in(5));
+        assert!(!asparagus.grows_in(10));
+
+        assert_eq!(catalog.by_family(Family::Legume).count(), 2);
+        assert!(catalog.for_zone(11).all(|plant| plant.zones.max >= 11));
+    }
+
+    #[test]
+    fn test_extend_from_a_data_file() {
+        let mut catalog = Catalog::builtin();
+        let before = catalog.len();
+        let extra = Catalog::parse(
+            "# our own seed saves\n\
+             \n\
+             tomato, Tomato, Solanum lycopersicum, Cherokee Purple, nightshade, vegetable, 60, 80, full, high, annual, 3-11, indoors 6 +2, -\n\
+             sorrel, Sorrel, Rumex acetosa, Common, amaranth, herb, 30, 60, partial, medium, perennial, 4, direct -2, 28\n",
+        )
+        .unwrap();
+        catalog.extend(extra);
+
+        assert_eq!(catalog.len(), before + 1);
+        assert_eq!(catalog.get("tomato").unwrap().variety, "Cherokee Purple");
+        let sorrel = catalog.get("sorrel").unwrap();
+        assert_eq!(sorrel.zones, Zones::new(4, 4).unwrap());
+        assert_eq!(sorrel.sowing, Sowing::Direct { week: -2 });
+        assert_eq!(sorrel.succession_days, Some(28));
+    }
+
+    #[test]
+    fn test_parse_errors_name_the_line() {
+        let error = Catalog::parse("# header\nokra, Okra, Abelmoschus esculentus\n").unwrap_err();
+        assert_eq!(error.to_string(), "line 2: expected 14 fields, found 3");
+
+        let error = Catalog::parse(
+            "okra, Okra, Abelmoschus esculentus, Clemson, mallow, vegetable, 30, 55, full, medium, annual, 5-11, direct +2, -",
+        )
+        .unwrap_err();
+        assert_eq!(error.to_string(), "line 1: unknown family 'mallow'");
+
+        let error = Catalog::parse(
+            "okra, Okra, Abelmoschus esculentus, Clemson, grass, vegetable, 0, 55, full, medium, annual, 5-11, direct +2, -",
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("spacing must be a positive"));
+
+        assert!(matches!(
+            Catalog::load("no/such/plants.csv"),
+            Err(DataError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn test_sowing() {
+        let sowing = Sowing::parse("indoors 8 -1").unwrap();
+        assert_eq!(
+            sowing,
+            Sowing::Indoors {
+                weeks_before: 8,
+                transplant_week: -1
+            }
+        );
+        assert_eq!(sowing.to_string(), "indoors 8 -1");
+        assert_eq!(Sowing::parse("direct 0").unwrap().to_string(), "direct +0");
+        assert_eq!(Sowing::parse("direct"), None);
+        assert_eq!(Sowing::parse("outdoors 3"), None);
+    }
+
+    #[test]
+    fn test_zones() {
+        assert_eq!(Zones::parse("3-9"), Zones::new(3, 9));
+        assert_eq!(Zones::parse("9-3"), None);
+        assert_eq!(Zones::parse("14"), None);
+        assert_eq!(Zones::new(10, 10).unwrap().to_string(), "10");
+    }
+}
@@ -0,0 +1,431 @@
+use std::fmt::{self, Write};
+use std::fs;
+use std::path::Path;
+
+use crate::garden::beds::Planting;
+use crate::garden::data::DataError;
+use crate::garden::plants::Family;
+
+// how many years to keep a family out of a bed unless told otherwise
+pub const DEFAULT_GAP_YEARS: i32 = 3;
+
+// a family that grew in a bed one year
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Record {
+    pub bed: String,
+    pub year: i32,
+    pub family: Family,
+}
+
+// a family back in a bed too soon
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    pub bed: String,
+    pub family: Family,
+    pub year: i32,
+    pub previous_year: i32,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} in {} after {} in {}, only {} year(s) apart",
+            self.bed,
+            self.family,
+            self.year,
+            self.family,
+            self.previous_year,
+            self.year - self.previous_year
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct History {
+    records: Vec<Record>,
+}
+
+impl History {
+    pub fn new() -> History {
+        History {
+            records: Vec::new(),
+        }
+    }
+
+    // reads "bed, year, family" lines, ignoring blanks and # comments
+    pub fn parse(text: &str) -> Result<History, DataError> {
+        let mut history = History::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let record = parse_record(line).map_err(|reason| DataError::Parse {
+                line: index + 1,
+                reason,
+            })?;
+            history.record(&record.bed, record.year, record.family);
+        }
+        Ok(history)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<History, DataError> {
+        History::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut text = String::from("# bed, year, family\n");
+        for record in &self.records {
+            writeln!(text, "{}, {}, {}", record.bed, record.year, record.family).unwrap();
+        }
+        text
+    }
+
+    // notes a family in a bed; recording it twice for a year does nothing
+    pub fn record(&mut self, bed: &str, year: i32, family: Family) {
+        let record = Record {
+            bed: String::from(bed),
+            year,
+            family,
+        };
+        if !self.records.contains(&record) {
+            self.records.push(record);
+        }
+    }
+
+    // notes a planting's family in its bed for the year it went in.
+    // perennials stay where they are, so they don't count towards rotation,
+    // and nothing's grown yet while a planting is only planned
+    pub fn record_planting(&mut self, bed: &str, planting: &Planting) {
+        if let Some(planted) = planting.planted
+            && !planting.plant.is_perennial()
+        {
+            self.record(bed, planted.year(), planting.plant.family);
+        }
+    }
+
+    pub fn records(&self) -> &[Record] {
+        &self.records
+    }
+
+    // the most recent year before `year` that a family grew in a bed
+    pub fn last_grown(&self, bed: &str, family: Family, year: i32) -> Option<i32> {
+        self.records
+            .iter()
+            .filter(|record| record.bed == bed && record.family == family && record.year < year)
+            .map(|record| record.year)
+            .max()
+    }
+
+    // the families in a bed in a given year
+    pub fn families(&self, bed: &str, year: i32) -> Vec<Family> {
+        let mut families: Vec<Family> = self
+            .records
+            .iter()
+            .filter(|record| record.bed == bed && record.year == year)
+            .map(|record| record.family)
+            .collect();
+        families.sort();
+        families
+    }
+
+    // beds in the order they first appear
+    pub fn beds(&self) -> Vec<&str> {
+        let mut beds: Vec<&str> = Vec::new();
+        for record in &self.records {
+            if !beds.contains(&record.bed.as_str()) {
+                beds.push(&record.bed);
+            }
+        }
+        beds
+    }
+}
+
+// "bed, year, family", as in a history file
+pub fn parse_record(line: &str) -> Result<Record, String> {
+    let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+    let [bed, year, family] = fields[..] else {
+        return Err(format!("expected 3 fields, found {}", fields.len()));
+    };
+    if bed.is_empty() {
+        return Err(String::from("the bed needs a name"));
+    }
+    Ok(Record {
+        bed: String::from(bed),
+        year: year
+            .parse()
+            .map_err(|_| format!("'{year}' is not a year"))?,
+        family: Family::parse(family).ok_or(format!("unknown family '{family}'"))?,
+    })
+}
+
+// the family that traditionally goes in the year before: brassicas feed
+// on the nitrogen legumes leave behind, fruiting crops follow the
+// leafy ones, roots go in after the hungry fruiting crops and legumes
+// build the soil back up after the roots
+pub fn predecessor(family: Family) -> Option<Family> {
+    match family {
+        Family::Brassica => Some(Family::Legume),
+        Family::Nightshade | Family::Cucurbit => Some(Family::Brassica),
+        Family::Umbellifer | Family::Allium | Family::Amaranth => Some(Family::Nightshade),
+        Family::Legume => Some(Family::Umbellifer),
+        _ => None,
+    }
+}
+
+// next year's bed for each family, and any that didn't fit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Proposal {
+    pub year: i32,
+    pub assignments: Vec<(String, Family)>,
+    pub unplaced: Vec<Family>,
+}
+
+impl Proposal {
+    pub fn bed_for(&self, family: Family) -> Option<&str> {
+        self.assignments
+            .iter()
+            .find(|(_, assigned)| *assigned == family)
+            .map(|(bed, _)| bed.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rotation {
+    // a family can go back in a bed this many years after it was last there
+    pub gap_years: i32,
+}
+
+impl Default for Rotation {
+    fn default() -> Rotation {
+        Rotation {
+            gap_years: DEFAULT_GAP_YEARS,
+        }
+    }
+}
+
+impl Rotation {
+    pub fn new(gap_years: i32) -> Rotation {
+        Rotation {
+            gap_years: gap_years.max(1),
+        }
+    }
+
+    pub fn allows(&self, history: &History, bed: &str, family: Family, year: i32) -> bool {
+        history
+            .last_grown(bed, family, year)
+            .is_none_or(|last| year - last >= self.gap_years)
+    }
+
+    // every time a family came back to a bed too soon
+    pub fn violations(&self, history: &History) -> Vec<Violation> {
+        let mut violations: Vec<Violation> = history
+            .records()
+            .iter()
+            .filter_map(|record| {
+                let previous_year = history.last_grown(&record.bed, record.family, record.year)?;
+                (record.year - previous_year < self.gap_years).then(|| Violation {
+                    bed: record.bed.clone(),
+                    family: record.family,
+                    year: record.year,
+                    previous_year,
+                })
+            })
+            .collect();
+        violations.sort_by(|a, b| (a.year, &a.bed, a.family).cmp(&(b.year, &b.bed, b.family)));
+        violations
+    }
+
+    // puts each family in its own bed for `year`, never breaking the
+    // rotation. beds that grew the family's predecessor last year come
+    // first, then those where it's been away longest. if there are more
+    // families than beds that will take them, the rest are unplaced
+    pub fn propose(
+        &self,
+        history: &History,
+        beds: &[&str],
+        families: &[Family],
+        year: i32,
+    ) -> Proposal {
+        let candidates: Vec<Vec<usize>> = families
+            .iter()
+            .map(|&family| {
+                let mut allowed: Vec<usize> = (0..beds.len())
+                    .filter(|&bed| self.allows(history, beds[bed], family, year))
+                    .collect();
+                allowed.sort_by_key(|&bed| {
+                    let follows = predecessor(family).is_some_and(|before| {
+                        history.families(beds[bed], year - 1).contains(&before)
+                    });
+                    let away = history
+                        .last_grown(beds[bed], family, year)
+                        .map_or(i32::MAX, |last| year - last);
+                    (!follows, std::cmp::Reverse(away), bed)
+                });
+                allowed
+            })
+            .collect();
+
+        // a bipartite matching by augmenting paths, so an early family
+        // taking a bed doesn't leave a later one with nowhere to go
+        let mut owner: Vec<Option<usize>> = vec![None; beds.len()];
+        for family in 0..families.len() {
+            let mut seen = vec![false; beds.len()];
+            augment(family, &candidates, &mut owner, &mut seen);
+        }
+
+        let mut assignments = Vec::new();
+        for (bed, owner) in owner.iter().enumerate() {
+            if let Some(family) = owner {
+                assignments.push((String::from(beds[bed]), families[*family]));
+            }
+        }
+        let unplaced = families
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| !owner.contains(&Some(*index)))
+            .map(|(_, family)| *family)
+            .collect();
+        Proposal {
+            year,
+            assignments,
+            unplaced,
+        }
+    }
+}
+
+fn augment(
+    family: usize,
+    candidates: &[Vec<usize>],
+    owner: &mut [Option<usize>],
+    seen: &mut [bool],
+) -> bool {
+    for &bed in &candidates[family] {
+        if seen[bed] {
+            continue;
+        }
+        seen[bed] = true;
+        let free = match owner[bed] {
+            None => true,
+            Some(other) => augment(other, candidates, owner, seen),
+        };
+        if free {
+            owner[bed] = Some(family);
+            return true;
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::garden::beds::{Bed, Soil};
+    use crate::garden::dates::Date;
+    use crate::garden::plants::Catalog;
+
+    fn history() -> History {
+        History::parse(
+            "# four beds, three years\n\
+             A, 2022, legume\n\
+             B, 2022, brassica\n\
+             C, 2022, nightshade\n\
+             D, 2022, umbellifer\n\
+             A, 2023, brassica\n\
+             B, 2023, nightshade\n\
+             C, 2023, nightshade\n\
+             D, 2023, legume\n\
+             A, 2024, nightshade\n\
+             B, 2024, umbellifer\n\
+             C, 2024, allium\n\
+             D, 2024, umbellifer\n",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse_and_write_history() {
+        let history = history();
+        assert_eq!(history.records().len(), 12);
+        assert_eq!(history.beds(), ["A", "B", "C", "D"]);
+        assert_eq!(History::parse(&history.to_csv()).unwrap(), history);
+        let error = History::parse("A, last year, legume").unwrap_err();
+        assert_eq!(error.to_string(), "line 1: 'last year' is not a year");
+    }
+
+    #[test]
+    fn test_violations() {
+        let violations = Rotation::default().violations(&history());
+        let text: Vec<String> = violations.iter().map(Violation::to_string).collect();
+        assert_eq!(
+            text,
+            [
+                "C: nightshade in 2023 after nightshade in 2022, only 1 year(s) apart",
+                "D: umbellifer in 2024 after umbellifer in 2022, only 2 year(s) apart",
+            ]
+        );
+        // a two year gap is fine with a shorter rotation
+        assert_eq!(Rotation::new(2).violations(&history()).len(), 1);
+        assert_eq!(Rotation::new(1).violations(&history()).len(), 0);
+    }
+
+    #[test]
+    fn test_propose_next_year() {
+        let history = history();
+        let rotation = Rotation::default();
+        let families = [
+            Family::Legume,
+            Family::Brassica,
+            Family::Nightshade,
+            Family::Umbellifer,
+        ];
+        let proposal = rotation.propose(&history, &history.beds(), &families, 2025);
+
+        assert!(proposal.unplaced.is_empty());
+        for (bed, family) in &proposal.assignments {
+            assert!(
+                rotation.allows(&history, bed, *family, 2025),
+                "{bed} {family}"
+            );
+        }
+        // legumes follow last year's umbellifers and umbellifers follow
+        // the nightshades; D is the only bed nightshades haven't been in
+        assert_eq!(proposal.bed_for(Family::Legume), Some("B"));
+        assert_eq!(proposal.bed_for(Family::Umbellifer), Some("A"));
+        assert_eq!(proposal.bed_for(Family::Nightshade), Some("D"));
+        assert_eq!(proposal.bed_for(Family::Brassica), Some("C"));
+    }
+
+    #[test]
+    fn test_propose_leaves_out_what_cannot_fit() {
+        let mut history = History::new();
+        history.record("A", 2024, Family::Nightshade);
+        history.record("B", 2024, Family::Nightshade);
+        let proposal = Rotation::default().propose(
+            &history,
+            &["A", "B"],
+            &[Family::Nightshade, Family::Legume],
+            2025,
+        );
+        assert_eq!(proposal.assignments, [(String::from("A"), Family::Legume)]);
+        assert_eq!(proposal.unplaced, [Family::Nightshade]);
+    }
+
+    #[test]
+    fn test_record_planting_skips_perennials_and_plans() {
+        let catalog = Catalog::builtin();
+        let mut bed = Bed::new("A", 200, 100, Soil::Loam).unwrap();
+        let planted = Date::new(2025, 5, 1).unwrap();
+        for (plant, x) in [("tomato", 3), ("pepper", 10), ("asparagus", 16)] {
+            let id = bed.place(catalog.get(plant).unwrap(), x, 6).unwrap();
+            bed.plant_on(id, planted).unwrap();
+        }
+        bed.place(catalog.get("bean").unwrap(), 10, 0).unwrap();
+        let mut history = History::new();
+        for planting in bed.plantings() {
+            history.record_planting(&bed.name, planting);
+        }
+        assert_eq!(history.families("A", 2025), [Family::Nightshade]);
+    }
+}
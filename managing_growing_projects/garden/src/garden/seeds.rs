@@ -0,0 +1,528 @@
+use std::error::Error;
+use std::fmt::{self, Write};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::garden::calendar::{Calendar, Task};
+use crate::garden::data::DataError;
+use crate::garden::dates::{Date, YEARS};
+use crate::garden::plants::Plant;
+
+// how long each species' seed keeps, see data/seed_life.csv
+const BUILTIN: &str = include_str!("../../data/seed_life.csv");
+
+// for species we have no figure for
+pub const DEFAULT_LIFE_YEARS: u32 = 2;
+
+// how well seed comes up fresh, and at the end of its shelf life. after
+// that it halves every year
+const FRESH: f64 = 0.9;
+const END_OF_LIFE: f64 = 0.7;
+
+// seed that comes up worse than this isn't worth sowing
+pub const DEAD_BELOW: f64 = 0.3;
+
+// however poor the seed, don't sow more than this for one plant
+const MAX_SEEDS_PER_PLANT: u32 = 5;
+
+// seed shelf life by species
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Viability {
+    lives: Vec<(String, u32)>,
+}
+
+impl Viability {
+    pub fn new() -> Viability {
+        Viability { lives: Vec::new() }
+    }
+
+    pub fn builtin() -> Viability {
+        Viability::parse(BUILTIN).expect("the built-in seed lives parse")
+    }
+
+    // reads "species, years" lines, ignoring blanks and # comments
+    pub fn parse(text: &str) -> Result<Viability, DataError> {
+        let mut viability = Viability::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let parsed = match line.split_once(',') {
+                Some((species, years)) if !species.trim().is_empty() => years
+                    .trim()
+                    .parse()
+                    .map(|years| (species.trim(), years))
+                    .map_err(|_| format!("'{}' is not a number of years", years.trim())),
+                _ => Err(String::from("expected a species and a number of years")),
+            };
+            let (species, years) = parsed.map_err(|reason| DataError::Parse {
+                line: index + 1,
+                reason,
+            })?;
+            viability.set(species, years);
+        }
+        Ok(viability)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Viability, DataError> {
+        Viability::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn set(&mut self, species: &str, years: u32) {
+        match self
+            .lives
+            .iter_mut()
+            .find(|(known, _)| known.eq_ignore_ascii_case(species))
+        {
+            Some((_, known)) => *known = years,
+            None => self.lives.push((String::from(species), years)),
+        }
+    }
+
+    pub fn life_years(&self, species: &str) -> u32 {
+        self.lives
+            .iter()
+            .find(|(known, _)| known.eq_ignore_ascii_case(species))
+            .map_or(DEFAULT_LIFE_YEARS, |(_, years)| *years)
+    }
+
+    // the share of seeds we can expect to come up, for seed `age` years old:
+    // slowly worse over its shelf life, then halving every year
+    pub fn germination(&self, species: &str, age: i32) -> f64 {
+        let life = self.life_years(species).max(1) as i32;
+        let age = age.max(0);
+        if age <= life {
+            FRESH - (FRESH - END_OF_LIFE) * f64::from(age) / f64::from(life)
+        } else {
+            END_OF_LIFE * 0.5f64.powi(age - life)
+        }
+    }
+
+    // enough to expect one plant to come up
+    pub fn seeds_per_plant(&self, species: &str, age: i32) -> u32 {
+        let seeds = (1.0 / self.germination(species, age)).ceil() as u32;
+        seeds.clamp(1, MAX_SEEDS_PER_PLANT)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Packet {
+    pub id: u32,
+    // the catalog id of what's in it
+    pub plant: String,
+    pub variety: String,
+    pub source: String,
+    // the year it was packed for
+    pub year: i32,
+    pub quantity: u32,
+}
+
+impl Packet {
+    pub fn age(&self, year: i32) -> i32 {
+        year - self.year
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SeedError {
+    // no packet with any seed left in it
+    OutOfSeed(String),
+    // seed left, but too old to come up
+    DeadSeed { plant: String, packed: i32 },
+    UnknownPacket(u32),
+}
+
+impl fmt::Display for SeedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SeedError::OutOfSeed(plant) => write!(f, "there's no {plant} seed left"),
+            SeedError::DeadSeed { plant, packed } => {
+                write!(f, "the {plant} seed left is from {packed} and likely dead")
+            }
+            SeedError::UnknownPacket(id) => write!(f, "there is no seed packet {id}"),
+        }
+    }
+}
+
+impl Error for SeedError {}
+
+// seeds taken out of packets for a sowing
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SeedUse {
+    // packet id and how many seeds came out of it
+    pub taken: Vec<(u32, u32)>,
+    // seeds we wanted and didn't have
+    pub short: u32,
+}
+
+impl SeedUse {
+    pub fn seeds(&self) -> u32 {
+        self.taken.iter().map(|(_, seeds)| seeds).sum()
+    }
+}
+
+// the packets in the seed tin
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Inventory {
+    packets: Vec<Packet>,
+    next_id: u32,
+}
+
+impl Default for Inventory {
+    fn default() -> Inventory {
+        Inventory::new()
+    }
+}
+
+impl Inventory {
+    pub fn new() -> Inventory {
+        Inventory {
+            packets: Vec::new(),
+            next_id: 1,
+        }
+    }
+
+    // reads lines written by to_csv, ignoring blanks and # comments
+    pub fn parse(text: &str) -> Result<Inventory, DataError> {
+        let mut inventory = Inventory::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            inventory
+                .parse_line(line)
+                .map_err(|reason| DataError::Parse {
+                    line: index + 1,
+                    reason,
+                })?;
+        }
+        Ok(inventory)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Inventory, DataError> {
+        Inventory::parse(&fs::read_to_string(path)?)
+    }
+
+    // "packet, <id>, <plant id>, <year>, <quantity>, <variety>, <source>",
+    // where the source may have commas in it
+    pub fn parse_line(&mut self, line: &str) -> Result<(), String> {
+        let fields: Vec<&str> = line.splitn(7, ',').map(str::trim).collect();
+        let ["packet", id, plant, year, quantity, variety, source] = fields[..] else {
+            return Err(String::from(
+                "expected packet, id, plant, year, quantity, variety, source",
+            ));
+        };
+        let id: u32 = id
+            .parse()
+            .map_err(|_| format!("'{id}' is not a packet number"))?;
+        if self.packet(id).is_some() {
+            return Err(format!("there are two packets numbered {id}"));
+        }
+        let next_id = id
+            .checked_add(1)
+            .ok_or(format!("packet number {id} is too large"))?;
+        self.packets.push(Packet {
+            id,
+            plant: plant.to_lowercase(),
+            variety: String::from(variety),
+            source: String::from(source),
+            year: year
+                .parse()
+                .ok()
+                .filter(|year| YEARS.contains(year))
+                .ok_or(format!("'{year}' is not a year"))?,
+            quantity: quantity
+                .parse()
+                .map_err(|_| format!("'{quantity}' is not a number of seeds"))?,
+        });
+        self.next_id = self.next_id.max(next_id);
+        Ok(())
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut text = String::from("# packet, id, plant, year, quantity, variety, source\n");
+        for packet in &self.packets {
+            writeln!(
+                text,
+                "packet, {}, {}, {}, {}, {}, {}",
+                packet.id,
+                packet.plant,
+                packet.year,
+                packet.quantity,
+                packet.variety,
+                packet.source
+            )
+            .unwrap();
+        }
+        text
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_csv())
+    }
+
+    // files a new packet of a plant's seed and returns its number, or
+    // None once the packet numbers have run out
+    pub fn add(&mut self, plant: &Plant, source: &str, year: i32, quantity: u32) -> Option<u32> {
+        let id = self.next_id;
+        self.next_id = id.checked_add(1)?;
+        self.packets.push(Packet {
+            id,
+            plant: plant.id.clone(),
+            variety: plant.variety.clone(),
+            source: String::from(source),
+            year,
+            quantity,
+        });
+        Some(id)
+    }
+
+    pub fn remove(&mut self, id: u32) -> Result<Packet, SeedError> {
+        let index = self
+            .packets
+            .iter()
+            .position(|packet| packet.id == id)
+            .ok_or(SeedError::UnknownPacket(id))?;
+        Ok(self.packets.remove(index))
+    }
+
+    pub fn packets(&self) -> &[Packet] {
+        &self.packets
+    }
+
+    pub fn packet(&self, id: u32) -> Option<&Packet> {
+        self.packets.iter().find(|packet| packet.id == id)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.packets.is_empty()
+    }
+
+    // a plant's packets with seed left, oldest first so it gets used up
+    pub fn for_plant(&self, plant: &Plant) -> Vec<&Packet> {
+        let mut packets: Vec<&Packet> = self
+            .packets
+            .iter()
+            .filter(|packet| packet.plant == plant.id && packet.quantity > 0)
+            .collect();
+        packets.sort_by_key(|packet| (packet.year, packet.id));
+        packets
+    }
+
+    // what's wrong with sowing a plant in a year, if anything
+    pub fn shortage(&self, plant: &Plant, viability: &Viability, year: i32) -> Option<SeedError> {
+        let packets = self.for_plant(plant);
+        let viable = |packet: &&Packet| {
+            viability.germination(&plant.species, packet.age(year)) >= DEAD_BELOW
+        };
+        if packets.iter().any(viable) {
+            return None;
+        }
+        Some(match packets.last() {
+            Some(newest) => SeedError::DeadSeed {
+                plant: plant.name.clone(),
+                packed: newest.year,
+            },
+            None => SeedError::OutOfSeed(plant.name.clone()),
+        })
+    }
+
+    // takes enough seed out of the oldest good packets to expect `plants`
+    // plants to come up, sowing older seed more thickly
+    pub fn sow(
+        &mut self,
+        plant: &Plant,
+        viability: &Viability,
+        year: i32,
+        plants: u32,
+    ) -> Result<SeedUse, SeedError> {
+        if let Some(error) = self.shortage(plant, viability, year) {
+            return Err(error);
+        }
+        let usable: Vec<u32> = self
+            .for_plant(plant)
+            .into_iter()
+            .filter(|packet| viability.germination(&plant.species, packet.age(year)) >= DEAD_BELOW)
+            .map(|packet| packet.id)
+            .collect();
+        let mut used = SeedUse::default();
+        let mut wanted = plants;
+        for id in usable {
+            if wanted == 0 {
+                break;
+            }
+            let packet = self
+                .packets
+                .iter_mut()
+                .find(|packet| packet.id == id)
+                .unwrap();
+            let per_plant = viability.seeds_per_plant(&plant.species, packet.age(year));
+            // whole plants' worth from each packet, or whatever's left in it
+            let plants_here = wanted.min(packet.quantity.div_ceil(per_plant));
+            let seeds = (plants_here * per_plant).min(packet.quantity);
+            packet.quantity -= seeds;
+            used.taken.push((id, seeds));
+            wanted -= plants_here;
+        }
+        used.short = wanted;
+        Ok(used)
+    }
+}
+
+// a sowing on the calendar we don't have good seed for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Warning {
+    pub date: Date,
+    pub summary: String,
+    pub problem: SeedError,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}: {}", self.date, self.summary, self.problem)
+    }
+}
+
+// the first sowing of each plant on the calendar that we're out of seed
+// for, or whose seed will likely be dead by then
+pub fn check(calendar: &Calendar, inventory: &Inventory, viability: &Viability) -> Vec<Warning> {
+    let mut warnings: Vec<Warning> = Vec::new();
+    let mut checked: Vec<&str> = Vec::new();
+    for event in calendar.events() {
+        if !matches!(event.task, Task::StartIndoors | Task::DirectSow)
+            || checked.contains(&event.plant.id.as_str())
+        {
+            continue;
+        }
+        checked.push(&event.plant.id);
+        if let Some(problem) = inventory.shortage(&event.plant, viability, event.date.year()) {
+            warnings.push(Warning {
+                date: event.date,
+                summary: event.summary(),
+                problem,
+            });
+        }
+    }
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::garden::calendar::FrostDates;
+    use crate::garden::plants::Catalog;
+
+    #[test]
+    fn test_germination_by_age() {
+        let viability = Viability::builtin();
+        assert_eq!(viability.life_years("Solanum lycopersicum"), 4);
+        assert_eq!(
+            viability.life_years("Triffidus horribilis"),
+            DEFAULT_LIFE_YEARS
+        );
+
+        // tomato seed keeps four years, onion seed only one
+        let tomato: Vec<u32> = (0..8)
+            .map(|age| (viability.germination("Solanum lycopersicum", age) * 100.0).round() as u32)
+            .collect();
+        assert_eq!(tomato, [90, 85, 80, 75, 70, 35, 18, 9]);
+        assert!(viability.germination("Allium cepa", 3) < DEAD_BELOW);
+
+        let sow = |age| viability.seeds_per_plant("Solanum lycopersicum", age);
+        assert_eq!([sow(0), sow(5), sow(6), sow(9)], [2, 3, 5, 5]);
+
+        let error = Viability::parse("Zea mays, two").unwrap_err();
+        assert_eq!(error.to_string(), "line 1: 'two' is not a number of years");
+    }
+
+    #[test]
+    fn test_sowing_uses_the_oldest_good_seed_first() {
+        let catalog = Catalog::builtin();
+        let tomato = catalog.get("tomato").unwrap();
+        let viability = Viability::builtin();
+        let mut inventory = Inventory::new();
+        let dead = inventory.add(tomato, "swap", 2015, 20).unwrap();
+        let old = inventory.add(tomato, "Baker Creek", 2020, 5).unwrap();
+        let fresh = inventory.add(tomato, "Baker Creek", 2025, 30).unwrap();
+
+        // 2020 seed is down to 35% by 2025 so three seeds a plant: all
+        // five go for two plants, then fresh seed at two a plant
+        let used = inventory.sow(tomato, &viability, 2025, 4).unwrap();
+        assert_eq!(used.taken, [(old, 5), (fresh, 4)]);
+        assert_eq!(used.short, 0);
+        assert_eq!(inventory.packet(dead).unwrap().quantity, 20);
+        assert_eq!(inventory.packet(fresh).unwrap().quantity, 26);
+
+        let used = inventory.sow(tomato, &viability, 2025, 20).unwrap();
+        assert_eq!((used.seeds(), used.short), (26, 7));
+        assert_eq!(
+            inventory.sow(tomato, &viability, 2025, 1),
+            Err(SeedError::DeadSeed {
+                plant: String::from("Tomato"),
+                packed: 2015
+            })
+        );
+        inventory.remove(dead).unwrap();
+        assert_eq!(
+            inventory
+                .sow(tomato, &viability, 2025, 1)
+                .unwrap_err()
+                .to_string(),
+            "there's no Tomato seed left"
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let catalog = Catalog::builtin();
+        let mut inventory = Inventory::new();
+        inventory.add(
+            catalog.get("basil").unwrap(),
+            "saved, from the 2023 plants",
+            2023,
+            200,
+        );
+        let text = inventory.to_csv();
+        assert_eq!(
+            text,
+            "# packet, id, plant, year, quantity, variety, source\n\
+             packet, 1, basil, 2023, 200, Genovese, saved, from the 2023 plants\n"
+        );
+        assert_eq!(Inventory::parse(&text).unwrap(), inventory);
+    }
+
+    #[test]
+    fn test_check_against_the_calendar() {
+        let catalog = Catalog::builtin();
+        let plants: Vec<&Plant> = ["tomato", "onion", "bean", "lettuce"]
+            .into_iter()
+            .map(|id| catalog.get(id).unwrap())
+            .collect();
+        let frost = FrostDates::new(
+            Date::new(2025, 4, 15).unwrap(),
+            Date::new(2025, 10, 15).unwrap(),
+        )
+        .unwrap();
+        let calendar = Calendar::build(frost, &plants);
+
+        let mut inventory = Inventory::new();
+        inventory.add(plants[0], "", 2024, 10);
+        inventory.add(plants[1], "", 2022, 100);
+        inventory.add(plants[2], "", 2025, 0);
+        inventory.add(plants[3], "", 2025, 500);
+        let warnings: Vec<String> = check(&calendar, &inventory, &Viability::builtin())
+            .iter()
+            .map(Warning::to_string)
+            .collect();
+        assert_eq!(
+            warnings,
+            [
+                "2025-02-04 Start Onion indoors: the Onion seed left is from 2022 and likely dead",
+                "2025-04-22 Sow Bush Bean: there's no Bush Bean seed left",
+            ]
+        );
+    }
+}
@@ -0,0 +1,309 @@
+use std::fmt::Write;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::garden::beds::{Bed, Planting, Soil};
+use crate::garden::data::DataError;
+use crate::garden::dates::Date;
+use crate::garden::harvest::{Harvest, Journal};
+use crate::garden::pests::Tracker;
+use crate::garden::plants::Catalog;
+use crate::garden::rotation::{self, History};
+use crate::garden::seeds::Inventory;
+
+// the beds, the harvest journal, the pest log, the seed tin and what's
+// grown where over the years, as kept between runs
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Garden {
+    pub beds: Vec<Bed>,
+    pub journal: Journal,
+    pub pests: Tracker,
+    pub seeds: Inventory,
+    pub rotation: History,
+}
+
+impl Garden {
+    pub fn new() -> Garden {
+        Garden {
+            beds: Vec::new(),
+            journal: Journal::new(),
+            pests: Tracker::new(),
+            seeds: Inventory::new(),
+            rotation: History::new(),
+        }
+    }
+
+    // reads lines of these kinds, ignoring blanks and # comments:
+    //   bed, <name>, <width cm>, <length cm>, <soil>, <next planting id>
+    //   planting, <bed>, <id>, <plant id>, <x>, <y>, <date planted or ->
+    //   harvest, <a harvest journal line>
+    //   seen, ... and treated, ... as written by the pest tracker
+    //   packet, ... as written by the seed inventory
+    //   grown, <bed>, <year>, <family>
+    // plants are looked up in the catalog by id
+    pub fn parse(text: &str, catalog: &Catalog) -> Result<Garden, DataError> {
+        let mut garden = Garden::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            garden
+                .parse_line(line, catalog)
+                .map_err(|reason| DataError::Parse {
+                    line: index + 1,
+                    reason,
+                })?;
+        }
+        Ok(garden)
+    }
+
+    pub fn load(path: impl AsRef<Path>, catalog: &Catalog) -> Result<Garden, DataError> {
+        Garden::parse(&fs::read_to_string(path)?, catalog)
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = String::from("# garden state, see Garden::parse for the format\n");
+        for bed in &self.beds {
+            writeln!(
+                text,
+                "bed, {}, {}, {}, {}, {}",
+                bed.name,
+                bed.width_cm(),
+                bed.length_cm(),
+                bed.soil,
+                bed.next_id()
+            )
+            .unwrap();
+            for planting in bed.plantings() {
+                let planted = planting
+                    .planted
+                    .map_or(String::from("-"), |date| date.to_string());
+                writeln!(
+                    text,
+                    "planting, {}, {}, {}, {}, {}, {}",
+                    bed.name, planting.id, planting.plant.id, planting.x, planting.y, planted
+                )
+                .unwrap();
+            }
+        }
+        for harvest in self.journal.harvests() {
+            writeln!(text, "harvest, {}", harvest.to_csv()).unwrap();
+        }
+        if !self.pests.sightings().is_empty() || !self.pests.treatments().is_empty() {
+            text.push_str(&self.pests.to_csv());
+        }
+        if !self.seeds.is_empty() {
+            text.push_str(&self.seeds.to_csv());
+        }
+        if !self.rotation.records().is_empty() {
+            text.push_str("# grown, bed, year, family\n");
+        }
+        for record in self.rotation.records() {
+            writeln!(
+                text,
+                "grown, {}, {}, {}",
+                record.bed, record.year, record.family
+            )
+            .unwrap();
+        }
+        text
+    }
+
+    // writes a temporary file next to the real one and renames it over, so
+    // a crash part way through leaves the old state whole
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let mut temporary = path.as_os_str().to_owned();
+        temporary.push(".tmp");
+        fs::write(&temporary, self.to_text())?;
+        fs::rename(&temporary, path)
+    }
+
+    // bed names are matched ignoring case
+    pub fn bed(&self, name: &str) -> Option<&Bed> {
+        self.beds
+            .iter()
+            .find(|bed| bed.name.eq_ignore_ascii_case(name))
+    }
+
+    pub fn bed_mut(&mut self, name: &str) -> Option<&mut Bed> {
+        self.beds
+            .iter_mut()
+            .find(|bed| bed.name.eq_ignore_ascii_case(name))
+    }
+
+    fn parse_line(&mut self, line: &str, catalog: &Catalog) -> Result<(), String> {
+        let (kind, rest) = line.split_once(',').ok_or(format!(
+            "expected a bed, planting or harvest line, found '{line}'"
+        ))?;
+        let number = |text: &str, what: &str| {
+            text.parse::<u32>()
+                .map_err(|_| format!("{what} '{text}' is not a whole number"))
+        };
+        match kind.trim() {
+            "bed" => {
+                let fields: Vec<&str> = rest.split(',').map(str::trim).collect();
+                let [name, width_cm, length_cm, soil, next_id] = fields[..] else {
+                    return Err(format!("a bed needs 5 fields, found {}", fields.len()));
+                };
+                if self.bed(name).is_some() {
+                    return Err(format!("there are two beds called '{name}'"));
+                }
+                let soil = Soil::parse(soil).ok_or(format!("unknown soil '{soil}'"))?;
+                let mut bed = Bed::new(
+                    name,
+                    number(width_cm, "width")?,
+                    number(length_cm, "length")?,
+                    soil,
+                )
+                .map_err(|error| error.to_string())?;
+                bed.reserve_ids(number(next_id, "next planting id")?)
+                    .map_err(|error| error.to_string())?;
+                self.beds.push(bed);
+            }
+            "planting" => {
+                let fields: Vec<&str> = rest.split(',').map(str::trim).collect();
+                let [bed, id, plant, x, y, planted] = fields[..] else {
+                    return Err(format!("a planting needs 6 fields, found {}", fields.len()));
+                };
+                let plant = catalog
+                    .get(plant)
+                    .ok_or(format!("'{plant}' is not in the catalog"))?;
+                let planted = match planted {
+                    "-" => None,
+                    date => Some(Date::parse(date).ok_or(format!("'{date}' is not a date"))?),
+                };
+                let planting = Planting {
+                    id: number(id, "planting")?,
+                    plant: plant.clone(),
+                    x: number(x, "x")?,
+                    y: number(y, "y")?,
+                    planted,
+                };
+                self.bed_mut(bed)
+                    .ok_or(format!("planting in '{bed}' before the bed"))?
+                    .restore(planting)
+                    .map_err(|error| error.to_string())?;
+            }
+            "harvest" => self.journal.add(Harvest::parse(rest.trim())?),
+            "seen" | "treated" => self.pests.parse_line(line)?,
+            "packet" => self.seeds.parse_line(line)?,
+            "grown" => {
+                let record = rotation::parse_record(rest)?;
+                self.rotation
+                    .record(&record.bed, record.year, record.family);
+            }
+            other => return Err(format!("unknown line kind '{other}'")),
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::garden::harvest::Amount;
+    use crate::garden::plants::Family;
+    use std::env;
+    use std::process;
+
+    #[test]
+    fn test_round_trip() {
+        let catalog = Catalog::builtin();
+        let mut garden = Garden::new();
+        let mut bed = Bed::new("South", 240, 120, Soil::Clay).unwrap();
+        let tomato = bed.place(catalog.get("tomato").unwrap(), 3, 3).unwrap();
+        let basil = bed.place(catalog.get("basil").unwrap(), 9, 3).unwrap();
+        bed.place(catalog.get("basil").unwrap(), 9, 8).unwrap();
+        bed.plant_on(tomato, Date::new(2025, 5, 10).unwrap())
+            .unwrap();
+        // a removed planting's id isn't handed out again after a reload
+        bed.remove(basil).unwrap();
+        garden
+            .journal
+            .log(
+                &bed,
+                tomato,
+                Date::new(2025, 8, 1).unwrap(),
+                Amount::Grams(900),
+                "first, ripe",
+            )
+            .unwrap();
+        garden.beds.push(bed);
+        garden
+            .pests
+            .parse_line("seen, 2025-07-02, South, 1, aphids, light, on the tips")
+            .unwrap();
+        garden
+            .seeds
+            .add(catalog.get("tomato").unwrap(), "saved", 2024, 40);
+        garden.rotation.record("South", 2025, Family::Nightshade);
+
+        let text = garden.to_text();
+        assert_eq!(
+            text,
+            "# garden state, see Garden::parse for the format\n\
+             bed, South, 240, 120, clay, 4\n\
+             planting, South, 1, tomato, 3, 3, 2025-05-10\n\
+             planting, South, 3, basil, 9, 8, -\n\
+             harvest, 2025-08-01, South, 1, tomato, Solanum lycopersicum, Brandywine, 60, 900 g, first, ripe\n\
+             # seen or treated, date, bed, planting, problem, ...\n\
+             seen, 2025-07-02, South, 1, aphids, light, on the tips\n\
+             # packet, id, plant, year, quantity, variety, source\n\
+             packet, 1, tomato, 2024, 40, Brandywine, saved\n\
+             # grown, bed, year, family\n\
+             grown, South, 2025, nightshade\n"
+        );
+        let mut loaded = Garden::parse(&text, &catalog).unwrap();
+        assert_eq!(loaded, garden);
+
+        let path = env::temp_dir().join(format!("garden-state-{}.txt", process::id()));
+        garden.save(&path).unwrap();
+        let saved = Garden::load(&path, &catalog);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(saved.unwrap(), garden);
+        let bed = loaded.bed_mut("south").unwrap();
+        assert_eq!(bed.place(catalog.get("basil").unwrap(), 20, 3), Ok(4));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let catalog = Catalog::builtin();
+        let error = |text| Garden::parse(text, &catalog).unwrap_err().to_string();
+        assert_eq!(
+            error("planting, A, 1, tomato, 3, 3, -"),
+            "line 1: planting in 'A' before the bed"
+        );
+        assert_eq!(
+            error("bed, A, 100, 100, loam, 1\nplanting, A, 1, triffid, 3, 3, -"),
+            "line 2: 'triffid' is not in the catalog"
+        );
+        assert_eq!(
+            error("bed, A, 100, 100, loam, 1\nplanting, A, 1, tomato, 30, 3, -"),
+            "line 2: (30, 3) is outside the bed"
+        );
+        assert_eq!(
+            error("bed, A, 100, 100, loam, 1\nplanting, A, 4294967295, tomato, 3, 3, -"),
+            "line 2: planting id 4294967295 is too large"
+        );
+        assert_eq!(
+            error("bed, B, 100, 100, loam, 4294967295"),
+            "line 1: planting id 4294967295 is too large"
+        );
+        assert_eq!(
+            error("bed, B, 100, 100, loam, 1\nplanting, B, 4294967294, radish, 1, 1, -"),
+            "line 2: planting id 4294967294 is too large"
+        );
+        assert_eq!(
+            error("packet, 4294967295, tomato, 2024, 10, Roma, swap"),
+            "line 1: packet number 4294967295 is too large"
+        );
+        assert_eq!(
+            error("packet, 1, tomato, -2147483648, 10, Roma, swap"),
+            "line 1: '-2147483648' is not a year"
+        );
+        assert_eq!(error("shed, A"), "line 1: unknown line kind 'shed'");
+    }
+}
@@ -0,0 +1,280 @@
+use std::fmt::Write;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::garden::beds::{Bed, CELL_CM};
+use crate::garden::plants::{Family, Plant};
+
+// pixels per centimetre unless asked otherwise, so a 2.4 m bed is 480 px
+pub const DEFAULT_SCALE: f64 = 2.0;
+
+// room around the drawing, between beds, and for each bed's title, in px
+const MARGIN: f64 = 20.0;
+const TITLE: f64 = 24.0;
+const LEGEND_LINE: f64 = 20.0;
+const FONT: &str = "font-family=\"sans-serif\" font-size=\"12\"";
+
+// a fill for each family, picked to tell neighbours apart on screen and
+// in greyscale print
+pub fn colour(family: Family) -> &'static str {
+    match family {
+        Family::Allium => "#9b59b6",
+        Family::Amaranth => "#c0392b",
+        Family::Asparagus => "#16a085",
+        Family::Aster => "#27ae60",
+        Family::Brassica => "#2980b9",
+        Family::Cucurbit => "#f39c12",
+        Family::Grass => "#d4ac0d",
+        Family::Legume => "#7dcea0",
+        Family::Mint => "#1abc9c",
+        Family::Nightshade => "#e74c3c",
+        Family::Umbellifer => "#e67e22",
+    }
+}
+
+// one bed drawn to scale, `scale` px to the cm
+pub fn bed_svg(bed: &Bed, scale: f64) -> String {
+    garden_svg(std::slice::from_ref(bed), scale)
+}
+
+// the beds one under the other, each planting a dot with its spacing as a
+// circle around it, coloured by family, and a legend of the families under
+// them all
+pub fn garden_svg(beds: &[Bed], scale: f64) -> String {
+    let width_cm = beds.iter().map(Bed::width_cm).max().unwrap_or(0);
+    let families = families(beds);
+    let beds_px: f64 = beds
+        .iter()
+        .map(|bed| TITLE + f64::from(bed.length_cm()) * scale + MARGIN)
+        .sum();
+    let width = (f64::from(width_cm) * scale).max(200.0) + 2.0 * MARGIN;
+    let height = MARGIN + beds_px + families.len() as f64 * LEGEND_LINE + MARGIN;
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n",
+        w = number(width),
+        h = number(height)
+    );
+    writeln!(
+        svg,
+        "<rect width=\"{}\" height=\"{}\" fill=\"white\"/>",
+        number(width),
+        number(height)
+    )
+    .unwrap();
+
+    let mut top = MARGIN;
+    for (index, bed) in beds.iter().enumerate() {
+        draw_bed(&mut svg, bed, index, top, scale);
+        top += TITLE + f64::from(bed.length_cm()) * scale + MARGIN;
+    }
+
+    for (family, plants) in &families {
+        let middle = top + LEGEND_LINE / 2.0;
+        writeln!(
+            svg,
+            "<circle cx=\"{}\" cy=\"{}\" r=\"6\" fill=\"{c}\" fill-opacity=\"0.35\" stroke=\"{c}\"/>",
+            number(MARGIN + 6.0),
+            number(middle),
+            c = colour(*family)
+        )
+        .unwrap();
+        let names: Vec<&str> = plants.iter().map(|plant| plant.name.as_str()).collect();
+        writeln!(
+            svg,
+            "<text x=\"{}\" y=\"{}\" {FONT} dominant-baseline=\"middle\">{}: {}</text>",
+            number(MARGIN + 18.0),
+            number(middle),
+            family,
+            escape(&names.join(", "))
+        )
+        .unwrap();
+        top += LEGEND_LINE;
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+pub fn save(beds: &[Bed], scale: f64, path: impl AsRef<Path>) -> io::Result<()> {
+    fs::write(path, garden_svg(beds, scale))
+}
+
+fn draw_bed(svg: &mut String, bed: &Bed, index: usize, top: f64, scale: f64) {
+    let (left, top_of_bed) = (MARGIN, top + TITLE);
+    let (width, length) = (
+        f64::from(bed.width_cm()) * scale,
+        f64::from(bed.length_cm()) * scale,
+    );
+    writeln!(
+        svg,
+        "<text x=\"{}\" y=\"{}\" {FONT} font-weight=\"bold\">{} ({} x {} cm, {})</text>",
+        number(left),
+        number(top + TITLE - 8.0),
+        escape(&bed.name),
+        bed.width_cm(),
+        bed.length_cm(),
+        bed.soil
+    )
+    .unwrap();
+    // spacing circles are cut off at the bed's edges
+    writeln!(
+        svg,
+        "<clipPath id=\"bed-{index}\"><rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"/></clipPath>",
+        number(left),
+        number(top_of_bed),
+        number(width),
+        number(length)
+    )
+    .unwrap();
+    writeln!(
+        svg,
+        "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"#e8dcc4\" stroke=\"#6e5a3c\" stroke-width=\"2\"/>",
+        number(left),
+        number(top_of_bed),
+        number(width),
+        number(length)
+    )
+    .unwrap();
+    writeln!(svg, "<g clip-path=\"url(#bed-{index})\">").unwrap();
+    for planting in bed.plantings() {
+        let (x, y) = centre(planting.x, planting.y);
+        writeln!(
+            svg,
+            "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{c}\" fill-opacity=\"0.35\" stroke=\"{c}\"/>",
+            number(left + x * scale),
+            number(top_of_bed + y * scale),
+            number(f64::from(planting.plant.spacing_cm) / 2.0 * scale),
+            c = colour(planting.plant.family)
+        )
+        .unwrap();
+    }
+    svg.push_str("</g>\n");
+    for planting in bed.plantings() {
+        let (x, y) = centre(planting.x, planting.y);
+        let (x, y) = (left + x * scale, top_of_bed + y * scale);
+        writeln!(
+            svg,
+            "<circle cx=\"{}\" cy=\"{}\" r=\"3\" fill=\"{}\"><title>{} {}</title></circle>",
+            number(x),
+            number(y),
+            colour(planting.plant.family),
+            planting.id,
+            escape(&planting.plant.to_string())
+        )
+        .unwrap();
+        writeln!(
+            svg,
+            "<text x=\"{}\" y=\"{}\" {FONT} text-anchor=\"middle\">{}</text>",
+            number(x),
+            number(y - 6.0),
+            escape(&planting.plant.name)
+        )
+        .unwrap();
+    }
+}
+
+// the middle of a cell, in cm from the bed's top left corner
+fn centre(x: u32, y: u32) -> (f64, f64) {
+    let half = f64::from(CELL_CM) / 2.0;
+    (f64::from(x * CELL_CM) + half, f64::from(y * CELL_CM) + half)
+}
+
+// the families planted, in the usual order, with their plants in the order
+// they first turn up
+fn families(beds: &[Bed]) -> Vec<(Family, Vec<&Plant>)> {
+    let mut families: Vec<(Family, Vec<&Plant>)> = Vec::new();
+    for planting in beds.iter().flat_map(|bed| bed.plantings()) {
+        let plant = &planting.plant;
+        match families
+            .iter_mut()
+            .find(|(family, _)| *family == plant.family)
+        {
+            Some((_, plants)) if plants.iter().any(|known| known.id == plant.id) => {}
+            Some((_, plants)) => plants.push(plant),
+            None => families.push((plant.family, vec![plant])),
+        }
+    }
+    families.sort_by_key(|(family, _)| *family);
+    families
+}
+
+// whole numbers without a fraction, anything else to a tenth
+fn number(value: f64) -> String {
+    let rounded = (value * 10.0).round() / 10.0;
+    if rounded.fract() == 0.0 {
+        format!("{rounded:.0}")
+    } else {
+        format!("{rounded:.1}")
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::garden::beds::Soil;
+    use crate::garden::plants::Catalog;
+    use crate::garden::test_support::assert_snapshot;
+
+    fn beds() -> Vec<Bed> {
+        let catalog = Catalog::builtin();
+        let plant = |id| catalog.get(id).unwrap();
+        let mut south = Bed::new("South & sunny", 240, 120, Soil::Clay).unwrap();
+        south.place(plant("tomato"), 3, 3).unwrap();
+        south.place(plant("tomato"), 3, 9).unwrap();
+        south.place(plant("basil"), 9, 6).unwrap();
+        south.place(plant("pepper"), 15, 3).unwrap();
+        south.place(plant("carrot"), 21, 1).unwrap();
+        let mut north = Bed::new("North", 120, 60, Soil::Loam).unwrap();
+        north.place(plant("cabbage"), 2, 2).unwrap();
+        north.place(plant("bean"), 7, 1).unwrap();
+        north.place(plant("bean"), 7, 4).unwrap();
+        north.place(plant("lettuce"), 10, 3).unwrap();
+        vec![south, north]
+    }
+
+    #[test]
+    fn test_bed_snapshot() {
+        assert_snapshot("bed.svg", &bed_svg(&beds()[0], DEFAULT_SCALE));
+    }
+
+    #[test]
+    fn test_garden_snapshot() {
+        assert_snapshot("garden.svg", &garden_svg(&beds(), 1.5));
+    }
+
+    #[test]
+    fn test_drawn_to_scale() {
+        let beds = beds();
+        let svg = bed_svg(&beds[1], 3.0);
+        // 120 x 60 cm at 3 px a cm, inside the margin and under the title
+        assert!(svg.contains("<rect x=\"20\" y=\"44\" width=\"360\" height=\"180\""));
+        // the cabbage in cell (2, 2) is 25 cm in, its 45 cm spacing a 22.5 cm radius
+        assert!(svg.contains("<circle cx=\"95\" cy=\"119\" r=\"67.5\" fill=\"#2980b9\""));
+        assert!(svg.contains(">legume: Bush Bean</text>"));
+        assert!(bed_svg(&beds[0], 1.0).contains(">South &amp; sunny (240 x 120 cm, clay)</text>"));
+    }
+
+    #[test]
+    fn test_every_family_has_its_own_colour() {
+        let mut colours: Vec<&str> = Family::ALL.into_iter().map(colour).collect();
+        colours.sort();
+        colours.dedup();
+        assert_eq!(colours.len(), Family::ALL.len());
+    }
+}
@@ -0,0 +1,20 @@
+// helpers shared by the tests of every module
+use std::path::PathBuf;
+use std::{env, fs};
+
+// compares `actual` against src/snapshots/<name> and fails if they differ.
+// after an intended change, run the tests with UPDATE_SNAPSHOTS=1 to write
+// the current output over the stored snapshots, then review the diff
+pub fn assert_snapshot(name: &str, actual: &str) {
+    let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "src", "snapshots", name]
+        .iter()
+        .collect();
+    if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, actual).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(&path)
+        .unwrap_or_else(|_| panic!("missing snapshot {name}, run with UPDATE_SNAPSHOTS=1"));
+    assert_eq!(actual, expected, "snapshot {name} changed");
+}
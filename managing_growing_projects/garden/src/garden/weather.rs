@@ -0,0 +1,157 @@
+use std::fs;
+use std::path::Path;
+
+use crate::garden::data::DataError;
+use crate::garden::dates::Date;
+
+// one day's weather, as read off our own station
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Observation {
+    pub date: Date,
+    pub min_c: f64,
+    pub max_c: f64,
+    pub rain_mm: f64,
+}
+
+impl Observation {
+    pub fn mean_c(&self) -> f64 {
+        (self.min_c + self.max_c) / 2.0
+    }
+}
+
+// daily observations, oldest first. there may be gaps
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Weather {
+    observations: Vec<Observation>,
+}
+
+impl Weather {
+    pub fn new() -> Weather {
+        Weather {
+            observations: Vec::new(),
+        }
+    }
+
+    // reads "date, min_c, max_c, rain_mm" lines. a header line starting
+    // with "date", blank lines and # comments are skipped
+    pub fn parse(text: &str) -> Result<Weather, DataError> {
+        let mut weather = Weather::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with("date") {
+                continue;
+            }
+            let observation = parse_observation(line).map_err(|reason| DataError::Parse {
+                line: index + 1,
+                reason,
+            })?;
+            weather.add(observation);
+        }
+        Ok(weather)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Weather, DataError> {
+        Weather::parse(&fs::read_to_string(path)?)
+    }
+
+    // adds a day, replacing anything already recorded for it
+    pub fn add(&mut self, observation: Observation) {
+        match self
+            .observations
+            .binary_search_by_key(&observation.date, |known| known.date)
+        {
+            Ok(index) => self.observations[index] = observation,
+            Err(index) => self.observations.insert(index, observation),
+        }
+    }
+
+    pub fn observations(&self) -> &[Observation] {
+        &self.observations
+    }
+
+    pub fn on(&self, date: Date) -> Option<&Observation> {
+        self.observations
+            .binary_search_by_key(&date, |known| known.date)
+            .ok()
+            .map(|index| &self.observations[index])
+    }
+
+    // days from `from` to `to` inclusive that we have numbers for
+    pub fn between(&self, from: Date, to: Date) -> impl Iterator<Item = &Observation> {
+        self.observations
+            .iter()
+            .filter(move |observation| observation.date >= from && observation.date <= to)
+    }
+
+    // rain over the `days` days ending on `date`
+    pub fn rain_mm(&self, date: Date, days: i64) -> f64 {
+        self.between(date.add_days(1 - days), date)
+            .map(|observation| observation.rain_mm)
+            .sum()
+    }
+}
+
+fn parse_observation(line: &str) -> Result<Observation, String> {
+    let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+    let [date, min_c, max_c, rain_mm] = fields[..] else {
+        return Err(format!("expected 4 fields, found {}", fields.len()));
+    };
+    let number = |text: &str, what: &str| {
+        text.parse::<f64>()
+            .ok()
+            .filter(|value| value.is_finite())
+            .ok_or(format!("{what} '{text}' is not a number"))
+    };
+    let observation = Observation {
+        date: Date::parse(date).ok_or(format!("'{date}' is not a YYYY-MM-DD date"))?,
+        min_c: number(min_c, "minimum")?,
+        max_c: number(max_c, "maximum")?,
+        rain_mm: number(rain_mm, "rain")?,
+    };
+    if observation.min_c > observation.max_c {
+        return Err(format!("the minimum {min_c} is above the maximum {max_c}"));
+    }
+    if observation.rain_mm < 0.0 {
+        return Err(format!("rain can't be negative, got {rain_mm}"));
+    }
+    Ok(observation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(day: u8) -> Date {
+        Date::new(2025, 6, day).unwrap()
+    }
+
+    #[test]
+    fn test_parse_and_query() {
+        let weather = Weather::parse(
+            "date, min_c, max_c, rain_mm\n\
+             2025-06-03, 12, 24.5, 0\n\
+             2025-06-01, 10, 20, 4.5\n\
+             2025-06-02, 11, 22, 12\n",
+        )
+        .unwrap();
+        let days: Vec<Date> = weather.observations().iter().map(|day| day.date).collect();
+        assert_eq!(days, [date(1), date(2), date(3)]);
+        assert_eq!(weather.on(date(3)).unwrap().mean_c(), 18.25);
+        assert_eq!(weather.on(date(4)), None);
+        assert_eq!(weather.rain_mm(date(3), 2), 12.0);
+        assert_eq!(weather.rain_mm(date(3), 7), 16.5);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = Weather::parse("2025-06-01, 10, 20").unwrap_err();
+        assert_eq!(error.to_string(), "line 1: expected 4 fields, found 3");
+        let error = Weather::parse("\n2025-06-01, 21, 20, 0").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 2: the minimum 21 is above the maximum 20"
+        );
+        let error = Weather::parse("2025-06-01, 10, 20, lots").unwrap_err();
+        assert_eq!(error.to_string(), "line 1: rain 'lots' is not a number");
+    }
+}
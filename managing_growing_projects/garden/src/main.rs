@@ -1,8 +1,97 @@
-use crate::garden::vegetables::Asparagus;
+use std::io::ErrorKind;
+use std::{env, process};
+
+use crate::garden::cli::{self, Context};
+use crate::garden::companions::Rules;
+use crate::garden::data::DataError;
+use crate::garden::dates::Date;
+use crate::garden::pests::Problems;
+use crate::garden::plants::Catalog;
+use crate::garden::seeds::Viability;
+use crate::garden::state::Garden;
+use crate::garden::weather::Weather;
 
 pub mod garden;
 
+const USAGE: &str = "\
+usage: garden [--state <file>] [--weather <csv>] [--plants <csv>] [--date YYYY-MM-DD] <command> [args..]
+       garden help   lists the commands";
+
+const DEFAULT_STATE: &str = "garden.txt";
+
+// runs one command against the garden kept in the state file, saving it
+// again if the command changed anything
 fn main() {
-    let plant = Asparagus {};
-    println!("I'm growing {plant:?}!");
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let state = option(&mut args, "--state").unwrap_or(String::from(DEFAULT_STATE));
+    let weather = option(&mut args, "--weather");
+    let plants = option(&mut args, "--plants");
+    let date = option(&mut args, "--date");
+    if args.first().is_some_and(|arg| arg.starts_with("--")) {
+        eprintln!("{USAGE}");
+        process::exit(2);
+    }
+
+    let mut catalog = Catalog::builtin();
+    if let Some(path) = &plants {
+        catalog.extend(or_exit(path, Catalog::load(path)));
+    }
+    let weather = match &weather {
+        Some(path) => or_exit(path, Weather::load(path)),
+        None => Weather::new(),
+    };
+    let today = match date {
+        Some(text) => Date::parse(&text).unwrap_or_else(|| {
+            eprintln!("error: '{text}' is not a YYYY-MM-DD date");
+            process::exit(2);
+        }),
+        None => Date::today(),
+    };
+    let mut garden = match Garden::load(&state, &catalog) {
+        Err(DataError::Io(error)) if error.kind() == ErrorKind::NotFound => Garden::new(),
+        loaded => or_exit(&state, loaded),
+    };
+
+    let context = Context {
+        catalog,
+        problems: Problems::builtin(),
+        viability: Viability::builtin(),
+        companions: Rules::builtin(),
+        weather,
+        today,
+    };
+    match cli::run(&mut garden, &context, &args) {
+        Ok(outcome) => {
+            print!("{}", outcome.text);
+            if outcome.changed
+                && let Err(error) = garden.save(&state)
+            {
+                eprintln!("{state}: could not save the garden: {error}");
+                process::exit(1);
+            }
+        }
+        Err(error) => {
+            eprintln!("error: {error}");
+            process::exit(1);
+        }
+    }
+}
+
+// takes "--name value" out of the arguments
+fn option(args: &mut Vec<String>, name: &str) -> Option<String> {
+    let index = args.iter().position(|arg| arg == name)?;
+    if index + 1 >= args.len() {
+        eprintln!("{USAGE}");
+        process::exit(2);
+    }
+    let value = args.remove(index + 1);
+    args.remove(index);
+    Some(value)
+}
+
+fn or_exit<T>(path: &str, result: Result<T, DataError>) -> T {
+    result.unwrap_or_else(|error| {
+        eprintln!("{path}: {error}");
+        process::exit(1);
+    })
 }
@@ -1,3 +1,6 @@
+pub mod kitchen;
+pub mod menu;
+
 pub struct Breakfast {
     pub toast: String,
     seasonal_fruit: String,
@@ -0,0 +1,139 @@
+use std::fmt;
+
+use crate::clock::Time;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Station {
+    Grill,
+    Fry,
+    Saute,
+    Cold,
+    Pastry,
+    Bar,
+}
+
+impl Station {
+    pub fn name(self) -> &'static str {
+        match self {
+            Station::Grill => "grill",
+            Station::Fry => "fry",
+            Station::Saute => "saute",
+            Station::Cold => "cold",
+            Station::Pastry => "pastry",
+            Station::Bar => "bar",
+        }
+    }
+}
+
+impl fmt::Display for Station {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TicketItem {
+    pub name: String,
+    pub quantity: u32,
+    pub modifiers: Vec<String>,
+    pub station: Station,
+}
+
+// one firing of a check; a check that orders dessert later gets a second ticket
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ticket {
+    pub id: u32,
+    pub check_id: u32,
+    pub table: u32,
+    pub fired_at: Time,
+    pub items: Vec<TicketItem>,
+    pub done: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct Kitchen {
+    tickets: Vec<Ticket>,
+    next_id: u32,
+}
+
+impl Kitchen {
+    pub fn new() -> Kitchen {
+        Kitchen {
+            tickets: Vec::new(),
+            next_id: 1,
+        }
+    }
+
+    // queues a ticket and returns its id
+    pub fn fire(
+        &mut self,
+        check_id: u32,
+        table: u32,
+        fired_at: Time,
+        items: Vec<TicketItem>,
+    ) -> u32 {
+        let id = self.next_id.max(1);
+        self.next_id = id + 1;
+        self.tickets.push(Ticket {
+            id,
+            check_id,
+            table,
+            fired_at,
+            items,
+            done: false,
+        });
+        id
+    }
+
+    // marks a ticket as sent out of the kitchen, returns false if unknown
+    pub fn bump(&mut self, ticket_id: u32) -> bool {
+        match self
+            .tickets
+            .iter_mut()
+            .find(|ticket| ticket.id == ticket_id)
+        {
+            Some(ticket) => {
+                ticket.done = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn ticket(&self, ticket_id: u32) -> Option<&Ticket> {
+        self.tickets.iter().find(|ticket| ticket.id == ticket_id)
+    }
+
+    pub fn open_tickets(&self) -> impl Iterator<Item = &Ticket> {
+        self.tickets.iter().filter(|ticket| !ticket.done)
+    }
+
+    pub fn tickets(&self) -> &[Ticket] {
+        &self.tickets
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fire_and_bump() {
+        let mut kitchen = Kitchen::new();
+        let item = TicketItem {
+            name: String::from("Cheeseburger"),
+            quantity: 1,
+            modifiers: vec![],
+            station: Station::Grill,
+        };
+        let first = kitchen.fire(1, 4, Time::new(18, 0).unwrap(), vec![item.clone()]);
+        let second = kitchen.fire(2, 5, Time::new(18, 2).unwrap(), vec![item]);
+        assert_ne!(first, second);
+        assert_eq!(kitchen.open_tickets().count(), 2);
+
+        assert!(kitchen.bump(first));
+        assert!(!kitchen.bump(99));
+        let open: Vec<u32> = kitchen.open_tickets().map(|ticket| ticket.id).collect();
+        assert_eq!(open, vec![second]);
+    }
+}
@@ -0,0 +1,208 @@
+use std::fmt;
+
+use crate::back_of_house::kitchen::Station;
+use crate::money::Money;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Category {
+    Appetizer,
+    Entree,
+    Side,
+    Dessert,
+    Drink,
+}
+
+impl Category {
+    pub const ALL: [Category; 5] = [
+        Category::Appetizer,
+        Category::Entree,
+        Category::Side,
+        Category::Dessert,
+        Category::Drink,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Category::Appetizer => "appetizer",
+            Category::Entree => "entree",
+            Category::Side => "side",
+            Category::Dessert => "dessert",
+            Category::Drink => "drink",
+        }
+    }
+
+    pub fn parse(text: &str) -> Option<Category> {
+        Category::ALL
+            .into_iter()
+            .find(|category| category.name().eq_ignore_ascii_case(text.trim()))
+    }
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MenuItem {
+    // short code staff type at the terminal, e.g. "burger"
+    pub code: String,
+    pub name: String,
+    pub category: Category,
+    pub price: Money,
+    // where the kitchen prepares it
+    pub station: Station,
+}
+
+impl MenuItem {
+    pub fn new(
+        code: &str,
+        name: &str,
+        category: Category,
+        cents: i64,
+        station: Station,
+    ) -> MenuItem {
+        MenuItem {
+            code: String::from(code),
+            name: String::from(name),
+            category,
+            price: Money::from_cents(cents),
+            station,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Menu {
+    items: Vec<MenuItem>,
+}
+
+impl Menu {
+    pub fn new() -> Menu {
+        Menu { items: Vec::new() }
+    }
+
+    // the menu we actually serve
+    pub fn house() -> Menu {
+        let mut menu = Menu::new();
+        for item in [
+            MenuItem::new(
+                "soup",
+                "Soup of the Day",
+                Category::Appetizer,
+                650,
+                Station::Saute,
+            ),
+            MenuItem::new(
+                "salad",
+                "House Salad",
+                Category::Appetizer,
+                800,
+                Station::Cold,
+            ),
+            MenuItem::new(
+                "wings",
+                "Chicken Wings",
+                Category::Appetizer,
+                1150,
+                Station::Fry,
+            ),
+            MenuItem::new(
+                "burger",
+                "Cheeseburger",
+                Category::Entree,
+                1500,
+                Station::Grill,
+            ),
+            MenuItem::new(
+                "steak",
+                "Ribeye Steak",
+                Category::Entree,
+                3200,
+                Station::Grill,
+            ),
+            MenuItem::new(
+                "salmon",
+                "Seared Salmon",
+                Category::Entree,
+                2400,
+                Station::Saute,
+            ),
+            MenuItem::new(
+                "pasta",
+                "Pasta Primavera",
+                Category::Entree,
+                1800,
+                Station::Saute,
+            ),
+            MenuItem::new("fries", "French Fries", Category::Side, 500, Station::Fry),
+            MenuItem::new(
+                "greens",
+                "Sauteed Greens",
+                Category::Side,
+                600,
+                Station::Saute,
+            ),
+            MenuItem::new("pie", "Peach Pie", Category::Dessert, 750, Station::Pastry),
+            MenuItem::new("sundae", "Sundae", Category::Dessert, 700, Station::Pastry),
+            MenuItem::new("soda", "Soda", Category::Drink, 300, Station::Bar),
+            MenuItem::new("beer", "Draft Beer", Category::Drink, 700, Station::Bar),
+            MenuItem::new("wine", "Glass of Wine", Category::Drink, 1100, Station::Bar),
+        ] {
+            menu.add(item);
+        }
+        menu
+    }
+
+    // adding an item with an existing code replaces it
+    pub fn add(&mut self, item: MenuItem) {
+        match self
+            .items
+            .iter_mut()
+            .find(|existing| existing.code == item.code)
+        {
+            Some(existing) => *existing = item,
+            None => self.items.push(item),
+        }
+    }
+
+    pub fn get(&self, code: &str) -> Option<&MenuItem> {
+        self.items
+            .iter()
+            .find(|item| item.code.eq_ignore_ascii_case(code))
+    }
+
+    pub fn items(&self) -> &[MenuItem] {
+        &self.items
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_is_case_insensitive() {
+        let menu = Menu::house();
+        let burger = menu.get("Burger").expect("burger should be on the menu");
+        assert_eq!(burger.price, Money::from_cents(1500));
+        assert_eq!(burger.station, Station::Grill);
+        assert!(menu.get("lobster").is_none());
+    }
+
+    #[test]
+    fn test_add_replaces_same_code() {
+        let mut menu = Menu::house();
+        let count = menu.items().len();
+        menu.add(MenuItem::new(
+            "soda",
+            "Soda",
+            Category::Drink,
+            350,
+            Station::Bar,
+        ));
+        assert_eq!(menu.items().len(), count);
+        assert_eq!(menu.get("soda").unwrap().price, Money::from_cents(350));
+    }
+}
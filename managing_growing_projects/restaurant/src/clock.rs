@@ -0,0 +1,193 @@
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// a time of day, kept as minutes since midnight. a service never crosses
+// midnight in this restaurant, so times and dates are kept apart
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Time(u16);
+
+impl Time {
+    pub const MINUTES_PER_DAY: u16 = 24 * 60;
+
+    pub fn new(hour: u16, minute: u16) -> Option<Time> {
+        if hour < 24 && minute < 60 {
+            Some(Time(hour * 60 + minute))
+        } else {
+            None
+        }
+    }
+
+    pub fn from_minutes(minutes: u16) -> Time {
+        Time(minutes % Self::MINUTES_PER_DAY)
+    }
+
+    // the current UTC wall clock; std has no notion of local time zones
+    pub fn now() -> Time {
+        let seconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or(0);
+        Time::from_minutes(((seconds / 60) % u64::from(Self::MINUTES_PER_DAY)) as u16)
+    }
+
+    // parses "HH:MM" in 24 hour time
+    pub fn parse(text: &str) -> Option<Time> {
+        let (hour, minute) = text.trim().split_once(':')?;
+        if minute.len() != 2 {
+            return None;
+        }
+        Time::new(hour.parse().ok()?, minute.parse().ok()?)
+    }
+
+    pub fn hour(self) -> u16 {
+        self.0 / 60
+    }
+
+    pub fn minute(self) -> u16 {
+        self.0 % 60
+    }
+
+    pub fn minutes(self) -> u16 {
+        self.0
+    }
+
+    // minutes elapsed from `earlier` to self, or 0 if earlier is later
+    pub fn minutes_since(self, earlier: Time) -> u16 {
+        self.0.saturating_sub(earlier.0)
+    }
+}
+
+impl fmt::Display for Time {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(&format!("{:02}:{:02}", self.hour(), self.minute()))
+    }
+}
+
+// a calendar date, for birthdays and visit history
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    year: i32,
+    month: u8,
+    day: u8,
+}
+
+impl Date {
+    pub fn new(year: i32, month: u8, day: u8) -> Option<Date> {
+        if (1..=12).contains(&month) && day >= 1 && day <= days_in_month(year, month) {
+            Some(Date { year, month, day })
+        } else {
+            None
+        }
+    }
+
+    // today's UTC date
+    pub fn today() -> Date {
+        let seconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or(0);
+        Date::from_days_since_epoch((seconds / 86_400) as i64)
+    }
+
+    // the civil calendar date for a count of days since 1970-01-01, see
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    pub fn from_days_since_epoch(days: i64) -> Date {
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let day_of_era = z.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let mp = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u8;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u8;
+        let year = (year_of_era + era * 400) as i32 + i32::from(month <= 2);
+        Date { year, month, day }
+    }
+
+    // parses "YYYY-MM-DD"
+    pub fn parse(text: &str) -> Option<Date> {
+        let mut parts = text.trim().splitn(3, '-');
+        let year = parts.next()?.parse().ok()?;
+        let month = parts.next()?.parse().ok()?;
+        let day = parts.next()?.parse().ok()?;
+        Date::new(year, month, day)
+    }
+
+    pub fn year(self) -> i32 {
+        self.year
+    }
+
+    pub fn month(self) -> u8 {
+        self.month
+    }
+
+    pub fn day(self) -> u8 {
+        self.day
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(&format!(
+            "{:04}-{:02}-{:02}",
+            self.year, self.month, self.day
+        ))
+    }
+}
+
+fn days_in_month(year: i32, month: u8) -> u8 {
+    match month {
+        4 | 6 | 9 | 11 => 30,
+        2 if (year % 4 == 0 && year % 100 != 0) || year % 400 == 0 => 29,
+        2 => 28,
+        _ => 31,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_display() {
+        let time = Time::parse("18:05").unwrap();
+        assert_eq!((time.hour(), time.minute()), (18, 5));
+        assert_eq!(time.to_string(), "18:05");
+        assert_eq!(Time::parse("24:00"), None);
+        assert_eq!(Time::parse("7:5"), None);
+        assert_eq!(Time::parse("noon"), None);
+    }
+
+    #[test]
+    fn test_minutes_since() {
+        let seated = Time::new(18, 40).unwrap();
+        let paid = Time::new(19, 55).unwrap();
+        assert_eq!(paid.minutes_since(seated), 75);
+        assert_eq!(seated.minutes_since(paid), 0);
+    }
+
+    #[test]
+    fn test_dates() {
+        assert_eq!(Date::parse("2024-02-29"), Date::new(2024, 2, 29));
+        assert!(Date::parse("2023-02-29").is_none());
+        assert!(Date::parse("2024-13-01").is_none());
+        assert_eq!(Date::new(2024, 7, 4).unwrap().to_string(), "2024-07-04");
+    }
+
+    #[test]
+    fn test_days_since_epoch() {
+        assert_eq!(
+            Date::from_days_since_epoch(0),
+            Date::new(1970, 1, 1).unwrap()
+        );
+        assert_eq!(
+            Date::from_days_since_epoch(19_782),
+            Date::new(2024, 2, 29).unwrap()
+        );
+        assert_eq!(
+            Date::from_days_since_epoch(-1),
+            Date::new(1969, 12, 31).unwrap()
+        );
+    }
+}
@@ -1,9 +1,2 @@
 pub mod hosting;
-
-mod serving {
-    fn take_order() {}
-
-    fn serve_order() {}
-
-    fn take_payment() {}
-}
\ No newline at end of file
+pub mod serving;
@@ -1,3 +1,7 @@
+use std::collections::VecDeque;
+use std::fmt;
+
+use crate::clock::Time;
 
 // returns true if successful, false if waitlist is full
 pub fn add_to_waitlist() -> bool {
@@ -6,6 +10,191 @@ pub fn add_to_waitlist() -> bool {
 
 fn seat_at_table() {}
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Party {
+    pub id: u32,
+    pub name: String,
+    pub size: u32,
+    pub arrived_at: Time,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableStatus {
+    Open,
+    // holds the id of the party sitting there
+    Seated { party_id: u32, since: Time },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Table {
+    pub number: u32,
+    pub seats: u32,
+    pub status: TableStatus,
+}
+
+impl Table {
+    pub fn new(number: u32, seats: u32) -> Table {
+        Table {
+            number,
+            seats,
+            status: TableStatus::Open,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.status == TableStatus::Open
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HostingError {
+    WaitlistFull,
+    UnknownParty(u32),
+    UnknownTable(u32),
+    TableTaken(u32),
+    TooSmall { table: u32, seats: u32, party: u32 },
+}
+
+impl fmt::Display for HostingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HostingError::WaitlistFull => write!(f, "the waitlist is full"),
+            HostingError::UnknownParty(id) => write!(f, "there is no party {id} waiting"),
+            HostingError::UnknownTable(number) => write!(f, "there is no table {number}"),
+            HostingError::TableTaken(number) => write!(f, "table {number} is already taken"),
+            HostingError::TooSmall {
+                table,
+                seats,
+                party,
+            } => write!(f, "table {table} seats {seats}, the party has {party}"),
+        }
+    }
+}
+
+impl std::error::Error for HostingError {}
+
+// parties waiting to be seated, first come first served
+#[derive(Debug)]
+pub struct Waitlist {
+    parties: VecDeque<Party>,
+    capacity: usize,
+    next_id: u32,
+}
+
+impl Waitlist {
+    pub fn new(capacity: usize) -> Waitlist {
+        Waitlist {
+            parties: VecDeque::new(),
+            capacity,
+            next_id: 1,
+        }
+    }
+
+    // returns the new party's id
+    pub fn add(&mut self, name: &str, size: u32, arrived_at: Time) -> Result<u32, HostingError> {
+        if self.parties.len() >= self.capacity {
+            return Err(HostingError::WaitlistFull);
+        }
+        let id = self.next_id;
+        self.next_id += 1;
+        self.parties.push_back(Party {
+            id,
+            name: String::from(name),
+            size,
+            arrived_at,
+        });
+        Ok(id)
+    }
+
+    pub fn get(&self, party_id: u32) -> Option<&Party> {
+        self.parties.iter().find(|party| party.id == party_id)
+    }
+
+    pub fn remove(&mut self, party_id: u32) -> Option<Party> {
+        let index = self.parties.iter().position(|party| party.id == party_id)?;
+        self.parties.remove(index)
+    }
+
+    pub fn parties(&self) -> impl Iterator<Item = &Party> {
+        self.parties.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.parties.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parties.is_empty()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FloorPlan {
+    tables: Vec<Table>,
+}
+
+impl FloorPlan {
+    pub fn new(tables: Vec<Table>) -> FloorPlan {
+        FloorPlan { tables }
+    }
+
+    // the dining room as it is laid out today
+    pub fn house() -> FloorPlan {
+        FloorPlan::new(vec![
+            Table::new(1, 2),
+            Table::new(2, 2),
+            Table::new(3, 4),
+            Table::new(4, 4),
+            Table::new(5, 4),
+            Table::new(6, 6),
+            Table::new(7, 6),
+            Table::new(8, 8),
+        ])
+    }
+
+    pub fn table(&self, number: u32) -> Option<&Table> {
+        self.tables.iter().find(|table| table.number == number)
+    }
+
+    pub fn tables(&self) -> &[Table] {
+        &self.tables
+    }
+
+    pub fn seat(&mut self, party: &Party, number: u32, now: Time) -> Result<(), HostingError> {
+        let table = self
+            .tables
+            .iter_mut()
+            .find(|table| table.number == number)
+            .ok_or(HostingError::UnknownTable(number))?;
+        if !table.is_open() {
+            return Err(HostingError::TableTaken(number));
+        }
+        if table.seats < party.size {
+            return Err(HostingError::TooSmall {
+                table: number,
+                seats: table.seats,
+                party: party.size,
+            });
+        }
+        table.status = TableStatus::Seated {
+            party_id: party.id,
+            since: now,
+        };
+        Ok(())
+    }
+
+    // frees a table once the party has paid and left
+    pub fn clear(&mut self, number: u32) -> Result<(), HostingError> {
+        let table = self
+            .tables
+            .iter_mut()
+            .find(|table| table.number == number)
+            .ok_or(HostingError::UnknownTable(number))?;
+        table.status = TableStatus::Open;
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -16,4 +205,46 @@ mod tests {
         let result = add_to_waitlist();
         assert!(result, "Expected add_to_waitlist to return true");
     }
+
+    #[test]
+    fn test_waitlist_is_bounded() {
+        let mut waitlist = Waitlist::new(1);
+        let now = Time::new(18, 0).unwrap();
+        assert_eq!(waitlist.add("Smith", 2, now), Ok(1));
+        assert_eq!(
+            waitlist.add("Jones", 4, now),
+            Err(HostingError::WaitlistFull)
+        );
+        assert!(waitlist.remove(1).is_some());
+        assert!(waitlist.is_empty());
+    }
+
+    #[test]
+    fn test_seat_checks_size_and_availability() {
+        let mut floor = FloorPlan::house();
+        let now = Time::new(18, 0).unwrap();
+        let party = Party {
+            id: 1,
+            name: String::from("Smith"),
+            size: 3,
+            arrived_at: now,
+        };
+        assert_eq!(
+            floor.seat(&party, 1, now),
+            Err(HostingError::TooSmall {
+                table: 1,
+                seats: 2,
+                party: 3
+            })
+        );
+        assert_eq!(floor.seat(&party, 3, now), Ok(()));
+        assert_eq!(floor.seat(&party, 3, now), Err(HostingError::TableTaken(3)));
+        assert_eq!(
+            floor.seat(&party, 42, now),
+            Err(HostingError::UnknownTable(42))
+        );
+
+        floor.clear(3).unwrap();
+        assert!(floor.table(3).unwrap().is_open());
+    }
 }
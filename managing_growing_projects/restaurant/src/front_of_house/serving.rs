@@ -0,0 +1,418 @@
+use std::fmt;
+
+use crate::back_of_house::kitchen::{Station, TicketItem};
+use crate::back_of_house::menu::{Category, MenuItem};
+use crate::clock::Time;
+use crate::money::Money;
+use crate::promotions::{Discount, PromotionContext, Promotions};
+
+// a line on a check. we copy what we need off the menu item so changing
+// the menu mid-service doesn't change what a guest already ordered
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderLine {
+    pub number: u32,
+    pub code: String,
+    pub name: String,
+    pub category: Category,
+    pub station: Station,
+    pub unit_price: Money,
+    pub quantity: u32,
+    pub modifiers: Vec<String>,
+    pub fired: bool,
+    pub voided: bool,
+    // given to the guest on the house
+    pub comped: bool,
+}
+
+impl OrderLine {
+    // the menu price of the line
+    pub fn amount(&self) -> Money {
+        self.unit_price * self.quantity
+    }
+
+    // what the guest pays for the line
+    pub fn charged(&self) -> Money {
+        if self.comped {
+            Money::ZERO
+        } else {
+            self.amount()
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ServingError {
+    CheckClosed(u32),
+    UnknownLine(u32),
+    AlreadyVoided(u32),
+    AlreadyComped(u32),
+    NothingToFire,
+}
+
+impl fmt::Display for ServingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ServingError::CheckClosed(id) => write!(f, "check {id} is already closed"),
+            ServingError::UnknownLine(number) => write!(f, "there is no line {number}"),
+            ServingError::AlreadyVoided(number) => write!(f, "line {number} is already voided"),
+            ServingError::AlreadyComped(number) => write!(f, "line {number} is already comped"),
+            ServingError::NothingToFire => write!(f, "nothing new to fire"),
+        }
+    }
+}
+
+impl std::error::Error for ServingError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    pub id: u32,
+    pub table: u32,
+    pub party_id: u32,
+    // number of guests on the check, used for covers
+    pub guests: u32,
+    pub server: Option<String>,
+    // the loyalty member paying, if they told us
+    pub customer_id: Option<u32>,
+    pub opened_at: Time,
+    pub closed_at: Option<Time>,
+    lines: Vec<OrderLine>,
+}
+
+impl Check {
+    pub fn new(id: u32, table: u32, party_id: u32, guests: u32, opened_at: Time) -> Check {
+        Check {
+            id,
+            table,
+            party_id,
+            guests,
+            server: None,
+            customer_id: None,
+            opened_at,
+            closed_at: None,
+            lines: Vec::new(),
+        }
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed_at.is_some()
+    }
+
+    pub fn lines(&self) -> &[OrderLine] {
+        &self.lines
+    }
+
+    // the lines the guest is actually paying for
+    pub fn live_lines(&self) -> impl Iterator<Item = &OrderLine> {
+        self.lines.iter().filter(|line| !line.voided)
+    }
+
+    // returns the new line's number
+    pub fn add(
+        &mut self,
+        item: &MenuItem,
+        quantity: u32,
+        modifiers: Vec<String>,
+    ) -> Result<u32, ServingError> {
+        self.ensure_open()?;
+        let number = self.lines.len() as u32 + 1;
+        self.lines.push(OrderLine {
+            number,
+            code: item.code.clone(),
+            name: item.name.clone(),
+            category: item.category,
+            station: item.station,
+            unit_price: item.price,
+            quantity,
+            modifiers,
+            fired: false,
+            voided: false,
+            comped: false,
+        });
+        Ok(number)
+    }
+
+    pub fn void(&mut self, number: u32) -> Result<&OrderLine, ServingError> {
+        self.ensure_open()?;
+        let line = self
+            .lines
+            .iter_mut()
+            .find(|line| line.number == number)
+            .ok_or(ServingError::UnknownLine(number))?;
+        if line.voided {
+            return Err(ServingError::AlreadyVoided(number));
+        }
+        line.voided = true;
+        Ok(line)
+    }
+
+    pub fn comp(&mut self, number: u32) -> Result<&OrderLine, ServingError> {
+        self.ensure_open()?;
+        let line = self
+            .lines
+            .iter_mut()
+            .find(|line| line.number == number && !line.voided)
+            .ok_or(ServingError::UnknownLine(number))?;
+        if line.comped {
+            return Err(ServingError::AlreadyComped(number));
+        }
+        line.comped = true;
+        Ok(line)
+    }
+
+    // marks everything not yet sent as fired and hands back what the
+    // kitchen needs to make it
+    pub fn fire(&mut self) -> Result<Vec<TicketItem>, ServingError> {
+        self.ensure_open()?;
+        let items: Vec<TicketItem> = self
+            .lines
+            .iter_mut()
+            .filter(|line| !line.fired && !line.voided)
+            .map(|line| {
+                line.fired = true;
+                TicketItem {
+                    name: line.name.clone(),
+                    quantity: line.quantity,
+                    modifiers: line.modifiers.clone(),
+                    station: line.station,
+                }
+            })
+            .collect();
+        if items.is_empty() {
+            return Err(ServingError::NothingToFire);
+        }
+        Ok(items)
+    }
+
+    pub fn close(&mut self, now: Time) -> Result<(), ServingError> {
+        self.ensure_open()?;
+        self.closed_at = Some(now);
+        Ok(())
+    }
+
+    fn ensure_open(&self) -> Result<(), ServingError> {
+        if self.is_closed() {
+            Err(ServingError::CheckClosed(self.id))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BillLine {
+    pub name: String,
+    pub quantity: u32,
+    pub modifiers: Vec<String>,
+    pub unit_price: Money,
+    pub amount: Money,
+    pub comped: bool,
+}
+
+// what the guest owes for a check
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bill {
+    pub check_id: u32,
+    pub table: u32,
+    pub lines: Vec<BillLine>,
+    pub subtotal: Money,
+    pub discounts: Vec<Discount>,
+    // tax rate in basis points, kept so receipts can print it
+    pub tax_rate: u32,
+    pub tax: Money,
+    pub tip: Money,
+    pub total: Money,
+}
+
+impl Bill {
+    pub fn compute(check: &Check, tax_rate: u32) -> Bill {
+        let lines: Vec<BillLine> = check
+            .live_lines()
+            .map(|line| BillLine {
+                name: line.name.clone(),
+                quantity: line.quantity,
+                modifiers: line.modifiers.clone(),
+                unit_price: line.unit_price,
+                amount: line.charged(),
+                comped: line.comped,
+            })
+            .collect();
+        let subtotal: Money = lines.iter().map(|line| line.amount).sum();
+        let tax = subtotal.percent(tax_rate);
+        Bill {
+            check_id: check.id,
+            table: check.table,
+            lines,
+            subtotal,
+            discounts: Vec::new(),
+            tax_rate,
+            tax,
+            tip: Money::ZERO,
+            total: subtotal + tax,
+        }
+    }
+
+    // the bill with every promotion the check qualifies for taken off
+    pub fn compute_with(
+        check: &Check,
+        tax_rate: u32,
+        promotions: &Promotions,
+        context: &PromotionContext,
+    ) -> Bill {
+        Bill::compute(check, tax_rate).with_discounts(promotions.apply(check, context))
+    }
+
+    // tax is charged on what's left after discounts
+    pub fn with_discounts(mut self, discounts: Vec<Discount>) -> Bill {
+        self.discounts = discounts;
+        self.tax = self.net().percent(self.tax_rate);
+        self.total = self.net() + self.tax + self.tip;
+        self
+    }
+
+    pub fn discount_total(&self) -> Money {
+        self.discounts.iter().map(|discount| discount.amount).sum()
+    }
+
+    // the subtotal less discounts
+    pub fn net(&self) -> Money {
+        self.subtotal - self.discount_total()
+    }
+
+    // None if the tip is too big to add to the total
+    pub fn with_tip(mut self, tip: Money) -> Option<Bill> {
+        self.total = self.due().checked_add(tip)?;
+        self.tip = tip;
+        Some(self)
+    }
+
+    // the amount due before tip
+    pub fn due(&self) -> Money {
+        self.total - self.tip
+    }
+
+    // splits the bill evenly between `ways` guests, one payment each
+    pub fn split(&self, ways: u32, paid_at: Time) -> Vec<Payment> {
+        self.due()
+            .split(ways)
+            .into_iter()
+            .zip(self.tip.split(ways))
+            .map(|(amount, tip)| Payment {
+                check_id: self.check_id,
+                amount,
+                tip,
+                paid_at,
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Payment {
+    pub check_id: u32,
+    // what was paid toward the bill, not counting tip
+    pub amount: Money,
+    pub tip: Money,
+    pub paid_at: Time,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::back_of_house::menu::Menu;
+
+    fn dinner() -> Check {
+        let menu = Menu::house();
+        let mut check = Check::new(1, 4, 1, 2, Time::new(18, 0).unwrap());
+        check
+            .add(
+                menu.get("burger").unwrap(),
+                2,
+                vec![String::from("no onions")],
+            )
+            .unwrap();
+        check.add(menu.get("fries").unwrap(), 1, vec![]).unwrap();
+        check.add(menu.get("beer").unwrap(), 2, vec![]).unwrap();
+        check
+    }
+
+    #[test]
+    fn test_fire_only_sends_new_lines() {
+        let mut check = dinner();
+        assert_eq!(check.fire().unwrap().len(), 3);
+        assert_eq!(check.fire(), Err(ServingError::NothingToFire));
+
+        let menu = Menu::house();
+        check.add(menu.get("pie").unwrap(), 1, vec![]).unwrap();
+        let items = check.fire().unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].station, Station::Pastry);
+    }
+
+    #[test]
+    fn test_bill_skips_voided_lines() {
+        let mut check = dinner();
+        check.void(3).unwrap();
+        assert_eq!(check.void(3).err(), Some(ServingError::AlreadyVoided(3)));
+
+        let bill = Bill::compute(&check, 825);
+        // 2 x 15.00 + 5.00
+        assert_eq!(bill.subtotal, Money::from_cents(3500));
+        assert_eq!(bill.tax, Money::from_cents(289));
+        assert_eq!(bill.total, Money::from_cents(3789));
+        assert_eq!(bill.lines.len(), 2);
+    }
+
+    #[test]
+    fn test_comped_lines_are_free() {
+        let mut check = dinner();
+        check.comp(2).unwrap();
+        assert_eq!(check.comp(2).err(), Some(ServingError::AlreadyComped(2)));
+
+        let bill = Bill::compute(&check, 825);
+        assert_eq!(bill.subtotal, Money::from_cents(4400));
+        assert!(bill.lines[1].comped);
+        assert_eq!(bill.lines[1].amount, Money::ZERO);
+    }
+
+    #[test]
+    fn test_discounts_reduce_tax() {
+        let discount = Discount {
+            promotion: String::from("Happy Hour"),
+            reason: String::from("half price beer"),
+            amount: Money::from_cents(700),
+        };
+        let bill = Bill::compute(&dinner(), 825)
+            .with_tip(Money::from_cents(500))
+            .unwrap()
+            .with_discounts(vec![discount]);
+        assert_eq!(bill.subtotal, Money::from_cents(4900));
+        assert_eq!(bill.net(), Money::from_cents(4200));
+        assert_eq!(bill.tax, Money::from_cents(347));
+        assert_eq!(bill.total, Money::from_cents(4200 + 347 + 500));
+    }
+
+    #[test]
+    fn test_split_with_tip_adds_up() {
+        let bill = Bill::compute(&dinner(), 825)
+            .with_tip(Money::from_cents(1000))
+            .unwrap();
+        let payments = bill.split(3, Time::new(19, 0).unwrap());
+        assert_eq!(payments.len(), 3);
+        let paid: Money = payments
+            .iter()
+            .map(|payment| payment.amount + payment.tip)
+            .sum();
+        assert_eq!(paid, bill.total);
+    }
+
+    #[test]
+    fn test_closed_check_rejects_changes() {
+        let mut check = dinner();
+        check.close(Time::new(19, 0).unwrap()).unwrap();
+        let menu = Menu::house();
+        assert_eq!(
+            check.add(menu.get("soda").unwrap(), 1, vec![]),
+            Err(ServingError::CheckClosed(1))
+        );
+    }
+}
@@ -1,7 +1,15 @@
 #![allow(unused)]
-mod front_of_house;
-mod back_of_house;
+pub mod front_of_house;
+pub mod back_of_house;
 mod customer;
+pub mod clock;
+pub mod loyalty;
+pub mod money;
+pub mod pos;
+pub mod printing;
+pub mod promotions;
+pub mod reports;
+pub mod staff;
 
 pub fn new_customer() {
     customer::eat_at_restaurant();
@@ -0,0 +1,190 @@
+use std::fmt;
+
+use crate::clock::Date;
+use crate::front_of_house::serving::Bill;
+use crate::money::Money;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Visit {
+    pub date: Date,
+    pub check_id: u32,
+    // what the guest paid for food and drink, after discounts
+    pub spent: Money,
+    pub points: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Customer {
+    pub id: u32,
+    pub name: String,
+    // month and day; the year doesn't matter for a birthday treat
+    pub birthday: Option<(u8, u8)>,
+    pub points: u32,
+    pub visits: Vec<Visit>,
+}
+
+impl Customer {
+    pub fn is_birthday(&self, date: Date) -> bool {
+        self.birthday == Some((date.month(), date.day()))
+    }
+
+    pub fn total_spent(&self) -> Money {
+        self.visits.iter().map(|visit| visit.spent).sum()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoyaltyError {
+    UnknownCustomer(u32),
+    AlreadyRecorded(u32),
+}
+
+impl fmt::Display for LoyaltyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoyaltyError::UnknownCustomer(id) => write!(f, "there is no customer {id}"),
+            LoyaltyError::AlreadyRecorded(check) => {
+                write!(f, "check {check} already earned points")
+            }
+        }
+    }
+}
+
+impl std::error::Error for LoyaltyError {}
+
+// parses a birthday written as "MM-DD"
+pub fn parse_birthday(text: &str) -> Option<(u8, u8)> {
+    let (month, day) = text.trim().split_once('-')?;
+    let month: u8 = month.parse().ok()?;
+    let day: u8 = day.parse().ok()?;
+    // check against a leap year so 02-29 is allowed
+    Date::new(2000, month, day).map(|_| (month, day))
+}
+
+#[derive(Debug, Clone)]
+pub struct Loyalty {
+    customers: Vec<Customer>,
+    // points earned for every whole dollar spent
+    pub points_per_dollar: u32,
+}
+
+impl Default for Loyalty {
+    fn default() -> Loyalty {
+        Loyalty::new()
+    }
+}
+
+impl Loyalty {
+    pub fn new() -> Loyalty {
+        Loyalty {
+            customers: Vec::new(),
+            points_per_dollar: 1,
+        }
+    }
+
+    // signs a guest up and returns their customer id
+    pub fn enroll(&mut self, name: &str, birthday: Option<(u8, u8)>) -> u32 {
+        let id = self.customers.len() as u32 + 1;
+        self.customers.push(Customer {
+            id,
+            name: String::from(name),
+            birthday,
+            points: 0,
+            visits: Vec::new(),
+        });
+        id
+    }
+
+    pub fn customer(&self, id: u32) -> Option<&Customer> {
+        self.customers.iter().find(|customer| customer.id == id)
+    }
+
+    pub fn customers(&self) -> &[Customer] {
+        &self.customers
+    }
+
+    // credits a paid bill to the customer, returning the points earned.
+    // points are on what was spent after discounts, before tax and tip
+    pub fn record_visit(&mut self, id: u32, date: Date, bill: &Bill) -> Result<u32, LoyaltyError> {
+        let points_per_dollar = self.points_per_dollar;
+        let customer = self
+            .customers
+            .iter_mut()
+            .find(|customer| customer.id == id)
+            .ok_or(LoyaltyError::UnknownCustomer(id))?;
+        if customer
+            .visits
+            .iter()
+            .any(|visit| visit.check_id == bill.check_id)
+        {
+            return Err(LoyaltyError::AlreadyRecorded(bill.check_id));
+        }
+        let spent = bill.net();
+        let dollars = u32::try_from(spent.cents() / 100).unwrap_or(0);
+        let points = dollars * points_per_dollar;
+        customer.points += points;
+        customer.visits.push(Visit {
+            date,
+            check_id: bill.check_id,
+            spent,
+            points,
+        });
+        Ok(points)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::back_of_house::menu::Menu;
+    use crate::clock::Time;
+    use crate::front_of_house::serving::Check;
+
+    fn bill(check_id: u32) -> Bill {
+        let menu = Menu::house();
+        let mut check = Check::new(check_id, 1, 1, 2, Time::new(19, 0).unwrap());
+        // 2 x 15.00 + 7.50
+        check.add(menu.get("burger").unwrap(), 2, vec![]).unwrap();
+        check.add(menu.get("pie").unwrap(), 1, vec![]).unwrap();
+        Bill::compute(&check, 825)
+            .with_tip(Money::from_cents(600))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_points_per_paid_bill() {
+        let mut loyalty = Loyalty::new();
+        let id = loyalty.enroll("Ana", None);
+        let date = Date::new(2025, 3, 1).unwrap();
+        assert_eq!(loyalty.record_visit(id, date, &bill(1)), Ok(37));
+        assert_eq!(
+            loyalty.record_visit(id, date, &bill(1)),
+            Err(LoyaltyError::AlreadyRecorded(1))
+        );
+
+        loyalty.points_per_dollar = 2;
+        assert_eq!(loyalty.record_visit(id, date, &bill(2)), Ok(74));
+
+        let ana = loyalty.customer(id).unwrap();
+        assert_eq!(ana.points, 111);
+        assert_eq!(ana.visits.len(), 2);
+        assert_eq!(ana.total_spent(), Money::from_cents(7500));
+        assert_eq!(
+            loyalty.record_visit(9, date, &bill(3)),
+            Err(LoyaltyError::UnknownCustomer(9))
+        );
+    }
+
+    #[test]
+    fn test_birthdays() {
+        assert_eq!(parse_birthday("02-29"), Some((2, 29)));
+        assert_eq!(parse_birthday("13-01"), None);
+        assert_eq!(parse_birthday("june"), None);
+
+        let mut loyalty = Loyalty::new();
+        let id = loyalty.enroll("Ben", Some((7, 4)));
+        let ben = loyalty.customer(id).unwrap();
+        assert!(ben.is_birthday(Date::new(2031, 7, 4).unwrap()));
+        assert!(!ben.is_birthday(Date::new(2031, 7, 5).unwrap()));
+    }
+}
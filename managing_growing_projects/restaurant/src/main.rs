@@ -1,33 +1,68 @@
 // can rename to avoid conflicts
-use std::fmt::Result;
 use std::io::Result as IoResult;
 
-use restaurant;
-
 // we can combine multiple imports from the same module
-// use std::cmp::Ordering;
-// use std::io;
-// using below
-// use std::{cmp::Ordering, io};
+use std::io::{self, BufRead, Write};
+use std::{env, fs, process};
 
-// we can also import all using a glob operator
-// use std::collections::*;
+use restaurant::clock::{Date, Time};
+use restaurant::pos::{Pos, Reply};
 
-fn main() {
-    println!("Hello, world!");
+const USAGE: &str = "usage: restaurant [--script <file>]";
 
-    function1();
-    function2();
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let result = match args.as_slice() {
+        [] => repl(),
+        [flag, path] if flag == "--script" => run_script(path),
+        _ => {
+            eprintln!("{USAGE}");
+            process::exit(2);
+        }
+    };
 
-    restaurant::new_customer();
+    if let Err(error) = result {
+        eprintln!("error: {error}");
+        process::exit(1);
+    }
 }
 
-fn function1() -> Option<Result> {
-    // --snip--
-    None
+// interactive mode, one command per line until quit or end of input
+fn repl() -> IoResult<()> {
+    let mut pos = Pos::new(Date::today(), Time::now());
+    println!("restaurant POS, type 'help' for commands");
+
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        print!("pos> ");
+        io::stdout().flush()?;
+
+        let Some(line) = lines.next() else {
+            println!();
+            return Ok(());
+        };
+        match pos.execute(&line?) {
+            Ok(Reply::Text(text)) if text.is_empty() => {}
+            Ok(Reply::Text(text)) => println!("{text}"),
+            Ok(Reply::Quit) => return Ok(()),
+            Err(error) => println!("error: {error}"),
+        }
+    }
 }
 
-fn function2() -> Option<IoResult<()>> {
-    // --snip--
-    None
-}
\ No newline at end of file
+// runs a file of commands, printing a transcript; stops at the first error
+fn run_script(path: &str) -> IoResult<()> {
+    let script = fs::read_to_string(path)?;
+    let mut pos = Pos::new(Date::today(), Time::default());
+    match pos.run_script(&script) {
+        Ok(transcript) => {
+            print!("{transcript}");
+            Ok(())
+        }
+        Err(error) => {
+            eprintln!("{path}: {error}");
+            process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,229 @@
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+// money is always kept in whole cents so adding up a check never drifts
+// the way floating point would
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Money(i64);
+
+impl Money {
+    pub const ZERO: Money = Money(0);
+
+    pub const fn from_cents(cents: i64) -> Money {
+        Money(cents)
+    }
+
+    pub fn cents(self) -> i64 {
+        self.0
+    }
+
+    // parses "12", "12.5" or "12.50" (an optional leading '$' is ignored)
+    pub fn parse(text: &str) -> Option<Money> {
+        let text = text.trim().trim_start_matches('$');
+        let (negative, text) = match text.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, text),
+        };
+        let (whole, fraction) = match text.split_once('.') {
+            Some((whole, fraction)) => (whole, fraction),
+            None => (text, ""),
+        };
+        if whole.is_empty() && fraction.is_empty() {
+            return None;
+        }
+        if fraction.len() > 2 || !fraction.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        let whole: i64 = if whole.is_empty() {
+            0
+        } else {
+            whole.parse().ok()?
+        };
+        let fraction: i64 = match fraction.len() {
+            0 => 0,
+            1 => fraction.parse::<i64>().ok()? * 10,
+            _ => fraction.parse().ok()?,
+        };
+        let cents = whole.checked_mul(100)?.checked_add(fraction)?;
+        Some(Money(if negative { -cents } else { cents }))
+    }
+
+    // a percentage given in basis points (1/100th of a percent), rounded
+    // half away from zero to the nearest cent
+    pub fn percent(self, basis_points: u32) -> Money {
+        let scaled = self.0 * i64::from(basis_points);
+        let rounded = if scaled >= 0 {
+            (scaled + 5_000) / 10_000
+        } else {
+            (scaled - 5_000) / 10_000
+        };
+        Money(rounded)
+    }
+
+    // splits into `ways` shares that add back up to exactly self; any
+    // leftover cents go to the first shares
+    pub fn split(self, ways: u32) -> Vec<Money> {
+        if ways == 0 {
+            return Vec::new();
+        }
+        let ways = i64::from(ways);
+        let share = self.0 / ways;
+        let remainder = self.0 % ways;
+        (0..ways)
+            .map(|i| {
+                Money(
+                    share
+                        + if i < remainder.abs() {
+                            remainder.signum()
+                        } else {
+                            0
+                        },
+                )
+            })
+            .collect()
+    }
+
+    // None instead of a panic when the result won't fit
+    pub fn checked_add(self, other: Money) -> Option<Money> {
+        self.0.checked_add(other.0).map(Money)
+    }
+
+    pub fn checked_sub(self, other: Money) -> Option<Money> {
+        self.0.checked_sub(other.0).map(Money)
+    }
+
+    pub fn checked_mul(self, quantity: u32) -> Option<Money> {
+        self.0.checked_mul(i64::from(quantity)).map(Money)
+    }
+
+    pub fn is_zero(self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let cents = self.0.unsigned_abs();
+        let text = format!("{sign}{}.{:02}", cents / 100, cents % 100);
+        // let callers pad/align money columns with the usual width flags
+        f.pad(&text)
+    }
+}
+
+impl Add for Money {
+    type Output = Money;
+
+    fn add(self, other: Money) -> Money {
+        Money(self.0 + other.0)
+    }
+}
+
+impl AddAssign for Money {
+    fn add_assign(&mut self, other: Money) {
+        self.0 += other.0;
+    }
+}
+
+impl Sub for Money {
+    type Output = Money;
+
+    fn sub(self, other: Money) -> Money {
+        Money(self.0 - other.0)
+    }
+}
+
+impl SubAssign for Money {
+    fn sub_assign(&mut self, other: Money) {
+        self.0 -= other.0;
+    }
+}
+
+impl Neg for Money {
+    type Output = Money;
+
+    fn neg(self) -> Money {
+        Money(-self.0)
+    }
+}
+
+impl Mul<u32> for Money {
+    type Output = Money;
+
+    fn mul(self, quantity: u32) -> Money {
+        Money(self.0 * i64::from(quantity))
+    }
+}
+
+impl Sum for Money {
+    fn sum<I: Iterator<Item = Money>>(iter: I) -> Money {
+        iter.fold(Money::ZERO, |total, amount| total + amount)
+    }
+}
+
+impl<'a> Sum<&'a Money> for Money {
+    fn sum<I: Iterator<Item = &'a Money>>(iter: I) -> Money {
+        iter.copied().sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Money::parse("12"), Some(Money::from_cents(1200)));
+        assert_eq!(Money::parse("$12.5"), Some(Money::from_cents(1250)));
+        assert_eq!(Money::parse("0.07"), Some(Money::from_cents(7)));
+        assert_eq!(Money::parse("-3.10"), Some(Money::from_cents(-310)));
+        assert_eq!(Money::parse("1.234"), None);
+        assert_eq!(Money::parse("abc"), None);
+        assert_eq!(Money::parse(""), None);
+    }
+
+    #[test]
+    fn test_percent_rounds_to_nearest_cent() {
+        // 8.25% of $10.10 is 83.325 cents
+        assert_eq!(Money::from_cents(1010).percent(825), Money::from_cents(83));
+        // 8.25% of $10.30 is 84.975 cents
+        assert_eq!(Money::from_cents(1030).percent(825), Money::from_cents(85));
+    }
+
+    #[test]
+    fn test_split_adds_back_up() {
+        let shares = Money::from_cents(1000).split(3);
+        assert_eq!(
+            shares,
+            vec![
+                Money::from_cents(334),
+                Money::from_cents(333),
+                Money::from_cents(333)
+            ]
+        );
+        assert_eq!(shares.iter().sum::<Money>(), Money::from_cents(1000));
+    }
+
+    #[test]
+    fn test_checked() {
+        let most = Money::from_cents(i64::MAX);
+        assert_eq!(most.checked_add(Money::from_cents(1)), None);
+        assert_eq!(
+            Money::from_cents(i64::MIN).checked_sub(Money::from_cents(1)),
+            None
+        );
+        assert_eq!(most.checked_mul(2), None);
+        assert_eq!(
+            Money::from_cents(250).checked_mul(3),
+            Some(Money::from_cents(750))
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Money::from_cents(5).to_string(), "0.05");
+        assert_eq!(Money::from_cents(-1250).to_string(), "-12.50");
+        assert_eq!(format!("{:>7}", Money::from_cents(1250)), "  12.50");
+    }
+}
@@ -0,0 +1,1362 @@
+use std::error::Error;
+use std::fmt::{self, Write};
+
+use crate::back_of_house::kitchen::Kitchen;
+use crate::back_of_house::menu::Menu;
+use crate::clock::{Date, Time};
+use crate::front_of_house::hosting::{FloorPlan, HostingError, Party, TableStatus, Waitlist};
+use crate::front_of_house::serving::{Bill, Check, Payment, ServingError};
+use crate::loyalty::{Loyalty, LoyaltyError, parse_birthday};
+use crate::money::Money;
+use crate::printing::Format;
+use crate::promotions::{PromotionContext, Promotions};
+use crate::reports::DailyReport;
+use crate::staff::{Role, Roster, Rotation, Section, StaffError, TipPool, assign_sections};
+
+const HELP: &str = "\
+commands:
+  menu                              list the menu
+  time [HH:MM]                      show or set the clock
+  date [YYYY-MM-DD]                 show or set the date
+  party <name> <size>               add a party to the waitlist
+  waitlist                          show who is waiting
+  tables                            show the floor
+  seat <party> [table]              seat a waiting party, picking a table in
+                                    the next server's section if none is given
+  open <table> [server]             open a check for a seated table; the
+                                    server defaults to the section's server
+  add <check> <item> [qty] [mod..]  add an item, e.g. add 1 burger 2 \"no onions\"
+  void <check> <line>               void a line on a check
+  comp <check> <line>               give a line on the house
+  fire <check>                      send new items to the kitchen
+  kitchen                           show open kitchen tickets
+  bump <ticket>                     mark a ticket as done
+  check <check>                     show a check
+  bill <check> [tip]                show the bill, tip as 5.00 or 18%
+  close <check> [tip <t>] [split <n>]
+                                    take payment and free the table
+  receipt <check> [format]          print a receipt: thermal, text or html
+  chit <ticket> [format]            print a kitchen ticket by station
+  report [csv|json]                 end of day sales and operations
+  staff [add <name> <role>]         show or add to the roster
+  shift <name> <HH:MM> <HH:MM>      schedule a shift
+  clockin <name>, clockout <name>   start or finish working
+  sections                          show server sections
+  tips                              share out the tip pool
+  customer [<id>|add <name> [MM-DD]]
+                                    list, show or enroll loyalty customers
+  attach <check> <customer>         put a check on a customer's account
+  promos                            list the promotions we run
+  history                           show previous commands
+  !<n>                              run command <n> from history again
+  quit                              leave the terminal";
+
+// the most of one item a single line can hold
+const MAX_QUANTITY: u32 = 999;
+
+// the biggest tip we'll take, as an amount or as a share of the
+// subtotal in basis points
+const MAX_TIP: Money = Money::from_cents(1_000_000);
+const MAX_TIP_PERCENT: u32 = 10_000;
+
+// 8.25% sales tax, in basis points
+pub const DEFAULT_TAX_RATE: u32 = 825;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PosError {
+    UnknownCommand(String),
+    Usage(&'static str),
+    UnknownItem(String),
+    UnknownCheck(u32),
+    UnknownTicket(u32),
+    UnknownHistory(usize),
+    NoPartyAtTable(u32),
+    NoTableAvailable(u32),
+    Hosting(HostingError),
+    Serving(ServingError),
+    Staff(StaffError),
+    Loyalty(LoyaltyError),
+}
+
+impl fmt::Display for PosError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PosError::UnknownCommand(command) => {
+                write!(f, "unknown command '{command}', try 'help'")
+            }
+            PosError::Usage(usage) => write!(f, "usage: {usage}"),
+            PosError::UnknownItem(code) => write!(f, "'{code}' is not on the menu"),
+            PosError::UnknownCheck(id) => write!(f, "there is no check {id}"),
+            PosError::UnknownTicket(id) => write!(f, "there is no ticket {id}"),
+            PosError::UnknownHistory(n) => write!(f, "there is no command {n} in history"),
+            PosError::NoPartyAtTable(table) => write!(f, "nobody is seated at table {table}"),
+            PosError::NoTableAvailable(size) => {
+                write!(f, "no server has an open table for {size}")
+            }
+            PosError::Hosting(error) => error.fmt(f),
+            PosError::Serving(error) => error.fmt(f),
+            PosError::Staff(error) => error.fmt(f),
+            PosError::Loyalty(error) => error.fmt(f),
+        }
+    }
+}
+
+impl Error for PosError {}
+
+impl From<HostingError> for PosError {
+    fn from(error: HostingError) -> PosError {
+        PosError::Hosting(error)
+    }
+}
+
+impl From<ServingError> for PosError {
+    fn from(error: ServingError) -> PosError {
+        PosError::Serving(error)
+    }
+}
+
+impl From<StaffError> for PosError {
+    fn from(error: StaffError) -> PosError {
+        PosError::Staff(error)
+    }
+}
+
+impl From<LoyaltyError> for PosError {
+    fn from(error: LoyaltyError) -> PosError {
+        PosError::Loyalty(error)
+    }
+}
+
+// a script that stopped part way through
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptError {
+    pub line: usize,
+    pub error: PosError,
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.error)
+    }
+}
+
+impl Error for ScriptError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reply {
+    Text(String),
+    Quit,
+}
+
+// everything the terminal knows about the current service
+#[derive(Debug)]
+pub struct Pos {
+    pub menu: Menu,
+    pub floor: FloorPlan,
+    pub waitlist: Waitlist,
+    pub kitchen: Kitchen,
+    pub checks: Vec<Check>,
+    pub bills: Vec<Bill>,
+    pub payments: Vec<Payment>,
+    pub roster: Roster,
+    pub tip_pool: TipPool,
+    pub loyalty: Loyalty,
+    pub promotions: Promotions,
+    pub tax_rate: u32,
+    pub today: Date,
+    pub now: Time,
+    rotation: Rotation,
+    // parties that have been seated, kept around for their head count
+    seated: Vec<Party>,
+    history: Vec<String>,
+}
+
+impl Pos {
+    pub fn new(today: Date, now: Time) -> Pos {
+        Pos {
+            menu: Menu::house(),
+            floor: FloorPlan::house(),
+            waitlist: Waitlist::new(50),
+            kitchen: Kitchen::new(),
+            checks: Vec::new(),
+            bills: Vec::new(),
+            payments: Vec::new(),
+            roster: Roster::new(),
+            tip_pool: TipPool::house(),
+            loyalty: Loyalty::new(),
+            promotions: Promotions::house(),
+            tax_rate: DEFAULT_TAX_RATE,
+            today,
+            now,
+            rotation: Rotation::new(),
+            seated: Vec::new(),
+            history: Vec::new(),
+        }
+    }
+
+    pub fn history(&self) -> &[String] {
+        &self.history
+    }
+
+    pub fn check(&self, id: u32) -> Option<&Check> {
+        self.checks.iter().find(|check| check.id == id)
+    }
+
+    // the floor split between the servers clocked in right now
+    pub fn sections(&self) -> Vec<Section> {
+        let servers: Vec<&str> = self
+            .roster
+            .on_duty(Role::Server)
+            .into_iter()
+            .map(|member| member.name.as_str())
+            .collect();
+        assign_sections(&self.floor, &servers)
+    }
+
+    fn section_server(&self, table: u32) -> Option<String> {
+        self.sections()
+            .into_iter()
+            .find(|section| section.tables.contains(&table))
+            .map(|section| section.server)
+    }
+
+    // runs one line typed at the terminal
+    pub fn execute(&mut self, line: &str) -> Result<Reply, PosError> {
+        let line = line.trim();
+        if line.is_empty() {
+            return Ok(Reply::Text(String::new()));
+        }
+
+        // "!3" runs the third command again
+        let line = match line.strip_prefix('!') {
+            Some(n) => {
+                let n: usize = n.parse().map_err(|_| PosError::Usage("!<n>"))?;
+                n.checked_sub(1)
+                    .and_then(|index| self.history.get(index))
+                    .cloned()
+                    .ok_or(PosError::UnknownHistory(n))?
+            }
+            None => String::from(line),
+        };
+
+        let words = split_words(&line);
+        let (command, args) = match words.split_first() {
+            Some((command, args)) => (command.to_lowercase(), args),
+            None => return Ok(Reply::Text(String::new())),
+        };
+        let reply = self.dispatch(&command, args)?;
+        // only what worked is worth running again
+        if command != "history" {
+            self.history.push(line);
+        }
+        Ok(reply)
+    }
+
+    fn dispatch(&mut self, command: &str, args: &[String]) -> Result<Reply, PosError> {
+        let text = match command {
+            "help" => String::from(HELP),
+            "quit" | "exit" => return Ok(Reply::Quit),
+            "history" => self.show_history(),
+            "menu" => self.show_menu(),
+            "time" => self.time(args)?,
+            "date" => self.date(args)?,
+            "party" => self.party(args)?,
+            "waitlist" => self.show_waitlist(),
+            "tables" => self.show_tables(),
+            "seat" => self.seat(args)?,
+            "open" => self.open(args)?,
+            "add" => self.add(args)?,
+            "void" => self.void(args)?,
+            "comp" => self.comp(args)?,
+            "fire" => self.fire(args)?,
+            "kitchen" => self.show_kitchen(),
+            "bump" => self.bump(args)?,
+            "check" => self.show_check(args)?,
+            "bill" => self.show_bill(args)?,
+            "close" => self.close(args)?,
+            "receipt" => self.receipt(args)?,
+            "chit" => self.chit(args)?,
+            "report" => self.report(args)?,
+            "staff" => self.staff(args)?,
+            "shift" => self.shift(args)?,
+            "clockin" => self.clock(args, true)?,
+            "clockout" => self.clock(args, false)?,
+            "sections" => self.show_sections(),
+            "tips" => self.show_tips(),
+            "customer" => self.customer(args)?,
+            "attach" => self.attach(args)?,
+            "promos" => self.show_promotions(),
+            _ => return Err(PosError::UnknownCommand(String::from(command))),
+        };
+        Ok(Reply::Text(text))
+    }
+
+    // runs a file of commands, echoing each one and its reply. blank lines
+    // and lines starting with '#' are skipped; the first failure stops it
+    pub fn run_script(&mut self, script: &str) -> Result<String, ScriptError> {
+        let mut transcript = String::new();
+        for (index, line) in script.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            writeln!(transcript, "> {line}").unwrap();
+            match self.execute(line) {
+                Ok(Reply::Text(text)) => {
+                    if !text.is_empty() {
+                        writeln!(transcript, "{text}").unwrap();
+                    }
+                }
+                Ok(Reply::Quit) => break,
+                Err(error) => {
+                    return Err(ScriptError {
+                        line: index + 1,
+                        error,
+                    });
+                }
+            }
+        }
+        Ok(transcript)
+    }
+
+    fn show_history(&self) -> String {
+        let mut text = String::new();
+        for (index, line) in self.history.iter().enumerate() {
+            writeln!(text, "{:>3}  {line}", index + 1).unwrap();
+        }
+        text.trim_end().to_string()
+    }
+
+    fn show_menu(&self) -> String {
+        let mut text = String::new();
+        for item in self.menu.items() {
+            writeln!(
+                text,
+                "{:<8} {:<18} {:<10} {:>7}",
+                item.code, item.name, item.category, item.price
+            )
+            .unwrap();
+        }
+        text.trim_end().to_string()
+    }
+
+    fn time(&mut self, args: &[String]) -> Result<String, PosError> {
+        match args {
+            [] => {}
+            [time] => self.now = Time::parse(time).ok_or(PosError::Usage("time [HH:MM]"))?,
+            _ => return Err(PosError::Usage("time [HH:MM]")),
+        }
+        Ok(format!("it is {}", self.now))
+    }
+
+    fn date(&mut self, args: &[String]) -> Result<String, PosError> {
+        match args {
+            [] => {}
+            [date] => self.today = Date::parse(date).ok_or(PosError::Usage("date [YYYY-MM-DD]"))?,
+            _ => return Err(PosError::Usage("date [YYYY-MM-DD]")),
+        }
+        Ok(format!("today is {}", self.today))
+    }
+
+    fn party(&mut self, args: &[String]) -> Result<String, PosError> {
+        const USAGE: &str = "party <name> <size>";
+        let [name, size] = args else {
+            return Err(PosError::Usage(USAGE));
+        };
+        let size = parse_number(size, USAGE)?;
+        if size == 0 {
+            return Err(PosError::Usage(USAGE));
+        }
+        let id = self.waitlist.add(name, size, self.now)?;
+        Ok(format!("party {id}: {name}, {size} waiting"))
+    }
+
+    fn show_waitlist(&self) -> String {
+        if self.waitlist.is_empty() {
+            return String::from("nobody is waiting");
+        }
+        let mut text = String::new();
+        for party in self.waitlist.parties() {
+            writeln!(
+                text,
+                "party {}: {}, {} since {} ({} min)",
+                party.id,
+                party.name,
+                party.size,
+                party.arrived_at,
+                self.now.minutes_since(party.arrived_at)
+            )
+            .unwrap();
+        }
+        text.trim_end().to_string()
+    }
+
+    fn show_tables(&self) -> String {
+        let mut text = String::new();
+        for table in self.floor.tables() {
+            let status = match table.status {
+                TableStatus::Open => String::from("open"),
+                TableStatus::Seated { party_id, since } => {
+                    let name = self
+                        .seated_party(party_id)
+                        .map(|party| party.name.as_str())
+                        .unwrap_or("?");
+                    format!("{name} since {since}")
+                }
+            };
+            writeln!(
+                text,
+                "table {} ({} seats): {status}",
+                table.number, table.seats
+            )
+            .unwrap();
+        }
+        text.trim_end().to_string()
+    }
+
+    fn seat(&mut self, args: &[String]) -> Result<String, PosError> {
+        const USAGE: &str = "seat <party> [table]";
+        let (party_id, table) = match args {
+            [party_id] => (party_id, None),
+            [party_id, table] => (party_id, Some(parse_number(table, USAGE)?)),
+            _ => return Err(PosError::Usage(USAGE)),
+        };
+        let party_id = parse_number(party_id, USAGE)?;
+        let party = self
+            .waitlist
+            .get(party_id)
+            .ok_or(HostingError::UnknownParty(party_id))?;
+        let (server, table) = match table {
+            Some(table) => (self.section_server(table), table),
+            None => {
+                let sections = self.sections();
+                let (server, table) = self
+                    .rotation
+                    .next(&sections, &self.floor, party.size)
+                    .ok_or(PosError::NoTableAvailable(party.size))?;
+                (Some(server), table)
+            }
+        };
+        self.floor.seat(party, table, self.now)?;
+        let party = self.waitlist.remove(party_id).unwrap();
+        let text = match server {
+            Some(server) => format!("seated {} at table {table}, server {server}", party.name),
+            None => format!("seated {} at table {table}", party.name),
+        };
+        self.seated.push(party);
+        Ok(text)
+    }
+
+    fn open(&mut self, args: &[String]) -> Result<String, PosError> {
+        const USAGE: &str = "open <table> [server]";
+        let (table, server) = match args {
+            [table] => (table, None),
+            [table, server] => (table, Some(server.clone())),
+            _ => return Err(PosError::Usage(USAGE)),
+        };
+        let number = parse_number(table, USAGE)?;
+        let table = self
+            .floor
+            .table(number)
+            .ok_or(HostingError::UnknownTable(number))?;
+        let TableStatus::Seated { party_id, .. } = table.status else {
+            return Err(PosError::NoPartyAtTable(number));
+        };
+        let guests = self
+            .seated_party(party_id)
+            .map_or(table.seats, |party| party.size);
+        // spell the server's name the way the roster does
+        let server = match server {
+            Some(server) => Some(
+                self.roster
+                    .member(&server)
+                    .map_or(server, |member| member.name.clone()),
+            ),
+            None => self.section_server(number),
+        };
+        let id = self.checks.len() as u32 + 1;
+        let mut check = Check::new(id, number, party_id, guests, self.now);
+        let text = match &server {
+            Some(server) => format!("check {id} open for table {number}, server {server}"),
+            None => format!("check {id} open for table {number}"),
+        };
+        check.server = server;
+        self.checks.push(check);
+        Ok(text)
+    }
+
+    fn add(&mut self, args: &[String]) -> Result<String, PosError> {
+        const USAGE: &str = "add <check> <item> [qty] [modifier...]";
+        let [check_id, code, rest @ ..] = args else {
+            return Err(PosError::Usage(USAGE));
+        };
+        let check_id = parse_number(check_id, USAGE)?;
+        let (quantity, modifiers) = match rest.split_first() {
+            Some((first, modifiers)) if first.chars().all(|c| c.is_ascii_digit()) => {
+                (parse_number(first, USAGE)?, modifiers)
+            }
+            _ => (1, rest),
+        };
+        if quantity == 0 || quantity > MAX_QUANTITY {
+            return Err(PosError::Usage(USAGE));
+        }
+        let item = self
+            .menu
+            .get(code)
+            .ok_or_else(|| PosError::UnknownItem(code.clone()))?
+            .clone();
+        let check = self.check_mut(check_id)?;
+        let line = check.add(&item, quantity, modifiers.to_vec())?;
+        Ok(format!(
+            "check {check_id} line {line}: {quantity} x {}",
+            item.name
+        ))
+    }
+
+    fn void(&mut self, args: &[String]) -> Result<String, PosError> {
+        const USAGE: &str = "void <check> <line>";
+        let [check_id, line] = args else {
+            return Err(PosError::Usage(USAGE));
+        };
+        let check_id = parse_number(check_id, USAGE)?;
+        let line = parse_number(line, USAGE)?;
+        let voided = self.check_mut(check_id)?.void(line)?;
+        Ok(format!("voided {} x {}", voided.quantity, voided.name))
+    }
+
+    fn comp(&mut self, args: &[String]) -> Result<String, PosError> {
+        const USAGE: &str = "comp <check> <line>";
+        let [check_id, line] = args else {
+            return Err(PosError::Usage(USAGE));
+        };
+        let check_id = parse_number(check_id, USAGE)?;
+        let line = parse_number(line, USAGE)?;
+        let comped = self.check_mut(check_id)?.comp(line)?;
+        Ok(format!("comped {} x {}", comped.quantity, comped.name))
+    }
+
+    fn fire(&mut self, args: &[String]) -> Result<String, PosError> {
+        const USAGE: &str = "fire <check>";
+        let [check_id] = args else {
+            return Err(PosError::Usage(USAGE));
+        };
+        let check_id = parse_number(check_id, USAGE)?;
+        let now = self.now;
+        let check = self.check_mut(check_id)?;
+        let items = check.fire()?;
+        let table = check.table;
+        let count = items.len();
+        let ticket = self.kitchen.fire(check_id, table, now, items);
+        Ok(format!(
+            "ticket {ticket}: {count} item(s) fired for table {table}"
+        ))
+    }
+
+    fn show_kitchen(&self) -> String {
+        let mut text = String::new();
+        for ticket in self.kitchen.open_tickets() {
+            writeln!(
+                text,
+                "ticket {} table {} fired {} ({} min)",
+                ticket.id,
+                ticket.table,
+                ticket.fired_at,
+                self.now.minutes_since(ticket.fired_at)
+            )
+            .unwrap();
+            for item in &ticket.items {
+                writeln!(
+                    text,
+                    "  {} x {} [{}]",
+                    item.quantity, item.name, item.station
+                )
+                .unwrap();
+                for modifier in &item.modifiers {
+                    writeln!(text, "      {modifier}").unwrap();
+                }
+            }
+        }
+        if text.is_empty() {
+            return String::from("no open tickets");
+        }
+        text.trim_end().to_string()
+    }
+
+    fn bump(&mut self, args: &[String]) -> Result<String, PosError> {
+        const USAGE: &str = "bump <ticket>";
+        let [ticket] = args else {
+            return Err(PosError::Usage(USAGE));
+        };
+        let ticket = parse_number(ticket, USAGE)?;
+        if !self.kitchen.bump(ticket) {
+            return Err(PosError::UnknownTicket(ticket));
+        }
+        Ok(format!("ticket {ticket} done"))
+    }
+
+    fn show_check(&self, args: &[String]) -> Result<String, PosError> {
+        const USAGE: &str = "check <check>";
+        let [check_id] = args else {
+            return Err(PosError::Usage(USAGE));
+        };
+        let check_id = parse_number(check_id, USAGE)?;
+        let check = self
+            .check(check_id)
+            .ok_or(PosError::UnknownCheck(check_id))?;
+        let mut text = format!(
+            "check {} table {} opened {}",
+            check.id, check.table, check.opened_at
+        );
+        if let Some(closed_at) = check.closed_at {
+            write!(text, ", closed {closed_at}").unwrap();
+        }
+        for line in check.lines() {
+            let state = match (line.voided, line.comped, line.fired) {
+                (true, _, _) => " (void)",
+                (false, true, _) => " (comp)",
+                (false, false, true) => " (fired)",
+                (false, false, false) => "",
+            };
+            write!(
+                text,
+                "\n{:>3}. {} x {:<18} {:>7}{state}",
+                line.number,
+                line.quantity,
+                line.name,
+                line.amount()
+            )
+            .unwrap();
+            for modifier in &line.modifiers {
+                write!(text, "\n         {modifier}").unwrap();
+            }
+        }
+        Ok(text)
+    }
+
+    fn show_bill(&self, args: &[String]) -> Result<String, PosError> {
+        const USAGE: &str = "bill <check> [tip]";
+        let (check_id, tip) = match args {
+            [check_id] => (check_id, None),
+            [check_id, tip] => (check_id, Some(tip)),
+            _ => return Err(PosError::Usage(USAGE)),
+        };
+        let check_id = parse_number(check_id, USAGE)?;
+        let bill = self.bill(check_id, tip.map(String::as_str), USAGE)?;
+        Ok(format_bill(&bill))
+    }
+
+    fn close(&mut self, args: &[String]) -> Result<String, PosError> {
+        const USAGE: &str = "close <check> [tip <t>] [split <n>]";
+        let [check_id, options @ ..] = args else {
+            return Err(PosError::Usage(USAGE));
+        };
+        let check_id = parse_number(check_id, USAGE)?;
+        let mut tip = None;
+        let mut ways = 1;
+        for pair in options.chunks(2) {
+            match pair {
+                [key, value] if key == "tip" => tip = Some(value.as_str()),
+                [key, value] if key == "split" => ways = parse_number(value, USAGE)?,
+                _ => return Err(PosError::Usage(USAGE)),
+            }
+        }
+        if ways == 0 {
+            return Err(PosError::Usage(USAGE));
+        }
+
+        // everything that can fail goes first, so a failure leaves the check
+        // open and nothing half recorded
+        let bill = self.bill(check_id, tip, USAGE)?;
+        let now = self.now;
+        let check = self.check_mut(check_id)?;
+        if check.is_closed() {
+            return Err(ServingError::CheckClosed(check_id).into());
+        }
+        let table = check.table;
+        let party_id = check.party_id;
+        let customer_id = check.customer_id;
+        if self.floor.table(table).is_none() {
+            return Err(HostingError::UnknownTable(table).into());
+        }
+        let points = match customer_id {
+            Some(customer_id) => Some(self.loyalty.record_visit(customer_id, self.today, &bill)?),
+            None => None,
+        };
+
+        self.check_mut(check_id)?.close(now)?;
+        // other checks may still be open on the same table
+        let table_done = self
+            .checks
+            .iter()
+            .filter(|check| check.table == table && check.party_id == party_id)
+            .all(Check::is_closed);
+        if table_done {
+            self.floor.clear(table)?;
+        }
+
+        let payments = bill.split(ways, now);
+        let mut text = format_bill(&bill);
+        if ways > 1 {
+            for (index, payment) in payments.iter().enumerate() {
+                write!(
+                    text,
+                    "\nguest {}: {} + {} tip = {}",
+                    index + 1,
+                    payment.amount,
+                    payment.tip,
+                    payment.amount + payment.tip
+                )
+                .unwrap();
+            }
+        }
+        if let (Some(customer_id), Some(points)) = (customer_id, points) {
+            let customer = self.loyalty.customer(customer_id).unwrap();
+            write!(
+                text,
+                "\n{} earned {points} points, {} in total",
+                customer.name, customer.points
+            )
+            .unwrap();
+        }
+        write!(text, "\ncheck {check_id} closed").unwrap();
+        self.payments.extend(payments);
+        self.bills.push(bill);
+        Ok(text)
+    }
+
+    fn receipt(&self, args: &[String]) -> Result<String, PosError> {
+        const USAGE: &str = "receipt <check> [thermal|text|html]";
+        let (check_id, format) = parse_print_args(args, USAGE)?;
+        // a closed check prints what was actually paid, an open one
+        // prints what is owed so far
+        let bill = match self.bills.iter().find(|bill| bill.check_id == check_id) {
+            Some(bill) => bill.clone(),
+            None => self.bill(check_id, None, USAGE)?,
+        };
+        Ok(format.renderer().receipt(&bill).trim_end().to_string())
+    }
+
+    fn chit(&self, args: &[String]) -> Result<String, PosError> {
+        const USAGE: &str = "chit <ticket> [thermal|text|html]";
+        let (ticket_id, format) = parse_print_args(args, USAGE)?;
+        let ticket = self
+            .kitchen
+            .ticket(ticket_id)
+            .ok_or(PosError::UnknownTicket(ticket_id))?;
+        Ok(format.renderer().chit(ticket).trim_end().to_string())
+    }
+
+    fn report(&self, args: &[String]) -> Result<String, PosError> {
+        let report = DailyReport::build(&self.checks, &self.bills, &self.payments);
+        let text = match args {
+            [] => report.summary(),
+            [format] if format == "csv" => report.to_csv(),
+            [format] if format == "json" => report.to_json(),
+            _ => return Err(PosError::Usage("report [csv|json]")),
+        };
+        Ok(text.trim_end().to_string())
+    }
+
+    fn staff(&mut self, args: &[String]) -> Result<String, PosError> {
+        const USAGE: &str = "staff [add <name> <host|server|cook|runner>]";
+        match args {
+            [] => {
+                let mut text = String::new();
+                for member in self.roster.members() {
+                    let status = if self.roster.is_on_duty(&member.name) {
+                        "on"
+                    } else {
+                        "off"
+                    };
+                    let minutes = self.roster.minutes_worked(&member.name, self.now);
+                    write!(
+                        text,
+                        "\n{:<10} {:<7} {status:<3}  {minutes} min",
+                        member.name, member.role
+                    )
+                    .unwrap();
+                    for shift in self.roster.shifts(&member.name) {
+                        write!(text, "  [{}-{}]", shift.start, shift.end).unwrap();
+                    }
+                }
+                if text.is_empty() {
+                    return Ok(String::from("nobody is on the roster"));
+                }
+                Ok(text.trim_start().to_string())
+            }
+            [add, name, role] if add == "add" => {
+                let role = Role::parse(role).ok_or(PosError::Usage(USAGE))?;
+                self.roster.add(name, role)?;
+                Ok(format!("added {name} as {role}"))
+            }
+            _ => Err(PosError::Usage(USAGE)),
+        }
+    }
+
+    fn shift(&mut self, args: &[String]) -> Result<String, PosError> {
+        const USAGE: &str = "shift <name> <HH:MM> <HH:MM>";
+        let [name, start, end] = args else {
+            return Err(PosError::Usage(USAGE));
+        };
+        let start = Time::parse(start).ok_or(PosError::Usage(USAGE))?;
+        let end = Time::parse(end).ok_or(PosError::Usage(USAGE))?;
+        self.roster.schedule(name, start, end)?;
+        Ok(format!("{name} works {start}-{end}"))
+    }
+
+    fn clock(&mut self, args: &[String], clock_in: bool) -> Result<String, PosError> {
+        let [name] = args else {
+            return Err(PosError::Usage(if clock_in {
+                "clockin <name>"
+            } else {
+                "clockout <name>"
+            }));
+        };
+        if clock_in {
+            self.roster.clock_in(name, self.now)?;
+            Ok(format!("{name} clocked in at {}", self.now))
+        } else {
+            self.roster.clock_out(name, self.now)?;
+            Ok(format!("{name} clocked out at {}", self.now))
+        }
+    }
+
+    fn show_sections(&self) -> String {
+        let sections = self.sections();
+        if sections.is_empty() {
+            return String::from("no servers are clocked in");
+        }
+        let mut text = String::new();
+        for section in sections {
+            let tables: Vec<String> = section.tables.iter().map(u32::to_string).collect();
+            writeln!(text, "{:<10} tables {}", section.server, tables.join(", ")).unwrap();
+        }
+        text.trim_end().to_string()
+    }
+
+    fn show_tips(&self) -> String {
+        let tips: Money = self.bills.iter().map(|bill| bill.tip).sum();
+        let mut text = format!("tip pool {tips}");
+        for share in self.tip_pool.distribute(tips, &self.roster, self.now) {
+            write!(
+                text,
+                "\n  {:<10} {:<7} {:>4} min {:>9}",
+                share.name, share.role, share.minutes, share.amount
+            )
+            .unwrap();
+        }
+        text
+    }
+
+    fn customer(&mut self, args: &[String]) -> Result<String, PosError> {
+        const USAGE: &str = "customer [<id>|add <name> [MM-DD]]";
+        match args {
+            [] => {
+                let mut text = String::new();
+                for customer in self.loyalty.customers() {
+                    writeln!(
+                        text,
+                        "customer {}: {}, {} points, {} visit(s)",
+                        customer.id,
+                        customer.name,
+                        customer.points,
+                        customer.visits.len()
+                    )
+                    .unwrap();
+                }
+                if text.is_empty() {
+                    return Ok(String::from("no loyalty customers yet"));
+                }
+                Ok(text.trim_end().to_string())
+            }
+            [add, name, birthday @ ..] if add == "add" && birthday.len() <= 1 => {
+                let birthday = match birthday {
+                    [birthday] => Some(parse_birthday(birthday).ok_or(PosError::Usage(USAGE))?),
+                    _ => None,
+                };
+                let id = self.loyalty.enroll(name, birthday);
+                Ok(format!("customer {id}: {name} enrolled"))
+            }
+            [id] => {
+                let id = parse_number(id, USAGE)?;
+                let customer = self
+                    .loyalty
+                    .customer(id)
+                    .ok_or(LoyaltyError::UnknownCustomer(id))?;
+                let mut text = format!(
+                    "customer {}: {}, {} points, spent {}",
+                    customer.id,
+                    customer.name,
+                    customer.points,
+                    customer.total_spent()
+                );
+                if let Some((month, day)) = customer.birthday {
+                    write!(text, ", birthday {month:02}-{day:02}").unwrap();
+                }
+                for visit in &customer.visits {
+                    write!(
+                        text,
+                        "\n  {} check {} spent {} earned {}",
+                        visit.date, visit.check_id, visit.spent, visit.points
+                    )
+                    .unwrap();
+                }
+                Ok(text)
+            }
+            _ => Err(PosError::Usage(USAGE)),
+        }
+    }
+
+    fn attach(&mut self, args: &[String]) -> Result<String, PosError> {
+        const USAGE: &str = "attach <check> <customer>";
+        let [check_id, customer_id] = args else {
+            return Err(PosError::Usage(USAGE));
+        };
+        let check_id = parse_number(check_id, USAGE)?;
+        let customer_id = parse_number(customer_id, USAGE)?;
+        let name = self
+            .loyalty
+            .customer(customer_id)
+            .ok_or(LoyaltyError::UnknownCustomer(customer_id))?
+            .name
+            .clone();
+        let check = self.check_mut(check_id)?;
+        if check.is_closed() {
+            return Err(ServingError::CheckClosed(check_id).into());
+        }
+        check.customer_id = Some(customer_id);
+        Ok(format!("check {check_id} is on {name}'s account"))
+    }
+
+    fn show_promotions(&self) -> String {
+        let mut text = String::new();
+        for promotion in self.promotions.promotions() {
+            writeln!(text, "{:<18} {}", promotion.name, promotion.rule).unwrap();
+        }
+        if text.is_empty() {
+            return String::from("no promotions running");
+        }
+        text.trim_end().to_string()
+    }
+
+    // works out the bill for a check, with an optional tip given as an
+    // amount ("5.00") or a percentage of the subtotal ("18%")
+    fn bill(
+        &self,
+        check_id: u32,
+        tip: Option<&str>,
+        usage: &'static str,
+    ) -> Result<Bill, PosError> {
+        let check = self
+            .check(check_id)
+            .ok_or(PosError::UnknownCheck(check_id))?;
+        let context = PromotionContext {
+            date: self.today,
+            customer: check
+                .customer_id
+                .and_then(|customer| self.loyalty.customer(customer)),
+        };
+        let bill = Bill::compute_with(check, self.tax_rate, &self.promotions, &context);
+        let tip = match tip {
+            None => Money::ZERO,
+            Some(tip) => match tip.strip_suffix('%') {
+                Some(percent) => {
+                    let percent = Money::parse(percent).ok_or(PosError::Usage(usage))?;
+                    // "18.5%" is 1850 basis points
+                    let basis_points = u32::try_from(percent.cents())
+                        .ok()
+                        .filter(|&basis_points| basis_points <= MAX_TIP_PERCENT)
+                        .ok_or(PosError::Usage(usage))?;
+                    bill.subtotal.percent(basis_points)
+                }
+                None => Money::parse(tip).ok_or(PosError::Usage(usage))?,
+            },
+        };
+        if tip < Money::ZERO || tip > MAX_TIP {
+            return Err(PosError::Usage(usage));
+        }
+        bill.with_tip(tip).ok_or(PosError::Usage(usage))
+    }
+
+    fn check_mut(&mut self, id: u32) -> Result<&mut Check, PosError> {
+        self.checks
+            .iter_mut()
+            .find(|check| check.id == id)
+            .ok_or(PosError::UnknownCheck(id))
+    }
+
+    fn seated_party(&self, party_id: u32) -> Option<&Party> {
+        self.seated.iter().find(|party| party.id == party_id)
+    }
+}
+
+fn format_bill(bill: &Bill) -> String {
+    let mut text = format!("bill for check {} table {}", bill.check_id, bill.table);
+    for line in &bill.lines {
+        write!(
+            text,
+            "\n  {} x {:<18} {:>8}",
+            line.quantity, line.name, line.amount
+        )
+        .unwrap();
+    }
+    write!(text, "\n  {:<22} {:>8}", "subtotal", bill.subtotal).unwrap();
+    for discount in &bill.discounts {
+        write!(
+            text,
+            "\n  {:<22} {:>8}\n    {}",
+            discount.promotion, -discount.amount, discount.reason
+        )
+        .unwrap();
+    }
+    write!(text, "\n  {:<22} {:>8}", "tax", bill.tax).unwrap();
+    if !bill.tip.is_zero() {
+        write!(text, "\n  {:<22} {:>8}", "tip", bill.tip).unwrap();
+    }
+    write!(text, "\n  {:<22} {:>8}", "total", bill.total).unwrap();
+    text
+}
+
+fn parse_print_args(args: &[String], usage: &'static str) -> Result<(u32, Format), PosError> {
+    match args {
+        [id] => Ok((parse_number(id, usage)?, Format::Thermal)),
+        [id, format] => Ok((
+            parse_number(id, usage)?,
+            Format::parse(format).ok_or(PosError::Usage(usage))?,
+        )),
+        _ => Err(PosError::Usage(usage)),
+    }
+}
+
+fn parse_number(text: &str, usage: &'static str) -> Result<u32, PosError> {
+    text.parse().map_err(|_| PosError::Usage(usage))
+}
+
+// splits a command line on whitespace, keeping "double quoted" words together
+fn split_words(line: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut quoted = false;
+    let mut in_word = false;
+    for c in line.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                in_word = true;
+            }
+            c if c.is_whitespace() && !quoted => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            c => {
+                word.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if in_word {
+        words.push(word);
+    }
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pos() -> Pos {
+        Pos::new(Date::new(2025, 6, 14).unwrap(), Time::new(18, 0).unwrap())
+    }
+
+    fn run(pos: &mut Pos, line: &str) -> String {
+        match pos.execute(line) {
+            Ok(Reply::Text(text)) => text,
+            Ok(Reply::Quit) => panic!("'{line}' quit unexpectedly"),
+            Err(error) => panic!("'{line}' failed: {error}"),
+        }
+    }
+
+    #[test]
+    fn test_split_words_keeps_quotes() {
+        assert_eq!(
+            split_words(r#"add 1 burger 2 "no onions"  extra"#),
+            vec!["add", "1", "burger", "2", "no onions", "extra"]
+        );
+        assert_eq!(
+            split_words(r#"add 1 soda """#),
+            vec!["add", "1", "soda", ""]
+        );
+    }
+
+    #[test]
+    fn test_seat_order_and_close() {
+        let mut pos = pos();
+        run(&mut pos, "party Smith 2");
+        run(&mut pos, "seat 1 1");
+        assert_eq!(run(&mut pos, "open 1"), "check 1 open for table 1");
+        run(&mut pos, r#"add 1 burger 2 "no onions""#);
+        run(&mut pos, "add 1 soda");
+        run(&mut pos, "fire 1");
+        assert_eq!(pos.kitchen.open_tickets().count(), 1);
+
+        run(&mut pos, "time 19:10");
+        let text = run(&mut pos, "close 1 tip 6 split 2");
+        assert!(text.contains("guest 2"), "{text}");
+        assert!(pos.check(1).unwrap().is_closed());
+        assert!(pos.floor.table(1).unwrap().is_open());
+        assert_eq!(pos.payments.len(), 2);
+        let paid: Money = pos.payments.iter().map(|p| p.amount + p.tip).sum();
+        assert_eq!(paid, pos.bills[0].total);
+    }
+
+    #[test]
+    fn test_errors_are_reported() {
+        let mut pos = pos();
+        assert_eq!(
+            pos.execute("frobnicate"),
+            Err(PosError::UnknownCommand(String::from("frobnicate")))
+        );
+        assert_eq!(pos.execute("open 3"), Err(PosError::NoPartyAtTable(3)));
+        assert_eq!(pos.execute("add 9 burger"), Err(PosError::UnknownCheck(9)));
+        assert_eq!(
+            pos.execute("seat"),
+            Err(PosError::Usage("seat <party> [table]"))
+        );
+
+        run(&mut pos, "party Lee 6");
+        assert_eq!(
+            pos.execute("seat 1 2"),
+            Err(PosError::Hosting(HostingError::TooSmall {
+                table: 2,
+                seats: 2,
+                party: 6
+            }))
+        );
+    }
+
+    #[test]
+    fn test_percent_tip() {
+        let mut pos = pos();
+        run(&mut pos, "party Park 2");
+        run(&mut pos, "seat 1 2");
+        run(&mut pos, "open 2");
+        run(&mut pos, "add 1 steak");
+        let text = run(&mut pos, "bill 1 20%");
+        // 20% of 32.00
+        assert!(text.contains("tip                        6.40"), "{text}");
+    }
+
+    #[test]
+    fn test_tips_and_quantities_are_limited() {
+        let mut pos = pos();
+        run(&mut pos, "party A 2");
+        run(&mut pos, "seat 1 1");
+        run(&mut pos, "open 1");
+        let usage = "add <check> <item> [qty] [modifier...]";
+        assert_eq!(
+            pos.execute("add 1 steak 4294967295"),
+            Err(PosError::Usage(usage))
+        );
+        run(&mut pos, "add 1 steak 999");
+        let usage = "bill <check> [tip]";
+        assert_eq!(
+            pos.execute("bill 1 92233720368547758"),
+            Err(PosError::Usage(usage))
+        );
+        assert_eq!(pos.execute("bill 1 101%"), Err(PosError::Usage(usage)));
+        run(&mut pos, "bill 1 10000");
+        run(&mut pos, "bill 1 20%");
+    }
+
+    #[test]
+    fn test_receipt_and_chit() {
+        let mut pos = pos();
+        run(&mut pos, "party Park 2");
+        run(&mut pos, "seat 1 2");
+        run(&mut pos, "open 2");
+        run(&mut pos, r#"add 1 steak 1 "medium rare""#);
+        run(&mut pos, "add 1 wine 2");
+        run(&mut pos, "fire 1");
+
+        let chit = run(&mut pos, "chit 1");
+        assert!(
+            chit.contains("*** GRILL ***") && chit.contains("*** BAR ***"),
+            "{chit}"
+        );
+
+        run(&mut pos, "close 1 tip 10");
+        let receipt = run(&mut pos, "receipt 1 html");
+        assert!(
+            receipt.contains("<tr><th>Tip</th><td>10.00</td></tr>"),
+            "{receipt}"
+        );
+        assert_eq!(
+            pos.execute("receipt 1 pdf"),
+            Err(PosError::Usage("receipt <check> [thermal|text|html]"))
+        );
+    }
+
+    #[test]
+    fn test_report_after_service() {
+        let mut pos = pos();
+        run(&mut pos, "party Park 2");
+        run(&mut pos, "seat 1 2");
+        run(&mut pos, "open 2 ana");
+        run(&mut pos, "add 1 steak");
+        run(&mut pos, "add 1 pie");
+        run(&mut pos, "comp 1 2");
+        run(&mut pos, "close 1 tip 5");
+
+        let csv = run(&mut pos, "report csv");
+        assert!(csv.contains("adjustments,comps,1,7.50"), "{csv}");
+        assert!(csv.contains("server_tips,ana,2,5.00"), "{csv}");
+        let json = run(&mut pos, "report json");
+        assert!(json.contains("\"net_sales\": \"32.00\""), "{json}");
+    }
+
+    #[test]
+    fn test_staffed_service() {
+        let mut pos = pos();
+        run(&mut pos, "staff add Ana server");
+        run(&mut pos, "staff add Ben server");
+        run(&mut pos, "staff add Cy runner");
+        assert_eq!(
+            pos.execute("staff add Dee chef"),
+            Err(PosError::Usage(
+                "staff [add <name> <host|server|cook|runner>]"
+            ))
+        );
+        assert_eq!(
+            pos.execute("seat 1"),
+            Err(PosError::Hosting(HostingError::UnknownParty(1)))
+        );
+
+        run(&mut pos, "time 17:00");
+        run(&mut pos, "clockin ana");
+        run(&mut pos, "clockin ben");
+        run(&mut pos, "clockin cy");
+        let sections = run(&mut pos, "sections");
+        assert_eq!(
+            sections,
+            "Ana        tables 1, 2, 3, 4, 5\nBen        tables 6, 7, 8"
+        );
+
+        run(&mut pos, "time 18:00");
+        run(&mut pos, "party Smith 2");
+        run(&mut pos, "party Jones 4");
+        assert_eq!(
+            run(&mut pos, "seat 1"),
+            "seated Smith at table 1, server Ana"
+        );
+        assert_eq!(
+            run(&mut pos, "seat 2"),
+            "seated Jones at table 6, server Ben"
+        );
+
+        run(&mut pos, "open 6");
+        assert_eq!(pos.check(1).unwrap().server.as_deref(), Some("Ben"));
+        run(&mut pos, "add 1 steak 2");
+        run(&mut pos, "close 1 tip 15");
+
+        run(&mut pos, "time 21:00");
+        let tips = run(&mut pos, "tips");
+        assert!(tips.starts_with("tip pool 15.00"), "{tips}");
+        assert!(tips.contains("Cy"), "{tips}");
+    }
+
+    #[test]
+    fn test_loyalty_and_promotions() {
+        let mut pos = pos();
+        assert_eq!(
+            run(&mut pos, "customer add Ana 06-14"),
+            "customer 1: Ana enrolled"
+        );
+        run(&mut pos, "time 17:00");
+        run(&mut pos, "party Ana 2");
+        run(&mut pos, "seat 1 2");
+        run(&mut pos, "open 2");
+        run(&mut pos, "attach 1 1");
+        assert_eq!(
+            pos.execute("attach 1 7"),
+            Err(PosError::Loyalty(LoyaltyError::UnknownCustomer(7)))
+        );
+        run(&mut pos, "add 1 beer 2");
+        run(&mut pos, "add 1 burger 2");
+        run(&mut pos, "add 1 pie");
+
+        let bill = run(&mut pos, "bill 1");
+        assert!(bill.contains("Happy Hour"), "{bill}");
+        assert!(
+            bill.contains("happy birthday Ana: Peach Pie on the house"),
+            "{bill}"
+        );
+
+        run(&mut pos, "time 18:30");
+        let text = run(&mut pos, "close 1");
+        // 14 + 30 + 7.50, less 7 and 7.50 off
+        assert!(text.contains("Ana earned 37 points, 37 in total"), "{text}");
+        let customer = run(&mut pos, "customer 1");
+        assert!(
+            customer.contains("2025-06-14 check 1 spent 37.00 earned 37"),
+            "{customer}"
+        );
+        assert_eq!(pos.bills[0].discount_total(), Money::from_cents(1450));
+    }
+
+    #[test]
+    fn test_failed_close_changes_nothing() {
+        let mut pos = pos();
+        run(&mut pos, "customer add Ana");
+        run(&mut pos, "party Ana 2");
+        run(&mut pos, "seat 1 2");
+        run(&mut pos, "open 2");
+        run(&mut pos, "attach 1 1");
+        run(&mut pos, "add 1 burger");
+        // points for this check are already in, so recording them again fails
+        let bill = pos.bill(1, None, "").unwrap();
+        pos.loyalty.record_visit(1, pos.today, &bill).unwrap();
+
+        assert_eq!(
+            pos.execute("close 1"),
+            Err(PosError::Loyalty(LoyaltyError::AlreadyRecorded(1)))
+        );
+        assert!(!pos.check(1).unwrap().is_closed());
+        assert_ne!(pos.floor.table(2).unwrap().status, TableStatus::Open);
+        assert!(pos.bills.is_empty());
+        assert!(pos.payments.is_empty());
+    }
+
+    #[test]
+    fn test_history_replay() {
+        let mut pos = pos();
+        run(&mut pos, "party Smith 2");
+        run(&mut pos, "!1");
+        assert_eq!(pos.waitlist.len(), 2);
+        assert_eq!(pos.history(), ["party Smith 2", "party Smith 2"]);
+        assert_eq!(pos.execute("!7"), Err(PosError::UnknownHistory(7)));
+
+        // typos and failures aren't kept to be replayed
+        assert!(pos.execute("pary Jones 4").is_err());
+        assert!(pos.execute("seat 9 9").is_err());
+        assert_eq!(pos.history(), ["party Smith 2", "party Smith 2"]);
+    }
+
+    #[test]
+    fn test_sample_script_runs() {
+        let mut pos = pos();
+        let transcript = pos
+            .run_script(include_str!("../scripts/dinner_service.pos"))
+            .unwrap_or_else(|error| panic!("script failed at {error}"));
+        assert!(transcript.contains("check 2 closed"), "{transcript}");
+        assert!(pos.checks.iter().all(Check::is_closed));
+    }
+
+    #[test]
+    fn test_script_reports_failing_line() {
+        let mut pos = pos();
+        let error = pos.run_script("# comment\n\nmenu\nseat 4 4\n").unwrap_err();
+        assert_eq!(error.line, 4);
+    }
+}
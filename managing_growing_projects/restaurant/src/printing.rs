@@ -0,0 +1,540 @@
+use std::fmt::Write;
+
+use crate::back_of_house::kitchen::{Station, Ticket, TicketItem};
+use crate::front_of_house::serving::Bill;
+use crate::money::Money;
+
+pub const RESTAURANT_NAME: &str = "The Rusty Spoon";
+
+// most 80mm thermal printers fit 42 characters of their default font
+pub const THERMAL_WIDTH: usize = 42;
+
+// something that can turn a bill or a kitchen ticket into printable text
+pub trait Renderer {
+    fn receipt(&self, bill: &Bill) -> String;
+    fn chit(&self, ticket: &Ticket) -> String;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Thermal,
+    Text,
+    Html,
+}
+
+impl Format {
+    pub fn parse(text: &str) -> Option<Format> {
+        match text.to_lowercase().as_str() {
+            "thermal" => Some(Format::Thermal),
+            "text" => Some(Format::Text),
+            "html" => Some(Format::Html),
+            _ => None,
+        }
+    }
+
+    pub fn renderer(self) -> Box<dyn Renderer> {
+        match self {
+            Format::Thermal => Box::new(Thermal::new(THERMAL_WIDTH)),
+            Format::Text => Box::new(PlainText),
+            Format::Html => Box::new(Html),
+        }
+    }
+}
+
+// the items on a ticket, grouped by the station that makes them, in
+// station order so every chit comes out the same way
+pub fn by_station(ticket: &Ticket) -> Vec<(Station, Vec<&TicketItem>)> {
+    let mut groups: Vec<(Station, Vec<&TicketItem>)> = Vec::new();
+    for item in &ticket.items {
+        match groups
+            .iter_mut()
+            .find(|(station, _)| *station == item.station)
+        {
+            Some((_, items)) => items.push(item),
+            None => groups.push((item.station, vec![item])),
+        }
+    }
+    groups.sort_by_key(|(station, _)| *station);
+    groups
+}
+
+// fixed width output for a receipt printer; every line is exactly
+// `width` characters or shorter
+#[derive(Debug, Clone, Copy)]
+pub struct Thermal {
+    width: usize,
+}
+
+impl Thermal {
+    pub fn new(width: usize) -> Thermal {
+        // anything narrower can't fit a label and a price side by side
+        Thermal {
+            width: width.max(24),
+        }
+    }
+
+    fn centered(&self, text: &str) -> String {
+        let text = truncate(text, self.width);
+        let padding = (self.width - text.chars().count()) / 2;
+        format!("{}{text}", " ".repeat(padding))
+    }
+
+    // label on the left, amount flush right, label cut short if needed
+    fn columns(&self, label: &str, amount: &str) -> String {
+        let room = self.width - amount.chars().count() - 1;
+        let label = truncate(label, room);
+        format!("{label:<room$} {amount}")
+    }
+
+    fn rule(&self, c: char) -> String {
+        c.to_string().repeat(self.width)
+    }
+}
+
+impl Renderer for Thermal {
+    fn receipt(&self, bill: &Bill) -> String {
+        let mut lines = vec![
+            self.centered(RESTAURANT_NAME),
+            self.centered(&format!("Check {} - Table {}", bill.check_id, bill.table)),
+            self.rule('='),
+        ];
+        for line in &bill.lines {
+            let amount = if line.comped {
+                String::from("COMP")
+            } else {
+                line.amount.to_string()
+            };
+            lines.push(self.columns(&format!("{} {}", line.quantity, line.name), &amount));
+            if line.quantity > 1 {
+                lines.push(format!("    @ {} ea", line.unit_price));
+            }
+            for modifier in &line.modifiers {
+                for (index, part) in wrap(modifier, self.width - 6).iter().enumerate() {
+                    let bullet = if index == 0 { "-" } else { " " };
+                    lines.push(format!("    {bullet} {part}"));
+                }
+            }
+        }
+        lines.push(self.rule('-'));
+        lines.push(self.columns("Subtotal", &bill.subtotal.to_string()));
+        for discount in &bill.discounts {
+            lines.push(self.columns(&discount.promotion, &(-discount.amount).to_string()));
+            for part in wrap(&discount.reason, self.width - 2) {
+                lines.push(format!("  {part}"));
+            }
+        }
+        lines.push(self.columns(
+            &format!("Tax {}", format_rate(bill.tax_rate)),
+            &bill.tax.to_string(),
+        ));
+        if bill.tip.is_zero() {
+            // leave room for the guest to write one in
+            lines.push(self.columns("Tip", "__________"));
+            lines.push(self.columns("Total", "__________"));
+        } else {
+            lines.push(self.columns("Tip", &bill.tip.to_string()));
+            lines.push(self.columns("TOTAL", &bill.total.to_string()));
+        }
+        lines.push(self.rule('='));
+        lines.push(self.centered("Thank you!"));
+        lines.join("\n") + "\n"
+    }
+
+    fn chit(&self, ticket: &Ticket) -> String {
+        let mut chits = Vec::new();
+        for (station, items) in by_station(ticket) {
+            let mut lines = vec![
+                self.centered(&format!("*** {} ***", station.name().to_uppercase())),
+                self.columns(
+                    &format!("Table {}  Ticket {}", ticket.table, ticket.id),
+                    &ticket.fired_at.to_string(),
+                ),
+                self.rule('-'),
+            ];
+            for item in items {
+                lines.push(truncate(
+                    &format!("{:>2} x {}", item.quantity, item.name),
+                    self.width,
+                ));
+                for modifier in &item.modifiers {
+                    for (index, part) in wrap(modifier, self.width - 8).iter().enumerate() {
+                        let bullet = if index == 0 { "**" } else { "  " };
+                        lines.push(format!("     {bullet} {part}"));
+                    }
+                }
+            }
+            chits.push(lines.join("\n"));
+        }
+        // one slip per station printer, separated by a tear line
+        let cut = format!("\n{}\n", self.rule('~'));
+        chits.join(&cut) + "\n"
+    }
+}
+
+// unpadded text, for pasting into email or a chat
+#[derive(Debug, Clone, Copy)]
+pub struct PlainText;
+
+impl Renderer for PlainText {
+    fn receipt(&self, bill: &Bill) -> String {
+        let mut text = format!(
+            "{RESTAURANT_NAME}\nCheck {}, table {}\n\n",
+            bill.check_id, bill.table
+        );
+        for line in &bill.lines {
+            if line.comped {
+                writeln!(
+                    text,
+                    "{} x {} @ {}: comp",
+                    line.quantity, line.name, line.unit_price
+                )
+                .unwrap();
+            } else {
+                writeln!(
+                    text,
+                    "{} x {} @ {}: {}",
+                    line.quantity, line.name, line.unit_price, line.amount
+                )
+                .unwrap();
+            }
+            for modifier in &line.modifiers {
+                writeln!(text, "  - {modifier}").unwrap();
+            }
+        }
+        writeln!(text).unwrap();
+        writeln!(text, "Subtotal: {}", bill.subtotal).unwrap();
+        for discount in &bill.discounts {
+            writeln!(
+                text,
+                "{}: -{} ({})",
+                discount.promotion, discount.amount, discount.reason
+            )
+            .unwrap();
+        }
+        writeln!(text, "Tax ({}): {}", format_rate(bill.tax_rate), bill.tax).unwrap();
+        if !bill.tip.is_zero() {
+            writeln!(text, "Tip: {}", bill.tip).unwrap();
+        }
+        writeln!(text, "Total: {}", bill.total).unwrap();
+        text
+    }
+
+    fn chit(&self, ticket: &Ticket) -> String {
+        let mut text = format!(
+            "Ticket {}, table {}, fired {}\n",
+            ticket.id, ticket.table, ticket.fired_at
+        );
+        for (station, items) in by_station(ticket) {
+            writeln!(text, "\n[{station}]").unwrap();
+            for item in items {
+                writeln!(text, "{} x {}", item.quantity, item.name).unwrap();
+                for modifier in &item.modifiers {
+                    writeln!(text, "  - {modifier}").unwrap();
+                }
+            }
+        }
+        text
+    }
+}
+
+// a standalone fragment that can be dropped into a page or an email
+#[derive(Debug, Clone, Copy)]
+pub struct Html;
+
+impl Renderer for Html {
+    fn receipt(&self, bill: &Bill) -> String {
+        let mut html = String::from("<div class=\"receipt\">\n");
+        writeln!(html, "  <h1>{}</h1>", escape(RESTAURANT_NAME)).unwrap();
+        writeln!(
+            html,
+            "  <p>Check {}, table {}</p>",
+            bill.check_id, bill.table
+        )
+        .unwrap();
+        html.push_str("  <table>\n");
+        for line in &bill.lines {
+            let amount = if line.comped {
+                String::from("comp")
+            } else {
+                line.amount.to_string()
+            };
+            writeln!(
+                html,
+                "    <tr class=\"item\"><td>{}</td><td>{}</td><td>{amount}</td></tr>",
+                line.quantity,
+                escape(&line.name),
+            )
+            .unwrap();
+            for modifier in &line.modifiers {
+                writeln!(
+                    html,
+                    "    <tr class=\"modifier\"><td></td><td colspan=\"2\">{}</td></tr>",
+                    escape(modifier)
+                )
+                .unwrap();
+            }
+        }
+        html.push_str("  </table>\n  <table class=\"totals\">\n");
+        let mut totals = vec![(String::from("Subtotal"), bill.subtotal)];
+        for discount in &bill.discounts {
+            totals.push((discount.promotion.clone(), -discount.amount));
+        }
+        totals.push((format!("Tax ({})", format_rate(bill.tax_rate)), bill.tax));
+        if !bill.tip.is_zero() {
+            totals.push((String::from("Tip"), bill.tip));
+        }
+        totals.push((String::from("Total"), bill.total));
+        for (label, amount) in totals {
+            writeln!(
+                html,
+                "    <tr><th>{}</th><td>{amount}</td></tr>",
+                escape(&label)
+            )
+            .unwrap();
+        }
+        html.push_str("  </table>\n</div>\n");
+        html
+    }
+
+    fn chit(&self, ticket: &Ticket) -> String {
+        let mut html = format!(
+            "<div class=\"chit\">\n  <h1>Ticket {}, table {}</h1>\n  <p>Fired {}</p>\n",
+            ticket.id, ticket.table, ticket.fired_at
+        );
+        for (station, items) in by_station(ticket) {
+            writeln!(
+                html,
+                "  <section class=\"station\">\n    <h2>{station}</h2>\n    <ul>"
+            )
+            .unwrap();
+            for item in items {
+                write!(html, "      <li>{} x {}", item.quantity, escape(&item.name)).unwrap();
+                if !item.modifiers.is_empty() {
+                    html.push_str("<ul>");
+                    for modifier in &item.modifiers {
+                        write!(html, "<li>{}</li>", escape(modifier)).unwrap();
+                    }
+                    html.push_str("</ul>");
+                }
+                html.push_str("</li>\n");
+            }
+            html.push_str("    </ul>\n  </section>\n");
+        }
+        html.push_str("</div>\n");
+        html
+    }
+}
+
+// 825 basis points prints as "8.25%"
+fn format_rate(basis_points: u32) -> String {
+    format!("{}%", Money::from_cents(i64::from(basis_points)))
+}
+
+fn truncate(text: &str, width: usize) -> String {
+    text.chars().take(width).collect()
+}
+
+// breaks text into lines no longer than `width`, splitting on spaces
+// where it can and mid-word where it can't
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let mut word: Vec<char> = word.chars().collect();
+        while word.len() > width {
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            lines.push(word.drain(..width).collect());
+        }
+        let word: String = word.into_iter().collect();
+        if word.is_empty() {
+            continue;
+        }
+        if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > width {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(&word);
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::back_of_house::menu::Menu;
+    use crate::clock::Time;
+    use crate::front_of_house::serving::Check;
+    use crate::promotions::Discount;
+    use std::{env, fs, path::PathBuf};
+
+    // compares against src/snapshots/<name>; run with UPDATE_SNAPSHOTS=1
+    // to write the current output instead after an intended change
+    fn assert_snapshot(name: &str, actual: &str) {
+        let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "src", "snapshots", name]
+            .iter()
+            .collect();
+        if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, actual).unwrap();
+            return;
+        }
+        let expected = fs::read_to_string(&path)
+            .unwrap_or_else(|_| panic!("missing snapshot {name}, run with UPDATE_SNAPSHOTS=1"));
+        assert_eq!(actual, expected, "snapshot {name} changed");
+    }
+
+    fn check() -> Check {
+        let menu = Menu::house();
+        let mut check = Check::new(7, 4, 1, 3, Time::new(18, 30).unwrap());
+        check.add(menu.get("wings").unwrap(), 1, vec![]).unwrap();
+        check
+            .add(
+                menu.get("burger").unwrap(),
+                2,
+                vec![
+                    String::from("no onions"),
+                    String::from("sub gluten free bun & extra crispy bacon on the side"),
+                ],
+            )
+            .unwrap();
+        check
+            .add(
+                menu.get("steak").unwrap(),
+                1,
+                vec![String::from("medium rare")],
+            )
+            .unwrap();
+        check.add(menu.get("fries").unwrap(), 1, vec![]).unwrap();
+        check.add(menu.get("beer").unwrap(), 3, vec![]).unwrap();
+        check
+    }
+
+    fn bill() -> Bill {
+        Bill::compute(&check(), 825)
+            .with_tip(Money::from_cents(1500))
+            .unwrap()
+    }
+
+    fn ticket() -> Ticket {
+        Ticket {
+            id: 12,
+            check_id: 7,
+            table: 4,
+            fired_at: Time::new(18, 42).unwrap(),
+            items: check().fire().unwrap(),
+            done: false,
+        }
+    }
+
+    #[test]
+    fn test_thermal_receipt() {
+        let receipt = Thermal::new(THERMAL_WIDTH).receipt(&bill());
+        assert!(
+            receipt
+                .lines()
+                .all(|line| line.chars().count() <= THERMAL_WIDTH)
+        );
+        assert_snapshot("thermal_receipt.txt", &receipt);
+    }
+
+    #[test]
+    fn test_thermal_receipt_without_tip() {
+        let bill = Bill::compute(&check(), 825);
+        assert_snapshot(
+            "thermal_receipt_no_tip.txt",
+            &Thermal::new(THERMAL_WIDTH).receipt(&bill),
+        );
+    }
+
+    #[test]
+    fn test_thermal_chit() {
+        let chit = Thermal::new(THERMAL_WIDTH).chit(&ticket());
+        assert!(
+            chit.lines()
+                .all(|line| line.chars().count() <= THERMAL_WIDTH)
+        );
+        assert_snapshot("thermal_chit.txt", &chit);
+    }
+
+    #[test]
+    fn test_discounts_are_itemized() {
+        let bill = bill().with_discounts(vec![Discount {
+            promotion: String::from("Happy Hour"),
+            reason: String::from("50% off 3 drink item(s), check opened 17:30 during 16:00-18:00"),
+            amount: Money::from_cents(1050),
+        }]);
+        let receipt = Thermal::new(THERMAL_WIDTH).receipt(&bill);
+        assert!(
+            receipt
+                .lines()
+                .all(|line| line.chars().count() <= THERMAL_WIDTH)
+        );
+        assert_snapshot("thermal_receipt_discount.txt", &receipt);
+        assert!(
+            PlainText
+                .receipt(&bill)
+                .contains("Happy Hour: -10.50 (50% off")
+        );
+        assert!(
+            Html.receipt(&bill)
+                .contains("<tr><th>Happy Hour</th><td>-10.50</td></tr>")
+        );
+    }
+
+    #[test]
+    fn test_plain_text() {
+        assert_snapshot("receipt.txt", &PlainText.receipt(&bill()));
+        assert_snapshot("chit.txt", &PlainText.chit(&ticket()));
+    }
+
+    #[test]
+    fn test_html() {
+        assert_snapshot("receipt.html", &Html.receipt(&bill()));
+        assert_snapshot("chit.html", &Html.chit(&ticket()));
+    }
+
+    #[test]
+    fn test_by_station_groups_in_station_order() {
+        let stations: Vec<Station> = by_station(&ticket())
+            .into_iter()
+            .map(|(station, _)| station)
+            .collect();
+        assert_eq!(stations, vec![Station::Grill, Station::Fry, Station::Bar]);
+    }
+
+    #[test]
+    fn test_wrap() {
+        assert_eq!(
+            wrap("extra crispy bacon", 10),
+            vec!["extra", "crispy", "bacon"]
+        );
+        assert_eq!(wrap("abcdefghij klm", 4), vec!["abcd", "efgh", "ij", "klm"]);
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("mac & <cheese>"), "mac &amp; &lt;cheese&gt;");
+    }
+}
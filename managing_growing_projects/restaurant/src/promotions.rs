@@ -0,0 +1,431 @@
+use std::cmp::Reverse;
+use std::fmt;
+
+use crate::back_of_house::menu::Category;
+use crate::clock::{Date, Time};
+use crate::front_of_house::serving::Check;
+use crate::loyalty::Customer;
+use crate::money::Money;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Rule {
+    // a percentage (in basis points) off a category for checks opened
+    // between start and end
+    HappyHour {
+        start: Time,
+        end: Time,
+        category: Category,
+        percent: u32,
+    },
+    // every second item in the category is free, cheapest ones first
+    BuyOneGetOne {
+        category: Category,
+    },
+    // the priciest item in the category is free on the guest's birthday
+    BirthdayComp {
+        category: Category,
+    },
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Rule::HappyHour {
+                start,
+                end,
+                category,
+                percent,
+            } => write!(
+                f,
+                "{} off {category} {start}-{end}",
+                format_percent(*percent)
+            ),
+            Rule::BuyOneGetOne { category } => write!(f, "buy one get one {category}"),
+            Rule::BirthdayComp { category } => write!(f, "free {category} on your birthday"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Promotion {
+    pub name: String,
+    pub rule: Rule,
+}
+
+// money taken off a bill, and why
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Discount {
+    // the name of the promotion that produced it
+    pub promotion: String,
+    pub reason: String,
+    pub amount: Money,
+}
+
+// what a promotion might need to know beyond the check itself
+#[derive(Debug, Clone, Copy)]
+pub struct PromotionContext<'a> {
+    pub date: Date,
+    pub customer: Option<&'a Customer>,
+}
+
+// the portions of one line that no promotion has discounted yet; "3 x
+// beer" starts with three left so a deal can take some and not the others
+#[derive(Debug)]
+struct Portions<'a> {
+    name: &'a str,
+    category: Category,
+    price: Money,
+    left: u32,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Promotions {
+    promotions: Vec<Promotion>,
+}
+
+impl Promotions {
+    pub fn new() -> Promotions {
+        Promotions {
+            promotions: Vec::new(),
+        }
+    }
+
+    // what we run day to day
+    pub fn house() -> Promotions {
+        let mut promotions = Promotions::new();
+        promotions.add(
+            "Happy Hour",
+            Rule::HappyHour {
+                start: Time::new(16, 0).unwrap(),
+                end: Time::new(18, 0).unwrap(),
+                category: Category::Drink,
+                percent: 5_000,
+            },
+        );
+        promotions.add(
+            "Appetizer BOGO",
+            Rule::BuyOneGetOne {
+                category: Category::Appetizer,
+            },
+        );
+        promotions.add(
+            "Birthday Dessert",
+            Rule::BirthdayComp {
+                category: Category::Dessert,
+            },
+        );
+        promotions
+    }
+
+    pub fn add(&mut self, name: &str, rule: Rule) {
+        self.promotions.push(Promotion {
+            name: String::from(name),
+            rule,
+        });
+    }
+
+    pub fn promotions(&self) -> &[Promotion] {
+        &self.promotions
+    }
+
+    // works out every discount the check qualifies for. promotions are
+    // tried in the order they were added and never stack: once an item
+    // has been discounted, later promotions skip it
+    pub fn apply(&self, check: &Check, context: &PromotionContext) -> Vec<Discount> {
+        let mut lines: Vec<Portions> = check
+            .live_lines()
+            .filter(|line| !line.comped)
+            .map(|line| Portions {
+                name: &line.name,
+                category: line.category,
+                price: line.unit_price,
+                left: line.quantity,
+            })
+            .collect();
+
+        let mut discounts = Vec::new();
+        for promotion in &self.promotions {
+            let discount = match &promotion.rule {
+                Rule::HappyHour {
+                    start,
+                    end,
+                    category,
+                    percent,
+                } => happy_hour(
+                    &mut lines,
+                    check.opened_at,
+                    *start,
+                    *end,
+                    *category,
+                    *percent,
+                ),
+                Rule::BuyOneGetOne { category } => buy_one_get_one(&mut lines, *category),
+                Rule::BirthdayComp { category } => birthday(&mut lines, context, *category),
+            };
+            if let Some((reason, amount)) = discount {
+                discounts.push(Discount {
+                    promotion: promotion.name.clone(),
+                    reason,
+                    amount,
+                });
+            }
+        }
+        discounts
+    }
+}
+
+fn happy_hour(
+    lines: &mut [Portions],
+    opened_at: Time,
+    start: Time,
+    end: Time,
+    category: Category,
+    percent: u32,
+) -> Option<(String, Money)> {
+    if opened_at < start || opened_at >= end {
+        return None;
+    }
+    let mut count = 0u64;
+    let mut amount = Money::ZERO;
+    for line in lines
+        .iter_mut()
+        .filter(|line| line.left > 0 && line.category == category)
+    {
+        count += u64::from(line.left);
+        amount += line.price.percent(percent) * line.left;
+        line.left = 0;
+    }
+    (count > 0).then(|| {
+        let reason = format!(
+            "{} off {count} {category} item(s), check opened {opened_at} during {start}-{end}",
+            format_percent(percent)
+        );
+        (reason, amount)
+    })
+}
+
+fn buy_one_get_one(lines: &mut [Portions], category: Category) -> Option<(String, Money)> {
+    let mut eligible: Vec<&mut Portions> = lines
+        .iter_mut()
+        .filter(|line| line.left > 0 && line.category == category)
+        .collect();
+    // pair the priciest with the next priciest so the free one is always
+    // the cheaper of the two. a line's portions pair up among themselves,
+    // and an odd one out pays for the first portion of the next line
+    eligible.sort_by_key(|line| Reverse(line.price));
+    let mut free = Vec::new();
+    let mut amount = Money::ZERO;
+    let mut unpaired = None;
+    for (index, line) in eligible.iter_mut().enumerate() {
+        let (gifts, odd) = match unpaired.take() {
+            Some(_) => (1 + (line.left - 1) / 2, (line.left - 1) % 2 == 1),
+            None => (line.left / 2, line.left % 2 == 1),
+        };
+        if odd {
+            unpaired = Some(index);
+        }
+        line.left = 0;
+        if gifts > 0 {
+            amount += line.price * gifts;
+            free.push(match gifts {
+                1 => line.name.to_string(),
+                _ => format!("{gifts} x {}", line.name),
+            });
+        }
+    }
+    // the last odd one out had nothing to pair with
+    if let Some(index) = unpaired {
+        eligible[index].left = 1;
+    }
+    (!free.is_empty()).then(|| {
+        let reason = format!("buy one {category}, get one free: {}", free.join(", "));
+        (reason, amount)
+    })
+}
+
+fn birthday(
+    lines: &mut [Portions],
+    context: &PromotionContext,
+    category: Category,
+) -> Option<(String, Money)> {
+    let customer = context.customer?;
+    if !customer.is_birthday(context.date) {
+        return None;
+    }
+    let line = lines
+        .iter_mut()
+        .filter(|line| line.left > 0 && line.category == category)
+        .max_by_key(|line| line.price)?;
+    line.left -= 1;
+    let reason = format!(
+        "happy birthday {}: {} on the house",
+        customer.name, line.name
+    );
+    Some((reason, line.price))
+}
+
+// 5000 basis points is "50%", 1250 is "12.5%"
+fn format_percent(basis_points: u32) -> String {
+    let whole = basis_points / 100;
+    let fraction = basis_points % 100;
+    if fraction == 0 {
+        format!("{whole}%")
+    } else {
+        let fraction = format!("{fraction:02}");
+        format!("{whole}.{}%", fraction.trim_end_matches('0'))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::back_of_house::menu::Menu;
+    use crate::loyalty::Loyalty;
+
+    fn time(hour: u16, minute: u16) -> Time {
+        Time::new(hour, minute).unwrap()
+    }
+
+    fn check(opened_at: Time, items: &[(&str, u32)]) -> Check {
+        let menu = Menu::house();
+        let mut check = Check::new(1, 3, 1, 2, opened_at);
+        for (code, quantity) in items {
+            check
+                .add(menu.get(code).unwrap(), *quantity, vec![])
+                .unwrap();
+        }
+        check
+    }
+
+    fn context(date: Date, customer: Option<&Customer>) -> PromotionContext<'_> {
+        PromotionContext { date, customer }
+    }
+
+    fn today() -> Date {
+        Date::new(2025, 6, 14).unwrap()
+    }
+
+    #[test]
+    fn test_happy_hour_window() {
+        let promotions = Promotions::house();
+        let early = check(time(17, 30), &[("beer", 2), ("burger", 1)]);
+        let discounts = promotions.apply(&early, &context(today(), None));
+        assert_eq!(discounts.len(), 1);
+        assert_eq!(discounts[0].promotion, "Happy Hour");
+        assert_eq!(discounts[0].amount, Money::from_cents(700));
+        assert!(discounts[0].reason.starts_with("50% off 2 drink item(s)"));
+
+        let late = check(time(18, 0), &[("beer", 2)]);
+        assert!(promotions.apply(&late, &context(today(), None)).is_empty());
+    }
+
+    #[test]
+    fn test_buy_one_get_one_frees_the_cheaper_items() {
+        let promotions = Promotions::house();
+        // wings 11.50, salad 8.00 x 2, soup 6.50
+        let check = check(time(19, 0), &[("wings", 1), ("salad", 2), ("soup", 1)]);
+        let discounts = promotions.apply(&check, &context(today(), None));
+        assert_eq!(discounts.len(), 1);
+        // wings pays for a salad, the other salad pays for the soup
+        assert_eq!(discounts[0].amount, Money::from_cents(800 + 650));
+        assert!(
+            discounts[0]
+                .reason
+                .ends_with("House Salad, Soup of the Day")
+        );
+    }
+
+    #[test]
+    fn test_buy_one_get_one_pairs_across_lines() {
+        let promotions = Promotions::house();
+        // the third wings pays for the first salad
+        let mixed = check(time(19, 0), &[("wings", 3), ("salad", 3)]);
+        let discounts = promotions.apply(&mixed, &context(today(), None));
+        assert_eq!(discounts[0].amount, Money::from_cents(1150 + 2 * 800));
+        assert!(
+            discounts[0]
+                .reason
+                .ends_with(": Chicken Wings, 2 x House Salad")
+        );
+
+        // works from the quantities, not one entry per portion
+        let huge = check(time(19, 0), &[("wings", u32::MAX)]);
+        let discounts = promotions.apply(&huge, &context(today(), None));
+        assert_eq!(
+            discounts[0].amount,
+            Money::from_cents(1150) * (u32::MAX / 2)
+        );
+    }
+
+    #[test]
+    fn test_birthday_comp_needs_the_right_customer_and_day() {
+        let mut loyalty = Loyalty::new();
+        let id = loyalty.enroll("Ana", Some((6, 14)));
+        let ana = loyalty.customer(id).unwrap();
+        let promotions = Promotions::house();
+        let check = check(time(19, 0), &[("pie", 1), ("sundae", 1)]);
+
+        let discounts = promotions.apply(&check, &context(today(), Some(ana)));
+        assert_eq!(discounts.len(), 1);
+        assert_eq!(discounts[0].amount, Money::from_cents(750));
+        assert_eq!(
+            discounts[0].reason,
+            "happy birthday Ana: Peach Pie on the house"
+        );
+
+        let tomorrow = Date::new(2025, 6, 15).unwrap();
+        assert!(
+            promotions
+                .apply(&check, &context(tomorrow, Some(ana)))
+                .is_empty()
+        );
+        assert!(promotions.apply(&check, &context(today(), None)).is_empty());
+    }
+
+    #[test]
+    fn test_promotions_do_not_stack() {
+        let mut promotions = Promotions::new();
+        promotions.add(
+            "Drink BOGO",
+            Rule::BuyOneGetOne {
+                category: Category::Drink,
+            },
+        );
+        promotions.add(
+            "Happy Hour",
+            Rule::HappyHour {
+                start: time(16, 0),
+                end: time(18, 0),
+                category: Category::Drink,
+                percent: 5_000,
+            },
+        );
+        let check = check(time(17, 0), &[("beer", 3)]);
+        let discounts = promotions.apply(&check, &context(today(), None));
+        // two beers go to the BOGO, only the third gets happy hour
+        let amounts: Vec<Money> = discounts.iter().map(|discount| discount.amount).collect();
+        assert_eq!(
+            amounts,
+            vec![Money::from_cents(700), Money::from_cents(350)]
+        );
+    }
+
+    #[test]
+    fn test_comped_and_voided_lines_are_skipped() {
+        let mut check = check(time(17, 0), &[("beer", 1), ("wine", 1)]);
+        check.void(1).unwrap();
+        check.comp(2).unwrap();
+        assert!(
+            Promotions::house()
+                .apply(&check, &context(today(), None))
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_format_percent() {
+        assert_eq!(format_percent(5_000), "50%");
+        assert_eq!(format_percent(1_250), "12.5%");
+        assert_eq!(format_percent(825), "8.25%");
+    }
+}
@@ -0,0 +1,731 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use crate::back_of_house::menu::Category;
+use crate::front_of_house::serving::{Bill, Check, Payment};
+use crate::money::Money;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CategorySales {
+    pub category: Category,
+    pub quantity: u32,
+    pub sales: Money,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ItemSales {
+    pub name: String,
+    pub category: Category,
+    pub quantity: u32,
+    pub sales: Money,
+}
+
+// voided or comped lines, valued at menu price
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Adjustments {
+    pub lines: u32,
+    pub quantity: u32,
+    pub value: Money,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerTotals {
+    pub server: String,
+    pub checks: u32,
+    pub covers: u32,
+    pub sales: Money,
+    pub tips: Money,
+}
+
+// how often a promotion fired and what it gave away
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PromotionTotals {
+    pub promotion: String,
+    pub times: u32,
+    pub amount: Money,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableTurns {
+    pub table: u32,
+    pub turns: u32,
+    // average minutes from opening the check to paying it
+    pub average_minutes: u32,
+}
+
+// the numbers a manager looks at after close. only paid checks count
+// toward sales; anything still open is reported as a count
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DailyReport {
+    pub checks: u32,
+    pub open_checks: u32,
+    pub covers: u32,
+    pub net_sales: Money,
+    pub tax: Money,
+    pub tips: Money,
+    pub payments: u32,
+    pub collected: Money,
+    pub average_check: Money,
+    pub average_cover: Money,
+    pub by_category: Vec<CategorySales>,
+    pub by_item: Vec<ItemSales>,
+    pub voids: Adjustments,
+    pub comps: Adjustments,
+    pub discounts: Money,
+    pub by_promotion: Vec<PromotionTotals>,
+    // hour of day (by check open time) and the covers seated in it
+    pub covers_per_hour: Vec<(u16, u32)>,
+    pub average_turn_minutes: u32,
+    pub turns_by_table: Vec<TableTurns>,
+    pub by_server: Vec<ServerTotals>,
+}
+
+impl DailyReport {
+    pub fn build(checks: &[Check], bills: &[Bill], payments: &[Payment]) -> DailyReport {
+        let closed: Vec<&Check> = checks.iter().filter(|check| check.is_closed()).collect();
+        let bill_for = |check: &Check| bills.iter().find(|bill| bill.check_id == check.id);
+
+        let mut by_category: BTreeMap<Category, (u32, Money)> = BTreeMap::new();
+        let mut by_item: BTreeMap<(Category, String), (u32, Money)> = BTreeMap::new();
+        let mut voids = Adjustments::default();
+        let mut comps = Adjustments::default();
+        let mut covers_per_hour: BTreeMap<u16, u32> = BTreeMap::new();
+        let mut turns: BTreeMap<u32, (u32, u32)> = BTreeMap::new();
+        let mut servers: BTreeMap<String, ServerTotals> = BTreeMap::new();
+
+        for check in &closed {
+            for line in check.lines() {
+                let adjustment = if line.voided {
+                    Some(&mut voids)
+                } else if line.comped {
+                    Some(&mut comps)
+                } else {
+                    None
+                };
+                if let Some(adjustment) = adjustment {
+                    adjustment.lines += 1;
+                    adjustment.quantity += line.quantity;
+                    adjustment.value += line.amount();
+                    continue;
+                }
+                let category = by_category.entry(line.category).or_default();
+                category.0 += line.quantity;
+                category.1 += line.charged();
+                let item = by_item
+                    .entry((line.category, line.name.clone()))
+                    .or_default();
+                item.0 += line.quantity;
+                item.1 += line.charged();
+            }
+
+            *covers_per_hour.entry(check.opened_at.hour()).or_default() += check.guests;
+
+            if let Some(closed_at) = check.closed_at {
+                let table = turns.entry(check.table).or_default();
+                table.0 += 1;
+                table.1 += u32::from(closed_at.minutes_since(check.opened_at));
+            }
+
+            let server = check
+                .server
+                .clone()
+                .unwrap_or_else(|| String::from("unassigned"));
+            let totals = servers.entry(server.clone()).or_insert(ServerTotals {
+                server,
+                checks: 0,
+                covers: 0,
+                sales: Money::ZERO,
+                tips: Money::ZERO,
+            });
+            totals.checks += 1;
+            totals.covers += check.guests;
+            if let Some(bill) = bill_for(check) {
+                totals.sales += bill.net();
+                totals.tips += bill.tip;
+            }
+        }
+
+        let paid: Vec<&Bill> = closed.iter().filter_map(|check| bill_for(check)).collect();
+        let net_sales: Money = paid.iter().map(|bill| bill.net()).sum();
+        let covers: u32 = closed.iter().map(|check| check.guests).sum();
+        let closed_ids: Vec<u32> = closed.iter().map(|check| check.id).collect();
+        let payments: Vec<&Payment> = payments
+            .iter()
+            .filter(|payment| closed_ids.contains(&payment.check_id))
+            .collect();
+
+        let mut by_promotion: BTreeMap<String, (u32, Money)> = BTreeMap::new();
+        for discount in paid.iter().flat_map(|bill| &bill.discounts) {
+            let totals = by_promotion.entry(discount.promotion.clone()).or_default();
+            totals.0 += 1;
+            totals.1 += discount.amount;
+        }
+
+        let total_turns: u32 = turns.values().map(|(count, _)| count).sum();
+        let total_minutes: u32 = turns.values().map(|(_, minutes)| minutes).sum();
+
+        DailyReport {
+            checks: closed.len() as u32,
+            open_checks: (checks.len() - closed.len()) as u32,
+            covers,
+            net_sales,
+            tax: paid.iter().map(|bill| bill.tax).sum(),
+            tips: paid.iter().map(|bill| bill.tip).sum(),
+            payments: payments.len() as u32,
+            collected: payments
+                .iter()
+                .map(|payment| payment.amount + payment.tip)
+                .sum(),
+            average_check: average(net_sales, closed.len() as u32),
+            average_cover: average(net_sales, covers),
+            by_category: by_category
+                .into_iter()
+                .map(|(category, (quantity, sales))| CategorySales {
+                    category,
+                    quantity,
+                    sales,
+                })
+                .collect(),
+            by_item: by_item
+                .into_iter()
+                .map(|((category, name), (quantity, sales))| ItemSales {
+                    name,
+                    category,
+                    quantity,
+                    sales,
+                })
+                .collect(),
+            voids,
+            comps,
+            discounts: paid.iter().map(|bill| bill.discount_total()).sum(),
+            by_promotion: by_promotion
+                .into_iter()
+                .map(|(promotion, (times, amount))| PromotionTotals {
+                    promotion,
+                    times,
+                    amount,
+                })
+                .collect(),
+            covers_per_hour: covers_per_hour.into_iter().collect(),
+            average_turn_minutes: total_minutes.checked_div(total_turns).unwrap_or(0),
+            turns_by_table: turns
+                .into_iter()
+                .map(|(table, (count, minutes))| TableTurns {
+                    table,
+                    turns: count,
+                    average_minutes: minutes / count,
+                })
+                .collect(),
+            by_server: servers.into_values().collect(),
+        }
+    }
+
+    // a human readable summary for the terminal
+    pub fn summary(&self) -> String {
+        let mut text = String::new();
+        writeln!(
+            text,
+            "checks {} ({} still open), covers {}",
+            self.checks, self.open_checks, self.covers
+        )
+        .unwrap();
+        writeln!(
+            text,
+            "net sales {}, tax {}, tips {}",
+            self.net_sales, self.tax, self.tips
+        )
+        .unwrap();
+        writeln!(
+            text,
+            "average check {}, per cover {}",
+            self.average_check, self.average_cover
+        )
+        .unwrap();
+        writeln!(
+            text,
+            "voids {} ({}), comps {} ({})",
+            self.voids.lines, self.voids.value, self.comps.lines, self.comps.value
+        )
+        .unwrap();
+        writeln!(text, "discounts {}", self.discounts).unwrap();
+        for totals in &self.by_promotion {
+            writeln!(
+                text,
+                "  {:<18} {:>3}x {:>9}",
+                totals.promotion, totals.times, totals.amount
+            )
+            .unwrap();
+        }
+        writeln!(text, "average turn {} min", self.average_turn_minutes).unwrap();
+        for sales in &self.by_category {
+            writeln!(
+                text,
+                "  {:<10} {:>4} {:>9}",
+                sales.category, sales.quantity, sales.sales
+            )
+            .unwrap();
+        }
+        for totals in &self.by_server {
+            writeln!(
+                text,
+                "  {:<10} {} checks, {} covers, sales {}, tips {}",
+                totals.server, totals.checks, totals.covers, totals.sales, totals.tips
+            )
+            .unwrap();
+        }
+        text.trim_end().to_string()
+    }
+
+    // one row per figure so it loads straight into a spreadsheet:
+    // section, name, count, amount
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("section,name,count,amount\n");
+        let mut row = |section: &str, name: &str, count: Option<u32>, amount: Option<Money>| {
+            let count = count.map(|count| count.to_string()).unwrap_or_default();
+            let amount = amount.map(|amount| amount.to_string()).unwrap_or_default();
+            writeln!(csv, "{section},{},{count},{amount}", csv_field(name)).unwrap();
+        };
+
+        row("summary", "checks", Some(self.checks), None);
+        row("summary", "open_checks", Some(self.open_checks), None);
+        row("summary", "covers", Some(self.covers), None);
+        row("summary", "net_sales", None, Some(self.net_sales));
+        row("summary", "tax", None, Some(self.tax));
+        row("summary", "tips", None, Some(self.tips));
+        row(
+            "summary",
+            "payments",
+            Some(self.payments),
+            Some(self.collected),
+        );
+        row("summary", "average_check", None, Some(self.average_check));
+        row("summary", "average_cover", None, Some(self.average_cover));
+        row(
+            "summary",
+            "average_turn_minutes",
+            Some(self.average_turn_minutes),
+            None,
+        );
+        row(
+            "adjustments",
+            "voids",
+            Some(self.voids.quantity),
+            Some(self.voids.value),
+        );
+        row(
+            "adjustments",
+            "comps",
+            Some(self.comps.quantity),
+            Some(self.comps.value),
+        );
+        row("adjustments", "discounts", None, Some(self.discounts));
+        for totals in &self.by_promotion {
+            row(
+                "promotion",
+                &totals.promotion,
+                Some(totals.times),
+                Some(totals.amount),
+            );
+        }
+        for sales in &self.by_category {
+            row(
+                "category",
+                sales.category.name(),
+                Some(sales.quantity),
+                Some(sales.sales),
+            );
+        }
+        for sales in &self.by_item {
+            row("item", &sales.name, Some(sales.quantity), Some(sales.sales));
+        }
+        for (hour, covers) in &self.covers_per_hour {
+            row(
+                "covers_per_hour",
+                &format!("{hour:02}:00"),
+                Some(*covers),
+                None,
+            );
+        }
+        for turns in &self.turns_by_table {
+            row(
+                "table_turns",
+                &format!("table {}", turns.table),
+                Some(turns.turns),
+                None,
+            );
+            row(
+                "table_turn_minutes",
+                &format!("table {}", turns.table),
+                Some(turns.average_minutes),
+                None,
+            );
+        }
+        for totals in &self.by_server {
+            row(
+                "server_sales",
+                &totals.server,
+                Some(totals.checks),
+                Some(totals.sales),
+            );
+            row(
+                "server_tips",
+                &totals.server,
+                Some(totals.covers),
+                Some(totals.tips),
+            );
+        }
+        csv
+    }
+
+    // money goes out as a decimal string so no precision is lost
+    pub fn to_json(&self) -> String {
+        let mut json = String::from("{\n");
+        write!(
+            json,
+            "  \"checks\": {},\n  \"open_checks\": {},\n  \"covers\": {},\n",
+            self.checks, self.open_checks, self.covers
+        )
+        .unwrap();
+        write!(
+            json,
+            "  \"net_sales\": \"{}\",\n  \"tax\": \"{}\",\n  \"tips\": \"{}\",\n",
+            self.net_sales, self.tax, self.tips
+        )
+        .unwrap();
+        write!(
+            json,
+            "  \"payments\": {},\n  \"collected\": \"{}\",\n",
+            self.payments, self.collected
+        )
+        .unwrap();
+        write!(
+            json,
+            "  \"average_check\": \"{}\",\n  \"average_cover\": \"{}\",\n",
+            self.average_check, self.average_cover
+        )
+        .unwrap();
+        writeln!(json, "  \"voids\": {},", adjustments_json(&self.voids)).unwrap();
+        writeln!(json, "  \"comps\": {},", adjustments_json(&self.comps)).unwrap();
+        writeln!(json, "  \"discounts\": \"{}\",", self.discounts).unwrap();
+        let promotions: Vec<String> = self
+            .by_promotion
+            .iter()
+            .map(|totals| {
+                format!(
+                    "{{\"promotion\": {}, \"times\": {}, \"amount\": \"{}\"}}",
+                    json_string(&totals.promotion),
+                    totals.times,
+                    totals.amount
+                )
+            })
+            .collect();
+        writeln!(json, "  \"by_promotion\": {},", json_array(&promotions)).unwrap();
+
+        let categories: Vec<String> = self
+            .by_category
+            .iter()
+            .map(|sales| {
+                format!(
+                    "{{\"category\": \"{}\", \"quantity\": {}, \"sales\": \"{}\"}}",
+                    sales.category, sales.quantity, sales.sales
+                )
+            })
+            .collect();
+        writeln!(json, "  \"by_category\": {},", json_array(&categories)).unwrap();
+
+        let items: Vec<String> = self
+            .by_item
+            .iter()
+            .map(|sales| {
+                format!(
+                    "{{\"name\": {}, \"category\": \"{}\", \"quantity\": {}, \"sales\": \"{}\"}}",
+                    json_string(&sales.name),
+                    sales.category,
+                    sales.quantity,
+                    sales.sales
+                )
+            })
+            .collect();
+        writeln!(json, "  \"by_item\": {},", json_array(&items)).unwrap();
+
+        let hours: Vec<String> = self
+            .covers_per_hour
+            .iter()
+            .map(|(hour, covers)| format!("{{\"hour\": {hour}, \"covers\": {covers}}}"))
+            .collect();
+        writeln!(json, "  \"covers_per_hour\": {},", json_array(&hours)).unwrap();
+
+        writeln!(
+            json,
+            "  \"average_turn_minutes\": {},",
+            self.average_turn_minutes
+        )
+        .unwrap();
+        let tables: Vec<String> = self
+            .turns_by_table
+            .iter()
+            .map(|turns| {
+                format!(
+                    "{{\"table\": {}, \"turns\": {}, \"average_minutes\": {}}}",
+                    turns.table, turns.turns, turns.average_minutes
+                )
+            })
+            .collect();
+        writeln!(json, "  \"turns_by_table\": {},", json_array(&tables)).unwrap();
+
+        let servers: Vec<String> = self
+            .by_server
+            .iter()
+            .map(|totals| {
+                format!(
+                    "{{\"server\": {}, \"checks\": {}, \"covers\": {}, \"sales\": \"{}\", \"tips\": \"{}\"}}",
+                    json_string(&totals.server),
+                    totals.checks,
+                    totals.covers,
+                    totals.sales,
+                    totals.tips
+                )
+            })
+            .collect();
+        writeln!(json, "  \"by_server\": {}", json_array(&servers)).unwrap();
+        json.push_str("}\n");
+        json
+    }
+}
+
+fn average(total: Money, count: u32) -> Money {
+    if count == 0 {
+        return Money::ZERO;
+    }
+    // round to the nearest cent
+    let count = i64::from(count);
+    Money::from_cents((total.cents() * 2 + count) / (count * 2))
+}
+
+fn adjustments_json(adjustments: &Adjustments) -> String {
+    format!(
+        "{{\"lines\": {}, \"quantity\": {}, \"value\": \"{}\"}}",
+        adjustments.lines, adjustments.quantity, adjustments.value
+    )
+}
+
+fn json_array(values: &[String]) -> String {
+    if values.is_empty() {
+        return String::from("[]");
+    }
+    format!("[\n    {}\n  ]", values.join(",\n    "))
+}
+
+fn json_string(text: &str) -> String {
+    let mut json = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if u32::from(c) < 0x20 => {
+                write!(json, "\\u{:04x}", u32::from(c)).unwrap();
+            }
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+// quotes a CSV field only when it has to
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        String::from(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::back_of_house::menu::Menu;
+    use crate::clock::Time;
+    use crate::promotions::Discount;
+
+    fn time(hour: u16, minute: u16) -> Time {
+        Time::new(hour, minute).unwrap()
+    }
+
+    // two paid checks and one still open
+    fn day() -> (Vec<Check>, Vec<Bill>, Vec<Payment>) {
+        let menu = Menu::house();
+        let item = |code: &str| menu.get(code).unwrap();
+
+        let mut first = Check::new(1, 3, 1, 2, time(18, 0));
+        first.server = Some(String::from("ana"));
+        first.add(item("burger"), 2, vec![]).unwrap();
+        first.add(item("beer"), 2, vec![]).unwrap();
+        first.add(item("wings"), 1, vec![]).unwrap();
+        first.void(3).unwrap();
+        first.close(time(19, 0)).unwrap();
+
+        let mut second = Check::new(2, 6, 2, 4, time(19, 15));
+        second.server = Some(String::from("ben"));
+        second.add(item("steak"), 2, vec![]).unwrap();
+        second.add(item("salmon"), 2, vec![]).unwrap();
+        second.add(item("pie"), 1, vec![]).unwrap();
+        second.comp(3).unwrap();
+        second.close(time(20, 45)).unwrap();
+
+        let mut open = Check::new(3, 4, 3, 2, time(20, 30));
+        open.add(item("soda"), 2, vec![]).unwrap();
+
+        let first_bill = Bill::compute(&first, 825)
+            .with_tip(Money::from_cents(800))
+            .unwrap();
+        let second_bill = Bill::compute(&second, 825)
+            .with_tip(Money::from_cents(2000))
+            .unwrap();
+        let mut payments = first_bill.split(1, time(19, 0));
+        payments.extend(second_bill.split(2, time(20, 45)));
+        (
+            vec![first, second, open],
+            vec![first_bill, second_bill],
+            payments,
+        )
+    }
+
+    #[test]
+    fn test_totals() {
+        let (checks, bills, payments) = day();
+        let report = DailyReport::build(&checks, &bills, &payments);
+        assert_eq!(report.checks, 2);
+        assert_eq!(report.open_checks, 1);
+        assert_eq!(report.covers, 6);
+        // 30 + 14, then 64 + 48
+        assert_eq!(report.net_sales, Money::from_cents(15600));
+        assert_eq!(report.average_check, Money::from_cents(7800));
+        assert_eq!(report.average_cover, Money::from_cents(2600));
+        assert_eq!(report.tips, Money::from_cents(2800));
+        assert_eq!(report.payments, 3);
+        let billed: Money = bills.iter().map(|bill| bill.total).sum();
+        assert_eq!(report.collected, billed);
+    }
+
+    #[test]
+    fn test_voids_and_comps() {
+        let (checks, bills, payments) = day();
+        let report = DailyReport::build(&checks, &bills, &payments);
+        assert_eq!(report.voids.value, Money::from_cents(1150));
+        assert_eq!(report.comps.value, Money::from_cents(750));
+        assert!(
+            report
+                .by_item
+                .iter()
+                .all(|sales| sales.name != "Chicken Wings")
+        );
+    }
+
+    #[test]
+    fn test_breakdowns() {
+        let (checks, bills, payments) = day();
+        let report = DailyReport::build(&checks, &bills, &payments);
+
+        let entrees = &report.by_category[0];
+        assert_eq!(entrees.category, Category::Entree);
+        assert_eq!(
+            (entrees.quantity, entrees.sales),
+            (6, Money::from_cents(14200))
+        );
+
+        assert_eq!(report.covers_per_hour, vec![(18, 2), (19, 4)]);
+        // 60 and 90 minutes
+        assert_eq!(report.average_turn_minutes, 75);
+
+        let servers: Vec<(&str, Money)> = report
+            .by_server
+            .iter()
+            .map(|totals| (totals.server.as_str(), totals.tips))
+            .collect();
+        assert_eq!(
+            servers,
+            vec![
+                ("ana", Money::from_cents(800)),
+                ("ben", Money::from_cents(2000))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_csv() {
+        let (checks, bills, payments) = day();
+        let csv = DailyReport::build(&checks, &bills, &payments).to_csv();
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some("section,name,count,amount"));
+        assert!(csv.contains("\nsummary,net_sales,,156.00\n"), "{csv}");
+        assert!(csv.contains("\nitem,Ribeye Steak,2,64.00\n"), "{csv}");
+        assert!(csv.contains("\nserver_tips,ben,4,20.00\n"), "{csv}");
+        assert!(lines.all(|line| line.split(',').count() == 4));
+    }
+
+    #[test]
+    fn test_discounts_come_off_net_sales() {
+        let (checks, mut bills, payments) = day();
+        let discount = |promotion: &str, cents| Discount {
+            promotion: String::from(promotion),
+            reason: String::new(),
+            amount: Money::from_cents(cents),
+        };
+        bills[0] = bills[0]
+            .clone()
+            .with_discounts(vec![discount("Happy Hour", 700)]);
+        bills[1] = bills[1].clone().with_discounts(vec![
+            discount("Happy Hour", 300),
+            discount("Appetizer BOGO", 800),
+        ]);
+        let report = DailyReport::build(&checks, &bills, &payments);
+        assert_eq!(report.discounts, Money::from_cents(1800));
+        assert_eq!(report.net_sales, Money::from_cents(15600 - 1800));
+        assert_eq!(
+            report.by_promotion,
+            vec![
+                PromotionTotals {
+                    promotion: String::from("Appetizer BOGO"),
+                    times: 1,
+                    amount: Money::from_cents(800),
+                },
+                PromotionTotals {
+                    promotion: String::from("Happy Hour"),
+                    times: 2,
+                    amount: Money::from_cents(1000),
+                },
+            ]
+        );
+        assert!(report.to_csv().contains("\npromotion,Happy Hour,2,10.00\n"));
+    }
+
+    #[test]
+    fn test_json() {
+        let (checks, bills, payments) = day();
+        let json = DailyReport::build(&checks, &bills, &payments).to_json();
+        assert!(json.contains("\"net_sales\": \"156.00\""), "{json}");
+        assert!(json.contains("{\"hour\": 19, \"covers\": 4}"), "{json}");
+        assert!(
+            json.contains("{\"server\": \"ana\", \"checks\": 1"),
+            "{json}"
+        );
+    }
+
+    #[test]
+    fn test_empty_day() {
+        let report = DailyReport::build(&[], &[], &[]);
+        assert_eq!(report.average_check, Money::ZERO);
+        assert!(report.to_json().contains("\"by_item\": []"));
+    }
+
+    #[test]
+    fn test_escaping() {
+        assert_eq!(csv_field("Mac, Cheese"), "\"Mac, Cheese\"");
+        assert_eq!(csv_field("Mac\rCheese"), "\"Mac\rCheese\"");
+        assert_eq!(json_string("say \"hi\"\n"), "\"say \\\"hi\\\"\\n\"");
+    }
+}
@@ -0,0 +1,545 @@
+use std::fmt;
+
+use crate::clock::Time;
+use crate::front_of_house::hosting::{FloorPlan, Table};
+use crate::money::Money;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Role {
+    Host,
+    Server,
+    Cook,
+    Runner,
+}
+
+impl Role {
+    pub const ALL: [Role; 4] = [Role::Host, Role::Server, Role::Cook, Role::Runner];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Role::Host => "host",
+            Role::Server => "server",
+            Role::Cook => "cook",
+            Role::Runner => "runner",
+        }
+    }
+
+    pub fn parse(text: &str) -> Option<Role> {
+        Role::ALL
+            .into_iter()
+            .find(|role| role.name().eq_ignore_ascii_case(text.trim()))
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StaffMember {
+    pub name: String,
+    pub role: Role,
+}
+
+// when someone is scheduled to work
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Shift {
+    pub start: Time,
+    pub end: Time,
+}
+
+// when someone actually worked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeEntry {
+    pub clock_in: Time,
+    pub clock_out: Option<Time>,
+}
+
+impl TimeEntry {
+    // minutes worked, counting an open entry up to `now`
+    pub fn minutes(&self, now: Time) -> u32 {
+        let end = self.clock_out.unwrap_or(now);
+        u32::from(end.minutes_since(self.clock_in))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StaffError {
+    AlreadyOnRoster(String),
+    UnknownStaff(String),
+    AlreadyClockedIn(String),
+    NotClockedIn(String),
+    BadShift,
+}
+
+impl fmt::Display for StaffError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StaffError::AlreadyOnRoster(name) => write!(f, "{name} is already on the roster"),
+            StaffError::UnknownStaff(name) => write!(f, "{name} is not on the roster"),
+            StaffError::AlreadyClockedIn(name) => write!(f, "{name} is already clocked in"),
+            StaffError::NotClockedIn(name) => write!(f, "{name} is not clocked in"),
+            StaffError::BadShift => write!(f, "a shift has to end after it starts"),
+        }
+    }
+}
+
+impl std::error::Error for StaffError {}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    member: StaffMember,
+    shifts: Vec<Shift>,
+    time: Vec<TimeEntry>,
+}
+
+// who works here, when they're scheduled and when they clocked in
+#[derive(Debug, Clone, Default)]
+pub struct Roster {
+    entries: Vec<Entry>,
+}
+
+impl Roster {
+    pub fn new() -> Roster {
+        Roster {
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn add(&mut self, name: &str, role: Role) -> Result<(), StaffError> {
+        if self.find(name).is_some() {
+            return Err(StaffError::AlreadyOnRoster(String::from(name)));
+        }
+        self.entries.push(Entry {
+            member: StaffMember {
+                name: String::from(name),
+                role,
+            },
+            shifts: Vec::new(),
+            time: Vec::new(),
+        });
+        Ok(())
+    }
+
+    pub fn member(&self, name: &str) -> Option<&StaffMember> {
+        self.find(name).map(|entry| &entry.member)
+    }
+
+    pub fn members(&self) -> impl Iterator<Item = &StaffMember> {
+        self.entries.iter().map(|entry| &entry.member)
+    }
+
+    pub fn schedule(&mut self, name: &str, start: Time, end: Time) -> Result<(), StaffError> {
+        if end <= start {
+            return Err(StaffError::BadShift);
+        }
+        self.find_mut(name)?.shifts.push(Shift { start, end });
+        Ok(())
+    }
+
+    pub fn shifts(&self, name: &str) -> &[Shift] {
+        self.find(name).map_or(&[], |entry| &entry.shifts)
+    }
+
+    // who is scheduled to be working at `time`
+    pub fn scheduled_at(&self, time: Time) -> Vec<&StaffMember> {
+        self.entries
+            .iter()
+            .filter(|entry| {
+                entry
+                    .shifts
+                    .iter()
+                    .any(|shift| shift.start <= time && time < shift.end)
+            })
+            .map(|entry| &entry.member)
+            .collect()
+    }
+
+    pub fn clock_in(&mut self, name: &str, now: Time) -> Result<(), StaffError> {
+        let entry = self.find_mut(name)?;
+        if entry.time.iter().any(|time| time.clock_out.is_none()) {
+            return Err(StaffError::AlreadyClockedIn(entry.member.name.clone()));
+        }
+        entry.time.push(TimeEntry {
+            clock_in: now,
+            clock_out: None,
+        });
+        Ok(())
+    }
+
+    pub fn clock_out(&mut self, name: &str, now: Time) -> Result<(), StaffError> {
+        let entry = self.find_mut(name)?;
+        let open = entry
+            .time
+            .iter_mut()
+            .find(|time| time.clock_out.is_none())
+            .ok_or_else(|| StaffError::NotClockedIn(entry.member.name.clone()))?;
+        open.clock_out = Some(now);
+        Ok(())
+    }
+
+    pub fn is_on_duty(&self, name: &str) -> bool {
+        self.find(name)
+            .is_some_and(|entry| entry.time.iter().any(|time| time.clock_out.is_none()))
+    }
+
+    // everyone clocked in right now with the given role, in roster order
+    pub fn on_duty(&self, role: Role) -> Vec<&StaffMember> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.member.role == role && self.is_on_duty(&entry.member.name))
+            .map(|entry| &entry.member)
+            .collect()
+    }
+
+    pub fn minutes_worked(&self, name: &str, now: Time) -> u32 {
+        self.find(name).map_or(0, |entry| {
+            entry.time.iter().map(|time| time.minutes(now)).sum()
+        })
+    }
+
+    fn find(&self, name: &str) -> Option<&Entry> {
+        self.entries
+            .iter()
+            .find(|entry| entry.member.name.eq_ignore_ascii_case(name))
+    }
+
+    fn find_mut(&mut self, name: &str) -> Result<&mut Entry, StaffError> {
+        self.entries
+            .iter_mut()
+            .find(|entry| entry.member.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| StaffError::UnknownStaff(String::from(name)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Section {
+    pub server: String,
+    pub tables: Vec<u32>,
+}
+
+// splits the floor into one run of neighbouring tables per server, with
+// roughly the same number of seats in each. tables are numbered around
+// the room, so consecutive numbers are next to each other
+pub fn assign_sections(floor: &FloorPlan, servers: &[&str]) -> Vec<Section> {
+    if servers.is_empty() {
+        return Vec::new();
+    }
+    let tables: Vec<&Table> = floor.tables().iter().collect();
+    let mut sections = Vec::new();
+    let mut start = 0;
+    let mut seats_left: u32 = tables.iter().map(|table| table.seats).sum();
+
+    for (index, server) in servers.iter().enumerate() {
+        let servers_left = (servers.len() - index) as u32;
+        let tables_left = tables.len() - start;
+        let mut end = start;
+        if servers_left == 1 {
+            end = tables.len();
+        } else {
+            let target = seats_left / servers_left;
+            let mut seats = 0;
+            // leave at least one table for each server still to come,
+            // unless there are more servers than tables
+            let last = tables_left
+                .saturating_sub(servers_left as usize - 1)
+                .max(1)
+                .min(tables_left);
+            while end < start + last {
+                let next = seats + tables[end].seats;
+                // stop when adding this table overshoots more than
+                // stopping short would undershoot
+                if end > start && next > target && next.abs_diff(target) > target.abs_diff(seats) {
+                    break;
+                }
+                seats = next;
+                end += 1;
+            }
+        }
+        let section: Vec<u32> = tables[start..end]
+            .iter()
+            .map(|table| table.number)
+            .collect();
+        seats_left -= tables[start..end]
+            .iter()
+            .map(|table| table.seats)
+            .sum::<u32>();
+        sections.push(Section {
+            server: String::from(*server),
+            tables: section,
+        });
+        start = end;
+    }
+    sections
+}
+
+// hands out new parties to servers in turn so nobody gets slammed while
+// someone else stands around. a server whose section has no suitable open
+// table is skipped and keeps their place for the next party
+#[derive(Debug, Clone, Default)]
+pub struct Rotation {
+    next: usize,
+}
+
+impl Rotation {
+    pub fn new() -> Rotation {
+        Rotation { next: 0 }
+    }
+
+    // picks the next server with room for the party and the smallest open
+    // table in their section that fits it
+    pub fn next(
+        &mut self,
+        sections: &[Section],
+        floor: &FloorPlan,
+        party_size: u32,
+    ) -> Option<(String, u32)> {
+        let count = sections.len();
+        for offset in 0..count {
+            let index = (self.next + offset) % count;
+            let section = &sections[index];
+            let table = section
+                .tables
+                .iter()
+                .filter_map(|number| floor.table(*number))
+                .filter(|table| table.is_open() && table.seats >= party_size)
+                .min_by_key(|table| (table.seats, table.number));
+            if let Some(table) = table {
+                self.next = index + 1;
+                return Some((section.server.clone(), table.number));
+            }
+        }
+        None
+    }
+}
+
+// how the tip pool is shared: a role's points are multiplied by the
+// minutes each person worked. points are in tenths, so 10 is a full share
+#[derive(Debug, Clone)]
+pub struct TipPool {
+    points: Vec<(Role, u32)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TipShare {
+    pub name: String,
+    pub role: Role,
+    pub minutes: u32,
+    pub amount: Money,
+}
+
+impl TipPool {
+    pub fn new(points: Vec<(Role, u32)>) -> TipPool {
+        TipPool { points }
+    }
+
+    // servers take a full share, runners half and hosts three tenths; the
+    // kitchen isn't in the front of house pool
+    pub fn house() -> TipPool {
+        TipPool::new(vec![(Role::Server, 10), (Role::Runner, 5), (Role::Host, 3)])
+    }
+
+    fn points(&self, role: Role) -> u32 {
+        self.points
+            .iter()
+            .find(|(pooled, _)| *pooled == role)
+            .map_or(0, |(_, points)| *points)
+    }
+
+    // splits `tips` between everyone who worked, down to the cent. the
+    // shares always add back up to `tips`: leftover cents go to whoever
+    // lost the most to rounding
+    pub fn distribute(&self, tips: Money, roster: &Roster, now: Time) -> Vec<TipShare> {
+        let mut shares: Vec<(TipShare, u64)> = roster
+            .members()
+            .filter_map(|member| {
+                let points = self.points(member.role);
+                let minutes = roster.minutes_worked(&member.name, now);
+                let weight = u64::from(points) * u64::from(minutes);
+                (weight > 0).then(|| {
+                    let share = TipShare {
+                        name: member.name.clone(),
+                        role: member.role,
+                        minutes,
+                        amount: Money::ZERO,
+                    };
+                    (share, weight)
+                })
+            })
+            .collect();
+        let total_weight: u64 = shares.iter().map(|(_, weight)| weight).sum();
+        if total_weight == 0 || tips.cents() <= 0 {
+            return shares.into_iter().map(|(share, _)| share).collect();
+        }
+
+        let cents = tips.cents() as u64;
+        let mut remainders = Vec::with_capacity(shares.len());
+        let mut handed_out = 0;
+        for (index, (share, weight)) in shares.iter_mut().enumerate() {
+            let exact = cents * *weight;
+            let amount = exact / total_weight;
+            share.amount = Money::from_cents(amount as i64);
+            handed_out += amount;
+            remainders.push((exact % total_weight, index));
+        }
+        remainders.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+        for (_, index) in remainders.into_iter().take((cents - handed_out) as usize) {
+            shares[index].0.amount += Money::from_cents(1);
+        }
+        shares.into_iter().map(|(share, _)| share).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::front_of_house::hosting::Party;
+
+    fn time(hour: u16, minute: u16) -> Time {
+        Time::new(hour, minute).unwrap()
+    }
+
+    fn roster() -> Roster {
+        let mut roster = Roster::new();
+        roster.add("Ana", Role::Server).unwrap();
+        roster.add("Ben", Role::Server).unwrap();
+        roster.add("Cy", Role::Runner).unwrap();
+        roster.add("Dee", Role::Host).unwrap();
+        roster.add("Eli", Role::Cook).unwrap();
+        roster
+    }
+
+    #[test]
+    fn test_clock_in_and_out() {
+        let mut roster = roster();
+        assert_eq!(
+            roster.add("ana", Role::Host),
+            Err(StaffError::AlreadyOnRoster(String::from("ana")))
+        );
+        roster.clock_in("ana", time(16, 0)).unwrap();
+        assert_eq!(
+            roster.clock_in("Ana", time(16, 5)),
+            Err(StaffError::AlreadyClockedIn(String::from("Ana")))
+        );
+        assert_eq!(roster.on_duty(Role::Server).len(), 1);
+        assert_eq!(roster.minutes_worked("Ana", time(17, 30)), 90);
+
+        roster.clock_out("Ana", time(18, 0)).unwrap();
+        assert!(!roster.is_on_duty("Ana"));
+        assert_eq!(
+            roster.clock_out("Ana", time(18, 5)),
+            Err(StaffError::NotClockedIn(String::from("Ana")))
+        );
+        assert_eq!(roster.minutes_worked("Ana", time(23, 0)), 120);
+        assert_eq!(
+            roster.clock_in("Zed", time(16, 0)),
+            Err(StaffError::UnknownStaff(String::from("Zed")))
+        );
+    }
+
+    #[test]
+    fn test_schedule() {
+        let mut roster = roster();
+        roster.schedule("Ana", time(16, 0), time(22, 0)).unwrap();
+        roster.schedule("Ben", time(11, 0), time(17, 0)).unwrap();
+        assert_eq!(
+            roster.schedule("Cy", time(17, 0), time(16, 0)),
+            Err(StaffError::BadShift)
+        );
+        let names: Vec<&str> = roster
+            .scheduled_at(time(16, 30))
+            .iter()
+            .map(|member| member.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["Ana", "Ben"]);
+        assert_eq!(roster.scheduled_at(time(17, 0)).len(), 1);
+    }
+
+    #[test]
+    fn test_sections_cover_the_floor_evenly() {
+        let floor = FloorPlan::house();
+        let sections = assign_sections(&floor, &["Ana", "Ben", "Cy"]);
+        let tables: Vec<u32> = sections.iter().flat_map(|s| s.tables.clone()).collect();
+        assert_eq!(tables, (1..=8).collect::<Vec<u32>>());
+        let seats: Vec<u32> = sections
+            .iter()
+            .map(|section| {
+                section
+                    .tables
+                    .iter()
+                    .map(|number| floor.table(*number).unwrap().seats)
+                    .sum()
+            })
+            .collect();
+        // 36 seats between three servers
+        assert_eq!(seats, vec![12, 10, 14]);
+        assert!(assign_sections(&floor, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_many_servers_on_the_house_floor() {
+        // a small first table can leave a section over its share already
+        let floor = FloorPlan::house();
+        let names = ["a", "b", "c", "d", "e", "f", "g", "h", "i", "j"];
+        for count in 1..=names.len() {
+            let sections = assign_sections(&floor, &names[..count]);
+            assert_eq!(sections.len(), count);
+            let tables: Vec<u32> = sections.iter().flat_map(|s| s.tables.clone()).collect();
+            assert_eq!(tables, (1..=8).collect::<Vec<u32>>(), "{count} servers");
+        }
+    }
+
+    #[test]
+    fn test_more_servers_than_tables() {
+        let floor = FloorPlan::new(vec![Table::new(1, 4)]);
+        let sections = assign_sections(&floor, &["Ana", "Ben"]);
+        assert_eq!(sections[0].tables, vec![1]);
+        assert!(sections[1].tables.is_empty());
+    }
+
+    #[test]
+    fn test_rotation_takes_turns() {
+        let mut floor = FloorPlan::house();
+        let sections = assign_sections(&floor, &["Ana", "Ben"]);
+        let mut rotation = Rotation::new();
+        let mut picks = Vec::new();
+        for id in 1..=4 {
+            let (server, table) = rotation.next(&sections, &floor, 2).unwrap();
+            let party = Party {
+                id,
+                name: String::from("guest"),
+                size: 2,
+                arrived_at: time(18, 0),
+            };
+            floor.seat(&party, table, time(18, 0)).unwrap();
+            picks.push((server, table));
+        }
+        let servers: Vec<&str> = picks.iter().map(|(server, _)| server.as_str()).collect();
+        assert_eq!(servers, vec!["Ana", "Ben", "Ana", "Ben"]);
+
+        // a big party skips servers with nothing big enough open
+        let (server, table) = rotation.next(&sections, &floor, 8).unwrap();
+        assert_eq!((server.as_str(), table), ("Ben", 8));
+    }
+
+    #[test]
+    fn test_tip_pool_adds_up() {
+        let mut roster = roster();
+        for name in ["Ana", "Ben", "Cy", "Dee", "Eli"] {
+            roster.clock_in(name, time(17, 0)).unwrap();
+        }
+        roster.clock_out("Ben", time(20, 0)).unwrap();
+
+        let tips = Money::from_cents(10_001);
+        let shares = TipPool::house().distribute(tips, &roster, time(23, 0));
+        let names: Vec<&str> = shares.iter().map(|share| share.name.as_str()).collect();
+        assert_eq!(names, vec!["Ana", "Ben", "Cy", "Dee"]);
+        assert_eq!(shares.iter().map(|share| share.amount).sum::<Money>(), tips);
+        // Ana worked twice as long as Ben at the same rate
+        let ana = shares[0].amount.cents();
+        let ben = shares[1].amount.cents();
+        assert!((ana - 2 * ben).abs() <= 2, "{ana} vs {ben}");
+    }
+}
@@ -0,0 +1,136 @@
+#[cfg(test)]
+use std::cell::Cell;
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+// a moment in UTC, kept as whole seconds since the unix epoch
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Timestamp(u64);
+
+impl Timestamp {
+    pub fn from_secs(secs: u64) -> Timestamp {
+        Timestamp(secs)
+    }
+
+    pub fn now() -> Timestamp {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or(0);
+        Timestamp(secs)
+    }
+
+    pub fn secs(self) -> u64 {
+        self.0
+    }
+
+    pub fn plus_secs(self, secs: u64) -> Timestamp {
+        Timestamp(self.0.saturating_add(secs))
+    }
+}
+
+// ISO 8601, as in 2025-05-01T12:30:00Z
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (year, month, day) = civil_from_days(self.0 / SECONDS_PER_DAY);
+        let secs = self.0 % SECONDS_PER_DAY;
+        f.pad(&format!(
+            "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+            secs / 3600,
+            secs / 60 % 60,
+            secs % 60
+        ))
+    }
+}
+
+// where the time comes from, so code that expires things can be tested
+// without waiting
+pub trait Clock {
+    fn now(&self) -> Timestamp;
+}
+
+impl<C: Clock + ?Sized> Clock for &C {
+    fn now(&self) -> Timestamp {
+        (**self).now()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Timestamp {
+        Timestamp::now()
+    }
+}
+
+// a clock that only moves when told to, for tests
+#[cfg(test)]
+#[derive(Debug, Clone, Default)]
+pub struct ManualClock(Cell<Timestamp>);
+
+#[cfg(test)]
+impl ManualClock {
+    pub fn new(at: Timestamp) -> ManualClock {
+        ManualClock(Cell::new(at))
+    }
+
+    pub fn advance(&self, secs: u64) {
+        self.0.set(self.0.get().plus_secs(secs));
+    }
+}
+
+#[cfg(test)]
+impl Clock for ManualClock {
+    fn now(&self) -> Timestamp {
+        self.0.get()
+    }
+}
+
+// the gregorian date `days` after 1970-01-01, as year, month and day,
+// using Howard Hinnant's civil_from_days
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Timestamp::from_secs(0).to_string(), "1970-01-01T00:00:00Z");
+        assert_eq!(
+            Timestamp::from_secs(1_709_210_096).to_string(),
+            "2024-02-29T12:34:56Z"
+        );
+        assert_eq!(
+            Timestamp::from_secs(951_868_799).to_string(),
+            "2000-02-29T23:59:59Z"
+        );
+        assert_eq!(
+            Timestamp::from_secs(100).plus_secs(50),
+            Timestamp::from_secs(150)
+        );
+        assert_eq!(
+            Timestamp::from_secs(u64::MAX).plus_secs(1),
+            Timestamp::from_secs(u64::MAX)
+        );
+    }
+}
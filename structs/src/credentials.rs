@@ -0,0 +1,965 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::clock::{Clock, Timestamp};
+use crate::crypto::{self, Digest, Random};
+use crate::registry::{self, RegistryError, SavedRegistry, UserRegistry};
+use crate::user::User;
+
+// PBKDF2 rounds for new hashes; stored hashes with fewer are upgraded the
+// next time their owner logs in
+pub const DEFAULT_ITERATIONS: u32 = 100_000;
+const SALT_BYTES: usize = 16;
+const TOKEN_BYTES: usize = 32;
+const HASH_SCHEME: &str = "pbkdf2-sha256";
+
+// turned down after stripping case and any digits and symbols off the end,
+// so "Password123!" is as common as "password"
+const COMMON_PASSWORDS: [&str; 20] = [
+    "abc",
+    "admin",
+    "baseball",
+    "dragon",
+    "football",
+    "iloveyou",
+    "letmein",
+    "login",
+    "master",
+    "monkey",
+    "passw",
+    "password",
+    "princess",
+    "qwerty",
+    "qwertyuiop",
+    "shadow",
+    "starwars",
+    "sunshine",
+    "trustno",
+    "welcome",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PolicyError {
+    TooShort(usize),
+    TooLong(usize),
+    // how many kinds of character it needs
+    TooSimple(usize),
+    Common,
+    ContainsUsername,
+}
+
+impl fmt::Display for PolicyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PolicyError::TooShort(min) => {
+                write!(f, "the password needs at least {min} characters")
+            }
+            PolicyError::TooLong(max) => write!(f, "the password can be at most {max} characters"),
+            PolicyError::TooSimple(kinds) => write!(
+                f,
+                "the password needs {kinds} of: lowercase, uppercase, digits, symbols"
+            ),
+            PolicyError::Common => write!(f, "the password is too common"),
+            PolicyError::ContainsUsername => {
+                write!(f, "the password can't contain the username or email")
+            }
+        }
+    }
+}
+
+impl Error for PolicyError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PasswordPolicy {
+    pub min_length: usize,
+    pub max_length: usize,
+    // out of lowercase, uppercase, digits and everything else
+    pub min_kinds: usize,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> PasswordPolicy {
+        PasswordPolicy {
+            min_length: 12,
+            max_length: 128,
+            min_kinds: 2,
+        }
+    }
+}
+
+impl PasswordPolicy {
+    pub fn check(&self, password: &str, user: &User) -> Result<(), PolicyError> {
+        let len = password.chars().count();
+        if len < self.min_length {
+            return Err(PolicyError::TooShort(self.min_length));
+        }
+        if len > self.max_length {
+            return Err(PolicyError::TooLong(self.max_length));
+        }
+        let kinds = [
+            password.chars().any(|c| c.is_lowercase()),
+            password.chars().any(|c| c.is_uppercase()),
+            password.chars().any(|c| c.is_ascii_digit()),
+            password.chars().any(|c| !c.is_alphanumeric()),
+        ];
+        if kinds.iter().filter(|kind| **kind).count() < self.min_kinds {
+            return Err(PolicyError::TooSimple(self.min_kinds));
+        }
+        let lowered = password.to_lowercase();
+        let stem = lowered.trim_end_matches(|c: char| !c.is_alphabetic());
+        if COMMON_PASSWORDS.contains(&stem) {
+            return Err(PolicyError::Common);
+        }
+        let username = user.username.as_str().to_lowercase();
+        let mailbox = user
+            .email
+            .as_str()
+            .split('@')
+            .next()
+            .unwrap_or("")
+            .to_lowercase();
+        if lowered.contains(&username) || (mailbox.len() >= 3 && lowered.contains(&mailbox)) {
+            return Err(PolicyError::ContainsUsername);
+        }
+        Ok(())
+    }
+}
+
+// a salted PBKDF2-SHA256 hash, written as
+// pbkdf2-sha256$<iterations>$<salt hex>$<hash hex>
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PasswordHash {
+    iterations: u32,
+    salt: Vec<u8>,
+    hash: Digest,
+}
+
+impl PasswordHash {
+    pub fn new(
+        password: &str,
+        iterations: u32,
+        random: &mut impl Random,
+    ) -> Result<PasswordHash, getrandom::Error> {
+        let mut salt = vec![0; SALT_BYTES];
+        random.fill(&mut salt)?;
+        Ok(PasswordHash::with_salt(password, iterations, salt))
+    }
+
+    fn with_salt(password: &str, iterations: u32, salt: Vec<u8>) -> PasswordHash {
+        let mut hash = [0; 32];
+        crypto::pbkdf2_sha256(password.as_bytes(), &salt, iterations, &mut hash);
+        PasswordHash {
+            iterations,
+            salt,
+            hash,
+        }
+    }
+
+    pub fn verify(&self, password: &str) -> bool {
+        let mut hash = [0; 32];
+        crypto::pbkdf2_sha256(password.as_bytes(), &self.salt, self.iterations, &mut hash);
+        crypto::constant_time_eq(&hash, &self.hash)
+    }
+
+    pub fn iterations(&self) -> u32 {
+        self.iterations
+    }
+
+    pub fn parse(text: &str) -> Option<PasswordHash> {
+        let mut parts = text.trim().split('$');
+        if parts.next()? != HASH_SCHEME {
+            return None;
+        }
+        let iterations = parts.next()?.parse().ok().filter(|n| *n > 0)?;
+        let salt = crypto::from_hex(parts.next()?)?;
+        let hash = crypto::from_hex(parts.next()?)?.try_into().ok()?;
+        if parts.next().is_some() {
+            return None;
+        }
+        Some(PasswordHash {
+            iterations,
+            salt,
+            hash,
+        })
+    }
+}
+
+impl fmt::Display for PasswordHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{HASH_SCHEME}${}${}${}",
+            self.iterations,
+            crypto::to_hex(&self.salt),
+            crypto::to_hex(&self.hash)
+        )
+    }
+}
+
+// handed to the client after a login and shown back on every request. only
+// its hash is kept, so a leaked session list can't be used to sign in
+#[derive(Clone, PartialEq, Eq)]
+pub struct SessionToken(String);
+
+impl SessionToken {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+// tokens stay out of logs
+impl fmt::Debug for SessionToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SessionToken(..)")
+    }
+}
+
+#[derive(Debug)]
+pub enum AuthError {
+    Policy(PolicyError),
+    Registry(RegistryError),
+    // the same for an unknown user and a wrong password. Locked and
+    // Inactive are only ever about real accounts, so those two do give
+    // away that a username exists
+    InvalidCredentials,
+    Locked(Timestamp),
+    Inactive(String),
+    InvalidSession,
+    SessionExpired,
+    // no salt or token could be made
+    Random(getrandom::Error),
+}
+
+impl fmt::Display for AuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuthError::Policy(error) => error.fmt(f),
+            AuthError::Registry(error) => error.fmt(f),
+            AuthError::InvalidCredentials => write!(f, "wrong username or password"),
+            AuthError::Locked(until) => {
+                write!(f, "too many failed logins, try again after {until}")
+            }
+            AuthError::Inactive(name) => write!(f, "the user '{name}' is deactivated"),
+            AuthError::InvalidSession => write!(f, "not signed in"),
+            AuthError::SessionExpired => write!(f, "the session has expired, sign in again"),
+            AuthError::Random(error) => write!(f, "no randomness available: {error}"),
+        }
+    }
+}
+
+impl Error for AuthError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            AuthError::Policy(error) => Some(error),
+            AuthError::Registry(error) => Some(error),
+            AuthError::Random(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<PolicyError> for AuthError {
+    fn from(error: PolicyError) -> AuthError {
+        AuthError::Policy(error)
+    }
+}
+
+impl From<getrandom::Error> for AuthError {
+    fn from(error: getrandom::Error) -> AuthError {
+        AuthError::Random(error)
+    }
+}
+
+impl From<RegistryError> for AuthError {
+    fn from(error: RegistryError) -> AuthError {
+        match error {
+            RegistryError::Inactive(name) => AuthError::Inactive(name),
+            error => AuthError::Registry(error),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthConfig {
+    pub policy: PasswordPolicy,
+    pub iterations: u32,
+    // failed logins in a row before the account is locked, and for how long
+    pub max_failures: u32,
+    pub lockout_secs: u64,
+    pub session_secs: u64,
+}
+
+impl Default for AuthConfig {
+    fn default() -> AuthConfig {
+        AuthConfig {
+            policy: PasswordPolicy::default(),
+            iterations: DEFAULT_ITERATIONS,
+            max_failures: 5,
+            lockout_secs: 15 * 60,
+            session_secs: 12 * 60 * 60,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Credential {
+    username: String,
+    hash: PasswordHash,
+    failures: u32,
+    locked_until: Option<Timestamp>,
+}
+
+#[derive(Debug, Clone)]
+struct Session {
+    token_hash: Digest,
+    username: String,
+    expires: Timestamp,
+}
+
+// the registry's users with their passwords and signed in sessions
+pub struct Accounts<C: Clock, R: Random> {
+    registry: UserRegistry,
+    credentials: Vec<Credential>,
+    sessions: Vec<Session>,
+    config: AuthConfig,
+    clock: C,
+    random: R,
+}
+
+impl<C: Clock, R: Random> Accounts<C, R> {
+    pub fn new(config: AuthConfig, clock: C, random: R) -> Accounts<C, R> {
+        Accounts::with_registry(UserRegistry::new(), config, clock, random)
+    }
+
+    // users already in the registry can't log in until they have a password,
+    // which only comes with them from a file that Accounts saved
+    pub fn with_registry(
+        registry: UserRegistry,
+        config: AuthConfig,
+        clock: C,
+        random: R,
+    ) -> Accounts<C, R> {
+        Accounts {
+            registry,
+            credentials: Vec::new(),
+            sessions: Vec::new(),
+            config,
+            clock,
+            random,
+        }
+    }
+
+    pub fn registry(&self) -> &UserRegistry {
+        &self.registry
+    }
+
+    pub fn register(&mut self, user: User, password: &str) -> Result<&User, AuthError> {
+        self.config.policy.check(password, &user)?;
+        let hash = PasswordHash::new(password, self.config.iterations, &mut self.random)?;
+        let username = user.username.to_string();
+        self.registry.register(user)?;
+        self.credentials.push(Credential {
+            username: username.clone(),
+            hash,
+            failures: 0,
+            locked_until: None,
+        });
+        Ok(self.registry.get(&username).unwrap())
+    }
+
+    // checks the password and hands out a session; only a successful login
+    // counts as a sign-in
+    pub fn login(&mut self, username: &str, password: &str) -> Result<SessionToken, AuthError> {
+        let now = self.clock.now();
+        let Some(index) = self.credential(username) else {
+            // take as long as a real check would
+            PasswordHash::with_salt(password, self.config.iterations, vec![0; SALT_BYTES]);
+            return Err(AuthError::InvalidCredentials);
+        };
+        self.check_password(index, password, now)?;
+        let username = self.credentials[index].username.clone();
+        let user = self.registry.get(&username).unwrap();
+        if !user.active {
+            return Err(AuthError::Inactive(username));
+        }
+
+        // make everything that can fail before changing anything
+        let mut bytes = [0; TOKEN_BYTES];
+        self.random.fill(&mut bytes)?;
+        let token = SessionToken(crypto::to_hex(&bytes));
+        let upgraded = if self.credentials[index].hash.iterations() < self.config.iterations {
+            Some(PasswordHash::new(
+                password,
+                self.config.iterations,
+                &mut self.random,
+            )?)
+        } else {
+            None
+        };
+        self.registry.sign_in(&username, now)?;
+        if let Some(hash) = upgraded {
+            self.credentials[index].hash = hash;
+        }
+        self.sessions.retain(|session| session.expires > now);
+        self.sessions.push(Session {
+            token_hash: crypto::sha256(token.0.as_bytes()),
+            username,
+            expires: now.plus_secs(self.config.session_secs),
+        });
+        Ok(token)
+    }
+
+    // the user a token belongs to, if it's still good
+    pub fn authenticate(&mut self, token: &str) -> Result<&User, AuthError> {
+        let now = self.clock.now();
+        let index = self.session(token).ok_or(AuthError::InvalidSession)?;
+        if self.sessions[index].expires <= now {
+            self.sessions.remove(index);
+            return Err(AuthError::SessionExpired);
+        }
+        let user = self
+            .registry
+            .get(&self.sessions[index].username)
+            .ok_or(AuthError::InvalidSession)?;
+        if !user.active {
+            return Err(AuthError::Inactive(user.username.to_string()));
+        }
+        Ok(user)
+    }
+
+    pub fn logout(&mut self, token: &str) -> bool {
+        match self.session(token) {
+            Some(index) => {
+                self.sessions.remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // signs the user out everywhere once the new password is in. the current
+    // password is checked the same way as at login, lockout and all
+    pub fn change_password(
+        &mut self,
+        username: &str,
+        current: &str,
+        new: &str,
+    ) -> Result<(), AuthError> {
+        let now = self.clock.now();
+        let index = self
+            .credential(username)
+            .ok_or(AuthError::InvalidCredentials)?;
+        self.check_password(index, current, now)?;
+        let user = self.registry.get(username).unwrap();
+        if !user.active {
+            return Err(AuthError::Inactive(user.username.to_string()));
+        }
+        self.config.policy.check(new, user)?;
+        let credential = &mut self.credentials[index];
+        credential.hash = PasswordHash::new(new, self.config.iterations, &mut self.random)?;
+        let username = credential.username.clone();
+        self.sessions.retain(|session| session.username != username);
+        Ok(())
+    }
+
+    // deactivated users lose their sessions too
+    pub fn deactivate(&mut self, username: &str) -> Result<(), AuthError> {
+        let name = self.registry.deactivate(username)?.username.to_string();
+        self.sessions.retain(|session| session.username != name);
+        Ok(())
+    }
+
+    pub fn reactivate(&mut self, username: &str) -> Result<(), AuthError> {
+        self.registry.reactivate(username)?;
+        Ok(())
+    }
+
+    // refuses a locked account, and counts a wrong password towards locking it
+    fn check_password(
+        &mut self,
+        index: usize,
+        password: &str,
+        now: Timestamp,
+    ) -> Result<(), AuthError> {
+        let credential = &mut self.credentials[index];
+        match credential.locked_until {
+            Some(until) if now < until => return Err(AuthError::Locked(until)),
+            Some(_) => credential.locked_until = None,
+            None => {}
+        }
+        if !credential.hash.verify(password) {
+            credential.failures += 1;
+            if credential.failures >= self.config.max_failures {
+                let until = now.plus_secs(self.config.lockout_secs);
+                credential.failures = 0;
+                credential.locked_until = Some(until);
+                return Err(AuthError::Locked(until));
+            }
+            return Err(AuthError::InvalidCredentials);
+        }
+        credential.failures = 0;
+        Ok(())
+    }
+
+    // the registry's file with every password hash, failure count and lock
+    // added. sessions aren't kept, so everyone signs in again after a load
+    pub fn to_json(&self) -> String {
+        registry::to_pretty_json(&SavedAccounts {
+            registry: self.registry.saved(),
+            credentials: self
+                .credentials
+                .iter()
+                .map(|credential| SavedCredential {
+                    username: credential.username.clone(),
+                    hash: credential.hash.to_string(),
+                    failures: credential.failures,
+                    locked_until: credential.locked_until.map(Timestamp::secs),
+                })
+                .collect(),
+        })
+    }
+
+    // a file written by UserRegistry alone loads too, with nobody able to
+    // log in yet
+    pub fn from_json(
+        text: &str,
+        config: AuthConfig,
+        clock: C,
+        random: R,
+    ) -> Result<Accounts<C, R>, AuthError> {
+        let registry = UserRegistry::from_json(text)?;
+        let saved: SavedCredentials = serde_json::from_str(text).map_err(RegistryError::from)?;
+        let mut accounts = Accounts::with_registry(registry, config, clock, random);
+        for saved in saved.credentials {
+            let Some(user) = accounts.registry.get(&saved.username) else {
+                let reason = format!("a password for the unknown user '{}'", saved.username);
+                return Err(registry::corrupt(&reason).into());
+            };
+            let username = user.username.to_string();
+            if accounts.credential(&username).is_some() {
+                let reason = format!("two passwords for '{username}'");
+                return Err(registry::corrupt(&reason).into());
+            }
+            let hash = PasswordHash::parse(&saved.hash).ok_or_else(|| {
+                registry::corrupt(&format!("the password hash for '{username}' is unreadable"))
+            })?;
+            accounts.credentials.push(Credential {
+                username,
+                hash,
+                failures: saved.failures,
+                locked_until: saved.locked_until.map(Timestamp::from_secs),
+            });
+        }
+        Ok(accounts)
+    }
+
+    pub fn load(
+        path: impl AsRef<Path>,
+        config: AuthConfig,
+        clock: C,
+        random: R,
+    ) -> Result<Accounts<C, R>, AuthError> {
+        let text = fs::read_to_string(path).map_err(RegistryError::from)?;
+        Accounts::from_json(&text, config, clock, random)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        registry::save_replacing(path.as_ref(), &self.to_json())
+    }
+
+    fn credential(&self, username: &str) -> Option<usize> {
+        self.credentials
+            .iter()
+            .position(|credential| credential.username.eq_ignore_ascii_case(username))
+    }
+
+    fn session(&self, token: &str) -> Option<usize> {
+        let token_hash = crypto::sha256(token.as_bytes());
+        self.sessions
+            .iter()
+            .position(|session| crypto::constant_time_eq(&session.token_hash, &token_hash))
+    }
+}
+
+// the registry's layout with the credentials after the users. older
+// programs reading it as a plain registry skip them
+#[derive(Serialize)]
+struct SavedAccounts {
+    #[serde(flatten)]
+    registry: SavedRegistry,
+    credentials: Vec<SavedCredential>,
+}
+
+#[derive(Deserialize)]
+struct SavedCredentials {
+    #[serde(default)]
+    credentials: Vec<SavedCredential>,
+}
+
+// the hash string carries the scheme, iterations and salt with it
+#[derive(Serialize, Deserialize)]
+struct SavedCredential {
+    username: String,
+    hash: String,
+    failures: u32,
+    locked_until: Option<u64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use std::env;
+    use std::process;
+
+    // predictable salts and tokens
+    struct CountingRandom(u8);
+
+    impl Random for CountingRandom {
+        fn fill(&mut self, bytes: &mut [u8]) -> Result<(), getrandom::Error> {
+            for byte in bytes {
+                self.0 = self.0.wrapping_add(1);
+                *byte = self.0;
+            }
+            Ok(())
+        }
+    }
+
+    const PASSWORD: &str = "correct horse battery";
+
+    fn accounts(clock: &ManualClock) -> Accounts<&ManualClock, CountingRandom> {
+        let config = AuthConfig {
+            iterations: 10,
+            ..AuthConfig::default()
+        };
+        let mut accounts = Accounts::new(config, clock, CountingRandom(0));
+        accounts
+            .register(
+                User::new("someone@example.com", "someone").unwrap(),
+                PASSWORD,
+            )
+            .unwrap();
+        accounts
+    }
+
+    #[test]
+    fn test_policy() {
+        let policy = PasswordPolicy::default();
+        let user = User::new("jo.smith@example.com", "josmith").unwrap();
+        let check = |password: &str| policy.check(password, &user);
+        assert_eq!(check(PASSWORD), Ok(()));
+        assert_eq!(check("short 1"), Err(PolicyError::TooShort(12)));
+        assert_eq!(check(&"a1".repeat(65)), Err(PolicyError::TooLong(128)));
+        assert_eq!(check("alllowercaseletters"), Err(PolicyError::TooSimple(2)));
+        assert_eq!(check("Password123456!"), Err(PolicyError::Common));
+        assert_eq!(
+            check("my name is JoSmith!"),
+            Err(PolicyError::ContainsUsername)
+        );
+        assert_eq!(
+            check("I am jo.smith, hi"),
+            Err(PolicyError::ContainsUsername)
+        );
+    }
+
+    #[test]
+    fn test_hash() {
+        let hash = PasswordHash::with_salt("password", 1, b"salt".to_vec());
+        assert_eq!(
+            hash.to_string(),
+            "pbkdf2-sha256$1$73616c74$120fb6cffcf8b32c43e7225256c4f837a86548c92ccc35480805987cb70be17b"
+        );
+        assert_eq!(PasswordHash::parse(&hash.to_string()), Some(hash.clone()));
+        assert!(hash.verify("password"));
+        assert!(!hash.verify("Password"));
+        assert_eq!(PasswordHash::parse("md5$1$00$00"), None);
+        assert_eq!(PasswordHash::parse("pbkdf2-sha256$0$00$00"), None);
+
+        // the same password salted differently hashes differently
+        let mut random = CountingRandom(0);
+        let first = PasswordHash::new(PASSWORD, 10, &mut random).unwrap();
+        let second = PasswordHash::new(PASSWORD, 10, &mut random).unwrap();
+        assert_ne!(first, second);
+        assert!(first.verify(PASSWORD) && second.verify(PASSWORD));
+    }
+
+    #[test]
+    fn test_no_randomness() {
+        struct Broken;
+        impl Random for Broken {
+            fn fill(&mut self, _: &mut [u8]) -> Result<(), getrandom::Error> {
+                Err(getrandom::Error::UNSUPPORTED)
+            }
+        }
+        let clock = ManualClock::new(Timestamp::from_secs(1_700_000_000));
+        let mut accounts = Accounts::new(AuthConfig::default(), &clock, Broken);
+        let user = User::new("someone@example.com", "someone").unwrap();
+        assert!(matches!(
+            accounts.register(user, PASSWORD),
+            Err(AuthError::Random(_))
+        ));
+        assert!(accounts.registry().is_empty());
+
+        // a login that can't make a token doesn't count as a sign-in
+        struct Flaky(CountingRandom, bool);
+        impl Random for Flaky {
+            fn fill(&mut self, bytes: &mut [u8]) -> Result<(), getrandom::Error> {
+                match self.1 {
+                    true => Err(getrandom::Error::UNSUPPORTED),
+                    false => self.0.fill(bytes),
+                }
+            }
+        }
+        let config = AuthConfig {
+            iterations: 10,
+            ..AuthConfig::default()
+        };
+        let mut accounts = Accounts::new(config, &clock, Flaky(CountingRandom(0), false));
+        let user = User::new("someone@example.com", "someone").unwrap();
+        accounts.register(user, PASSWORD).unwrap();
+        accounts.random.1 = true;
+        assert!(matches!(
+            accounts.login("someone", PASSWORD),
+            Err(AuthError::Random(_))
+        ));
+        let user = accounts.registry().get("someone").unwrap();
+        assert_eq!((user.sign_in_count, user.last_seen), (1, None));
+    }
+
+    #[test]
+    fn test_login_and_sessions() {
+        let clock = ManualClock::new(Timestamp::from_secs(1_700_000_000));
+        let mut accounts = accounts(&clock);
+        assert!(matches!(
+            accounts.login("someone", "wrong password"),
+            Err(AuthError::InvalidCredentials)
+        ));
+        assert!(matches!(
+            accounts.login("nobody", PASSWORD),
+            Err(AuthError::InvalidCredentials)
+        ));
+        assert_eq!(accounts.registry().get("someone").unwrap().sign_in_count, 1);
+
+        let token = accounts.login("SomeOne", PASSWORD).unwrap();
+        assert_eq!(format!("{token:?}"), "SessionToken(..)");
+        let user = accounts.registry().get("someone").unwrap();
+        assert_eq!((user.sign_in_count, user.last_seen), (2, Some(clock.now())));
+        assert_eq!(
+            accounts
+                .authenticate(token.as_str())
+                .unwrap()
+                .username
+                .as_str(),
+            "someone"
+        );
+
+        clock.advance(12 * 60 * 60);
+        assert!(matches!(
+            accounts.authenticate(token.as_str()),
+            Err(AuthError::SessionExpired)
+        ));
+        assert!(matches!(
+            accounts.authenticate(token.as_str()),
+            Err(AuthError::InvalidSession)
+        ));
+
+        let token = accounts.login("someone", PASSWORD).unwrap();
+        assert!(accounts.logout(token.as_str()));
+        assert!(!accounts.logout(token.as_str()));
+
+        let token = accounts.login("someone", PASSWORD).unwrap();
+        accounts.deactivate("someone").unwrap();
+        assert!(accounts.authenticate(token.as_str()).is_err());
+        assert_eq!(
+            accounts.login("someone", PASSWORD).unwrap_err().to_string(),
+            "the user 'someone' is deactivated"
+        );
+        accounts.reactivate("someone").unwrap();
+        assert!(accounts.login("someone", PASSWORD).is_ok());
+    }
+
+    #[test]
+    fn test_lockout() {
+        let clock = ManualClock::new(Timestamp::from_secs(1_700_000_000));
+        let mut accounts = accounts(&clock);
+        for _ in 0..4 {
+            assert!(matches!(
+                accounts.login("someone", "nope"),
+                Err(AuthError::InvalidCredentials)
+            ));
+        }
+        let error = accounts.login("someone", "nope").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "too many failed logins, try again after 2023-11-14T22:28:20Z"
+        );
+        // even the right password is refused until the lock runs out
+        clock.advance(14 * 60);
+        assert!(matches!(
+            accounts.login("someone", PASSWORD),
+            Err(AuthError::Locked(_))
+        ));
+        clock.advance(60);
+        assert!(accounts.login("someone", PASSWORD).is_ok());
+        assert_eq!(accounts.registry().get("someone").unwrap().sign_in_count, 2);
+    }
+
+    #[test]
+    fn test_change_password_and_upgrade() {
+        let clock = ManualClock::new(Timestamp::from_secs(1_700_000_000));
+        let mut accounts = accounts(&clock);
+        let token = accounts.login("someone", PASSWORD).unwrap();
+        assert!(matches!(
+            accounts.change_password("someone", "wrong", "a new Password 2"),
+            Err(AuthError::InvalidCredentials)
+        ));
+        assert!(matches!(
+            accounts.change_password("someone", PASSWORD, "short"),
+            Err(AuthError::Policy(PolicyError::TooShort(12)))
+        ));
+        accounts
+            .change_password("someone", PASSWORD, "a new Password 2")
+            .unwrap();
+        assert!(accounts.authenticate(token.as_str()).is_err());
+        assert!(accounts.login("someone", PASSWORD).is_err());
+        assert!(accounts.login("someone", "a new Password 2").is_ok());
+
+        // hashes made with fewer rounds are redone at the next login, but
+        // not on one that's refused
+        accounts.config.iterations = 20;
+        accounts.deactivate("someone").unwrap();
+        assert!(matches!(
+            accounts.login("someone", "a new Password 2"),
+            Err(AuthError::Inactive(_))
+        ));
+        assert_eq!(accounts.credentials[0].hash.iterations(), 10);
+        accounts.reactivate("someone").unwrap();
+        accounts.login("someone", "a new Password 2").unwrap();
+        assert_eq!(accounts.credentials[0].hash.iterations(), 20);
+    }
+
+    #[test]
+    fn test_change_password_is_guarded_like_login() {
+        let clock = ManualClock::new(Timestamp::from_secs(1_700_000_000));
+        let mut accounts = accounts(&clock);
+        // wrong guesses here and at login count towards the same lock
+        for _ in 0..2 {
+            assert!(matches!(
+                accounts.login("someone", "nope"),
+                Err(AuthError::InvalidCredentials)
+            ));
+        }
+        for _ in 0..2 {
+            assert!(matches!(
+                accounts.change_password("someone", "nope", "a new Password 2"),
+                Err(AuthError::InvalidCredentials)
+            ));
+        }
+        assert!(matches!(
+            accounts.change_password("someone", "nope", "a new Password 2"),
+            Err(AuthError::Locked(_))
+        ));
+        // a locked account can't change its password, even knowing it
+        assert!(matches!(
+            accounts.change_password("someone", PASSWORD, "a new Password 2"),
+            Err(AuthError::Locked(_))
+        ));
+        clock.advance(15 * 60);
+
+        accounts.deactivate("someone").unwrap();
+        assert_eq!(
+            accounts
+                .change_password("someone", PASSWORD, "a new Password 2")
+                .unwrap_err()
+                .to_string(),
+            "the user 'someone' is deactivated"
+        );
+        accounts.reactivate("someone").unwrap();
+        assert!(accounts.login("someone", PASSWORD).is_ok());
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let clock = ManualClock::new(Timestamp::from_secs(1_700_000_000));
+        let mut accounts = accounts(&clock);
+        accounts
+            .register(
+                User::new("another@example.com", "another").unwrap(),
+                PASSWORD,
+            )
+            .unwrap();
+        for _ in 0..5 {
+            accounts.login("another", "nope").unwrap_err();
+        }
+        accounts.login("someone", "nope").unwrap_err();
+        let json = accounts.to_json();
+        // still a registry file underneath
+        assert_eq!(
+            UserRegistry::from_json(&json).unwrap(),
+            *accounts.registry()
+        );
+
+        let path = env::temp_dir().join(format!("struts-accounts-{}.json", process::id()));
+        accounts.save(&path).unwrap();
+        let config = accounts.config.clone();
+        let loaded = Accounts::load(&path, config, &clock, CountingRandom(100));
+        fs::remove_file(&path).unwrap();
+        let mut loaded = loaded.unwrap();
+        assert_eq!(loaded.registry(), accounts.registry());
+        assert_eq!(loaded.to_json(), json);
+        // the lock and the failure count came along
+        assert!(matches!(
+            loaded.login("another", PASSWORD),
+            Err(AuthError::Locked(_))
+        ));
+        for _ in 0..3 {
+            loaded.login("someone", "nope").unwrap_err();
+        }
+        assert!(matches!(
+            loaded.login("someone", "nope"),
+            Err(AuthError::Locked(_))
+        ));
+        clock.advance(15 * 60);
+        assert!(loaded.login("someone", PASSWORD).is_ok());
+
+        // users saved by the registry alone have no password yet
+        let mut bare = Accounts::from_json(
+            &accounts.registry().to_json(),
+            AuthConfig::default(),
+            &clock,
+            CountingRandom(0),
+        )
+        .unwrap();
+        assert_eq!(bare.registry().len(), 2);
+        assert!(matches!(
+            bare.login("someone", PASSWORD),
+            Err(AuthError::InvalidCredentials)
+        ));
+
+        let error = |credentials: &str| {
+            let text = json.replace(
+                r#""credentials": ["#,
+                &format!(r#""credentials": [{credentials},"#),
+            );
+            Accounts::from_json(&text, AuthConfig::default(), &clock, CountingRandom(0))
+                .err()
+                .unwrap()
+                .to_string()
+        };
+        assert_eq!(
+            error(r#"{"username": "nobody", "hash": "", "failures": 0, "locked_until": null}"#),
+            "the file is damaged: a password for the unknown user 'nobody'"
+        );
+        assert_eq!(
+            error(
+                r#"{"username": "Another", "hash": "md5$1$00$00", "failures": 0, "locked_until": null}"#
+            ),
+            "the file is damaged: the password hash for 'another' is unreadable"
+        );
+    }
+}
@@ -0,0 +1,91 @@
+use std::fmt::Write;
+
+use pbkdf2::pbkdf2_hmac;
+use sha2::{Digest as _, Sha256};
+
+// hashing and randomness for credentials, leaning on the RustCrypto crates
+// and getrandom rather than anything home made
+
+pub type Digest = [u8; 32];
+
+pub fn sha256(data: &[u8]) -> Digest {
+    Sha256::digest(data).into()
+}
+
+// PBKDF2 with HMAC-SHA256, filling `output` with as many blocks as it needs
+pub fn pbkdf2_sha256(password: &[u8], salt: &[u8], iterations: u32, output: &mut [u8]) {
+    pbkdf2_hmac::<Sha256>(password, salt, iterations, output);
+}
+
+// compares without stopping at the first difference, so how long it takes
+// says nothing about where two secrets differ
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    let mut hex = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        write!(hex, "{byte:02x}").unwrap();
+    }
+    hex
+}
+
+pub fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+// where salts and session tokens come from. without randomness nothing
+// here can be kept secret, so a failure is passed up rather than papered over
+pub trait Random {
+    fn fill(&mut self, bytes: &mut [u8]) -> Result<(), getrandom::Error>;
+}
+
+// the operating system's generator, on whatever platform this is
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OsRandom;
+
+impl Random for OsRandom {
+    fn fill(&mut self, bytes: &mut [u8]) -> Result<(), getrandom::Error> {
+        getrandom::getrandom(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a couple of published vectors, to show the pieces are wired up right
+    #[test]
+    fn test_hashes() {
+        assert_eq!(
+            to_hex(&sha256(b"abc")),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        let mut output = [0; 32];
+        pbkdf2_sha256(b"password", b"salt", 4096, &mut output);
+        assert_eq!(
+            to_hex(&output),
+            "c5e478d59288c841aa530db6845c4c8d962893a001ce4e11a4963873aa98134a"
+        );
+    }
+
+    #[test]
+    fn test_helpers() {
+        assert!(constant_time_eq(b"same", b"same"));
+        assert!(!constant_time_eq(b"same", b"sane"));
+        assert!(!constant_time_eq(b"same", b"sam"));
+        assert_eq!(from_hex("00ff7f"), Some(vec![0, 255, 127]));
+        assert_eq!(from_hex("0g"), None);
+        assert_eq!(from_hex("abc"), None);
+        let mut bytes = [0; 16];
+        OsRandom.fill(&mut bytes).unwrap();
+        assert_ne!(bytes, [0; 16]);
+    }
+}
@@ -0,0 +1,338 @@
+use std::f64::consts::FRAC_PI_2;
+
+use crate::Rectangle;
+
+// how a piece may be turned to make it fit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    None,
+    // as given or turned 90 degrees
+    QuarterTurn,
+    // any angle at all, like a long board laid corner to corner
+    AnyAngle,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FitMode {
+    // whether the piece may touch the sides, rather than needing room to spare
+    pub inclusive: bool,
+    pub rotation: Rotation,
+}
+
+impl FitMode {
+    // what can_hold checks
+    pub const STRICT: FitMode = FitMode {
+        inclusive: false,
+        rotation: Rotation::None,
+    };
+    pub const INCLUSIVE: FitMode = FitMode {
+        inclusive: true,
+        rotation: Rotation::None,
+    };
+    pub const QUARTER_TURN: FitMode = FitMode {
+        inclusive: true,
+        rotation: Rotation::QuarterTurn,
+    };
+    pub const ANY_ANGLE: FitMode = FitMode {
+        inclusive: true,
+        rotation: Rotation::AnyAngle,
+    };
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Orientation {
+    Upright,
+    QuarterTurn,
+    // degrees anticlockwise from how the piece was given, between 0 and 180
+    Angled(f64),
+}
+
+// how a piece fits. the margins are the room left across and down, around
+// the piece's bounding box when it's at an angle, so they're only whole
+// numbers for the other orientations
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fit {
+    pub orientation: Orientation,
+    pub horizontal: f64,
+    pub vertical: f64,
+}
+
+// angled fits are worked out in floating point, so they're allowed this much
+// slack on the inclusive side
+const EPSILON: f64 = 1e-9;
+
+impl Rectangle {
+    // how `other` fits inside, trying it upright first, then turned a quarter,
+    // then at an angle, as far as the mode allows. None if it doesn't fit
+    pub fn fit(&self, other: &Rectangle, mode: FitMode) -> Option<Fit> {
+        let (width, height) = (f64::from(self.width), f64::from(self.height));
+        let upright = |piece_width: u32, piece_height: u32, orientation| {
+            let room = |outer: u32, inner: u32| {
+                if mode.inclusive {
+                    outer >= inner
+                } else {
+                    outer > inner
+                }
+            };
+            (room(self.width, piece_width) && room(self.height, piece_height)).then(|| Fit {
+                orientation,
+                horizontal: width - f64::from(piece_width),
+                vertical: height - f64::from(piece_height),
+            })
+        };
+        if let Some(fit) = upright(other.width, other.height, Orientation::Upright) {
+            return Some(fit);
+        }
+        if mode.rotation == Rotation::None {
+            return None;
+        }
+        if let Some(fit) = upright(other.height, other.width, Orientation::QuarterTurn) {
+            return Some(fit);
+        }
+        if mode.rotation == Rotation::AnyAngle {
+            return self.fit_angled(other, mode);
+        }
+        None
+    }
+
+    // whether `other` fits at some angle that isn't a multiple of 90 degrees
+    fn fit_angled(&self, other: &Rectangle, mode: FitMode) -> Option<Fit> {
+        // work with the piece's long side p and short side q, in a frame where
+        // the container's long side a runs across and b runs down
+        let (p, q) = (
+            f64::from(other.width.max(other.height)),
+            f64::from(other.width.min(other.height)),
+        );
+        let transposed = self.width < self.height;
+        let (a, b) = (
+            f64::from(self.width.max(self.height)),
+            f64::from(self.width.min(self.height)),
+        );
+        // turning the long side up from across by theta makes the bounding box
+        // p cos theta + q sin theta wide, which is r cos(theta - phi). it's
+        // narrow enough from theta0 on. past theta0 the height p sin theta +
+        // q cos theta never drops below the lesser of its height at theta0
+        // and p, which is too tall anyway, so theta0 is the angle to try
+        let r = p.hypot(q);
+        // shorter than a, it fits upright if it fits at all. strictly, exactly
+        // a long needs turning a touch
+        let short_enough = if mode.inclusive { p <= a } else { p < a };
+        if short_enough || r == 0.0 {
+            return None;
+        }
+        let theta = q.atan2(p) + (a / r).acos();
+        let across = p * theta.cos() + q * theta.sin();
+        let down = p * theta.sin() + q * theta.cos();
+        let fits = if mode.inclusive {
+            theta <= FRAC_PI_2 && down <= b + EPSILON * b.max(1.0)
+        } else {
+            // any angle a little past theta0 is narrower than a, so only the
+            // height has to have room to spare
+            theta < FRAC_PI_2 && down < b
+        };
+        if !fits {
+            return None;
+        }
+
+        // back to the container's own frame and the piece as it was given
+        let (angle, across, down) = if transposed {
+            (FRAC_PI_2 - theta, down, across)
+        } else {
+            (theta, across, down)
+        };
+        let given = if other.width >= other.height {
+            0.0
+        } else {
+            FRAC_PI_2
+        };
+        Some(Fit {
+            orientation: Orientation::Angled((angle - given).to_degrees().rem_euclid(180.0)),
+            horizontal: (f64::from(self.width) - across).max(0.0),
+            vertical: (f64::from(self.height) - down).max(0.0),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn rectangle(width: u32, height: u32) -> Rectangle {
+        Rectangle::new(width, height).unwrap()
+    }
+
+    #[test]
+    fn test_modes() {
+        let board = rectangle(30, 50);
+        let same = rectangle(30, 50);
+        let turned = rectangle(50, 30);
+
+        assert_eq!(board.fit(&same, FitMode::STRICT), None);
+        assert_eq!(
+            board.fit(&same, FitMode::INCLUSIVE),
+            Some(Fit {
+                orientation: Orientation::Upright,
+                horizontal: 0.0,
+                vertical: 0.0
+            })
+        );
+        assert_eq!(board.fit(&turned, FitMode::INCLUSIVE), None);
+        assert_eq!(
+            board.fit(&turned, FitMode::QUARTER_TURN),
+            Some(Fit {
+                orientation: Orientation::QuarterTurn,
+                horizontal: 0.0,
+                vertical: 0.0
+            })
+        );
+        let strict_turn = FitMode {
+            inclusive: false,
+            rotation: Rotation::QuarterTurn,
+        };
+        assert_eq!(board.fit(&turned, strict_turn), None);
+        assert_eq!(
+            board.fit(&rectangle(45, 20), strict_turn),
+            Some(Fit {
+                orientation: Orientation::QuarterTurn,
+                horizontal: 10.0,
+                vertical: 5.0
+            })
+        );
+        // upright wins when both would do
+        assert_eq!(
+            board
+                .fit(&rectangle(10, 10), FitMode::ANY_ANGLE)
+                .unwrap()
+                .orientation,
+            Orientation::Upright
+        );
+    }
+
+    #[test]
+    fn test_angles() {
+        // a 10x1 stick is too long for an 8x8 box either way, but the
+        // diagonal is over 11
+        let stick = rectangle(10, 1);
+        let square = rectangle(8, 8);
+        assert_eq!(square.fit(&stick, FitMode::QUARTER_TURN), None);
+        let fit = square.fit(&stick, FitMode::ANY_ANGLE).unwrap();
+        let Orientation::Angled(angle) = fit.orientation else {
+            panic!("expected an angle, got {fit:?}");
+        };
+        assert!(angle > 0.0 && angle < 90.0, "{angle}");
+        assert!((fit.horizontal - 0.0).abs() < 1e-9, "{fit:?}");
+        assert!(fit.vertical > 0.0, "{fit:?}");
+
+        // standing the box up turns the answer the other way
+        let tall = rectangle(8, 9);
+        let fit = tall.fit(&stick, FitMode::ANY_ANGLE).unwrap();
+        assert!(fit.horizontal > 0.0 && fit.vertical.abs() < 1e-9, "{fit:?}");
+
+        // too wide to go corner to corner
+        assert_eq!(square.fit(&rectangle(10, 4), FitMode::ANY_ANGLE), None);
+        assert_eq!(rectangle(0, 0).fit(&stick, FitMode::ANY_ANGLE), None);
+    }
+
+    #[test]
+    fn test_can_hold() {
+        let board = rectangle(30, 50);
+        for (width, height) in [(29, 49), (30, 49), (29, 50), (10, 40), (60, 45)] {
+            let other = rectangle(width, height);
+            assert_eq!(
+                board.can_hold(&other),
+                board.fit(&other, FitMode::STRICT).is_some()
+            );
+        }
+    }
+
+    // where the corners of a piece at some angle end up, to check fits
+    // without the algebra they came from
+    fn bounding_box(piece: &Rectangle, degrees: f64) -> (f64, f64) {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        let (width, height) = (f64::from(piece.width), f64::from(piece.height));
+        (
+            width * cos.abs() + height * sin.abs(),
+            width * sin.abs() + height * cos.abs(),
+        )
+    }
+
+    fn mode() -> impl Strategy<Value = FitMode> {
+        (
+            any::<bool>(),
+            prop_oneof![
+                Just(Rotation::None),
+                Just(Rotation::QuarterTurn),
+                Just(Rotation::AnyAngle)
+            ],
+        )
+            .prop_map(|(inclusive, rotation)| FitMode {
+                inclusive,
+                rotation,
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn fits_are_real(
+            outer in (0..60u32, 0..60u32),
+            inner in (0..80u32, 0..30u32),
+            mode in mode(),
+        ) {
+            let outer = rectangle(outer.0, outer.1);
+            let inner = rectangle(inner.0, inner.1);
+            if let Some(fit) = outer.fit(&inner, mode) {
+                let degrees = match fit.orientation {
+                    Orientation::Upright => 0.0,
+                    Orientation::QuarterTurn => 90.0,
+                    Orientation::Angled(degrees) => degrees,
+                };
+                let allowed = match mode.rotation {
+                    Rotation::None => fit.orientation == Orientation::Upright,
+                    Rotation::QuarterTurn => !matches!(fit.orientation, Orientation::Angled(_)),
+                    Rotation::AnyAngle => true,
+                };
+                prop_assert!(allowed, "{:?}", fit);
+                let (across, down) = bounding_box(&inner, degrees);
+                prop_assert!(across <= f64::from(outer.width) + 1e-6, "{fit:?}");
+                prop_assert!(down <= f64::from(outer.height) + 1e-6, "{fit:?}");
+                prop_assert!((f64::from(outer.width) - across - fit.horizontal).abs() < 1e-6);
+                prop_assert!((f64::from(outer.height) - down - fit.vertical).abs() < 1e-6);
+            }
+        }
+
+        #[test]
+        fn looser_modes_fit_more(
+            outer in (0..60u32, 0..60u32),
+            inner in (0..80u32, 0..30u32),
+        ) {
+            let outer = rectangle(outer.0, outer.1);
+            let inner = rectangle(inner.0, inner.1);
+            let fits = |mode| outer.fit(&inner, mode).is_some();
+            prop_assert_eq!(outer.can_hold(&inner), fits(FitMode::STRICT));
+            prop_assert!(!fits(FitMode::STRICT) || fits(FitMode::INCLUSIVE));
+            prop_assert!(!fits(FitMode::INCLUSIVE) || fits(FitMode::QUARTER_TURN));
+            prop_assert!(!fits(FitMode::QUARTER_TURN) || fits(FitMode::ANY_ANGLE));
+            let strict_any = FitMode { inclusive: false, rotation: Rotation::AnyAngle };
+            prop_assert!(!fits(strict_any) || fits(FitMode::ANY_ANGLE));
+        }
+
+        #[test]
+        fn any_angle_finds_every_fit(
+            outer in (0..60u32, 0..60u32),
+            inner in (0..80u32, 0..30u32),
+        ) {
+            let outer = rectangle(outer.0, outer.1);
+            let inner = rectangle(inner.0, inner.1);
+            // try every tenth of a degree, with room to spare for rounding
+            let roomy = (0..=900).any(|tenths| {
+                let (across, down) = bounding_box(&inner, f64::from(tenths) / 10.0);
+                across < f64::from(outer.width) - 1e-6 && down < f64::from(outer.height) - 1e-6
+            });
+            if roomy {
+                let strict_any = FitMode { inclusive: false, rotation: Rotation::AnyAngle };
+                prop_assert!(outer.fit(&inner, strict_any).is_some());
+            }
+        }
+    }
+}
@@ -0,0 +1,318 @@
+use std::fmt;
+
+use crate::{Rectangle, RectangleError};
+
+// a spot on the integer grid, x growing right and y growing down
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Point {
+    pub fn new(x: i32, y: i32) -> Point {
+        Point { x, y }
+    }
+}
+
+// a rectangle placed on the grid, covering x..x + width and y..y + height.
+// the far edges are left out so rectangles side by side share an edge but
+// no area. edges are worked out in i64, where any i32 plus any u32 fits, so
+// nothing here can overflow; results that wouldn't fit back in a Rect come
+// back as None
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Rect {
+    pub fn new(x: i32, y: i32, width: u32, height: u32) -> Rect {
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    pub fn at(origin: Point, size: &Rectangle) -> Rect {
+        Rect::new(origin.x, origin.y, size.width, size.height)
+    }
+
+    // the rectangle between two edges each way, if it can be one
+    pub fn from_edges(left: i64, top: i64, right: i64, bottom: i64) -> Option<Rect> {
+        Some(Rect {
+            x: i32::try_from(left).ok()?,
+            y: i32::try_from(top).ok()?,
+            width: u32::try_from(right.checked_sub(left)?).ok()?,
+            height: u32::try_from(bottom.checked_sub(top)?).ok()?,
+        })
+    }
+
+    // a Rect can be bigger than a Rectangle's area allows
+    pub fn size(&self) -> Result<Rectangle, RectangleError> {
+        Rectangle::new(self.width, self.height)
+    }
+
+    pub fn left(&self) -> i64 {
+        i64::from(self.x)
+    }
+
+    pub fn top(&self) -> i64 {
+        i64::from(self.y)
+    }
+
+    pub fn right(&self) -> i64 {
+        self.left() + i64::from(self.width)
+    }
+
+    pub fn bottom(&self) -> i64 {
+        self.top() + i64::from(self.height)
+    }
+
+    // any u32 times any u32 fits in a u64
+    pub fn area(&self) -> u64 {
+        u64::from(self.width) * u64::from(self.height)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    pub fn contains_point(&self, point: Point) -> bool {
+        let (x, y) = (i64::from(point.x), i64::from(point.y));
+        self.left() <= x && x < self.right() && self.top() <= y && y < self.bottom()
+    }
+
+    // whether `other` lies wholly inside, edges included
+    pub fn contains(&self, other: &Rect) -> bool {
+        self.left() <= other.left()
+            && other.right() <= self.right()
+            && self.top() <= other.top()
+            && other.bottom() <= self.bottom()
+    }
+
+    // whether the two share any area; touching edges don't count
+    pub fn overlaps(&self, other: &Rect) -> bool {
+        self.left().max(other.left()) < self.right().min(other.right())
+            && self.top().max(other.top()) < self.bottom().min(other.bottom())
+    }
+
+    // the area the two share, if any
+    pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+        if !self.overlaps(other) {
+            return None;
+        }
+        Rect::from_edges(
+            self.left().max(other.left()),
+            self.top().max(other.top()),
+            self.right().min(other.right()),
+            self.bottom().min(other.bottom()),
+        )
+    }
+
+    // the smallest rectangle around both, or None if it's too big to be one
+    pub fn union(&self, other: &Rect) -> Option<Rect> {
+        Rect::from_edges(
+            self.left().min(other.left()),
+            self.top().min(other.top()),
+            self.right().max(other.right()),
+            self.bottom().max(other.bottom()),
+        )
+    }
+
+    // the smallest rectangle around them all, None for none or too big
+    pub fn bounding_box<'a>(rects: impl IntoIterator<Item = &'a Rect>) -> Option<Rect> {
+        let mut rects = rects.into_iter();
+        let first = *rects.next()?;
+        rects.try_fold(first, |total, rect| total.union(rect))
+    }
+
+    pub fn translate(&self, dx: i32, dy: i32) -> Option<Rect> {
+        Some(Rect {
+            x: self.x.checked_add(dx)?,
+            y: self.y.checked_add(dy)?,
+            ..*self
+        })
+    }
+
+    // scales the position and size by numerator / denominator about the
+    // grid's origin. edges are rounded down, so rectangles that tiled before
+    // still tile after
+    pub fn scale(&self, numerator: u32, denominator: u32) -> Option<Rect> {
+        if denominator == 0 {
+            return None;
+        }
+        let scale = |edge: i64| -> Option<i64> {
+            let scaled =
+                (i128::from(edge) * i128::from(numerator)).div_euclid(i128::from(denominator));
+            i64::try_from(scaled).ok()
+        };
+        Rect::from_edges(
+            scale(self.left())?,
+            scale(self.top())?,
+            scale(self.right())?,
+            scale(self.bottom())?,
+        )
+    }
+}
+
+impl fmt::Display for Rect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}x{} at ({}, {})",
+            self.width, self.height, self.x, self.y
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_basics() {
+        let a = Rect::new(0, 0, 30, 50);
+        let b = Rect::new(20, 40, 30, 50);
+        assert_eq!(a.intersection(&b), Some(Rect::new(20, 40, 10, 10)));
+        assert_eq!(a.union(&b), Some(Rect::new(0, 0, 50, 90)));
+        assert!(a.contains_point(Point::new(29, 49)));
+        assert!(!a.contains_point(Point::new(30, 0)));
+
+        // side by side they touch but don't overlap
+        let beside = Rect::new(30, 0, 10, 10);
+        assert!(!a.overlaps(&beside));
+        assert_eq!(a.intersection(&beside), None);
+
+        assert_eq!(a.translate(-5, 5), Some(Rect::new(-5, 5, 30, 50)));
+        assert_eq!(a.scale(3, 2), Some(Rect::new(0, 0, 45, 75)));
+        assert_eq!(
+            Rect::new(-3, 1, 3, 1).scale(1, 2),
+            Some(Rect::new(-2, 0, 2, 1))
+        );
+        assert_eq!(a.scale(1, 0), None);
+        assert_eq!(a.to_string(), "30x50 at (0, 0)");
+        assert_eq!(
+            Rect::at(Point::new(1, 2), &Rectangle::square(5).unwrap()),
+            Rect::new(1, 2, 5, 5)
+        );
+    }
+
+    #[test]
+    fn test_extremes() {
+        let huge = Rect::new(i32::MAX, i32::MAX, u32::MAX, u32::MAX);
+        assert_eq!(huge.right(), i64::from(i32::MAX) + i64::from(u32::MAX));
+        assert_eq!(huge.area(), u64::from(u32::MAX) * u64::from(u32::MAX));
+        assert_eq!(huge.translate(1, 0), None);
+        assert_eq!(huge.scale(2, 1), None);
+        assert!(huge.size().is_err());
+
+        // the box around both ends of the grid is wider than a u32
+        let far_left = Rect::new(i32::MIN, 0, 1, 1);
+        assert_eq!(far_left.union(&huge), None);
+        assert_eq!(
+            Rect::bounding_box(&[far_left, Rect::new(0, 0, 1, 1)]),
+            Some(Rect::new(i32::MIN, 0, (1 << 31) + 1, 1))
+        );
+        assert_eq!(Rect::bounding_box(&[]), None);
+    }
+
+    fn rect() -> impl Strategy<Value = Rect> {
+        prop_oneof![
+            // anywhere at all, to shake out overflows
+            (any::<i32>(), any::<i32>(), any::<u32>(), any::<u32>()),
+            // small and close together, so they often overlap
+            (-50..50i32, -50..50i32, 0..60u32, 0..60u32),
+        ]
+        .prop_map(|(x, y, width, height)| Rect::new(x, y, width, height))
+    }
+
+    fn point() -> impl Strategy<Value = Point> {
+        prop_oneof![(any::<i32>(), any::<i32>()), (-60..120i32, -60..120i32)]
+            .prop_map(|(x, y)| Point::new(x, y))
+    }
+
+    proptest! {
+        #[test]
+        fn intersection_is_in_both(a in rect(), b in rect()) {
+            let shared = a.intersection(&b);
+            prop_assert_eq!(shared, b.intersection(&a));
+            prop_assert_eq!(shared.is_some(), a.overlaps(&b));
+            if let Some(shared) = shared {
+                prop_assert!(!shared.is_empty());
+                prop_assert!(a.contains(&shared) && b.contains(&shared));
+            }
+        }
+
+        #[test]
+        fn a_point_is_in_both_exactly_when_in_the_intersection(
+            a in rect(),
+            b in rect(),
+            p in point(),
+        ) {
+            let in_both = a.contains_point(p) && b.contains_point(p);
+            let in_shared = a.intersection(&b).is_some_and(|shared| shared.contains_point(p));
+            prop_assert_eq!(in_both, in_shared);
+        }
+
+        #[test]
+        fn union_holds_both(a in rect(), b in rect()) {
+            prop_assert_eq!(a.union(&b), b.union(&a));
+            match a.union(&b) {
+                Some(union) => {
+                    prop_assert!(union.contains(&a) && union.contains(&b));
+                    prop_assert!(union.area() >= a.area().max(b.area()));
+                }
+                // only when it really is too big
+                None => prop_assert!(
+                    a.right().max(b.right()) - a.left().min(b.left()) > i64::from(u32::MAX)
+                        || a.bottom().max(b.bottom()) - a.top().min(b.top())
+                            > i64::from(u32::MAX)
+                ),
+            }
+        }
+
+        #[test]
+        fn translating_moves_without_resizing(a in rect(), dx in any::<i32>(), dy in any::<i32>()) {
+            match a.translate(dx, dy) {
+                Some(moved) => {
+                    prop_assert_eq!(moved.area(), a.area());
+                    prop_assert_eq!(moved.left() - a.left(), i64::from(dx));
+                    prop_assert_eq!(moved.top() - a.top(), i64::from(dy));
+                    if let (Some(back_x), Some(back_y)) = (dx.checked_neg(), dy.checked_neg()) {
+                        prop_assert_eq!(moved.translate(back_x, back_y), Some(a));
+                    }
+                }
+                None => prop_assert!(a.x.checked_add(dx).is_none() || a.y.checked_add(dy).is_none()),
+            }
+        }
+
+        #[test]
+        fn scaling_by_whole_numbers_scales_the_area(a in rect(), factor in 0..100u32) {
+            prop_assert_eq!(a.scale(1, 1), Some(a));
+            if let Some(scaled) = a.scale(factor, 1) {
+                let factor = u128::from(factor);
+                prop_assert_eq!(u128::from(scaled.area()), u128::from(a.area()) * factor * factor);
+            }
+        }
+
+        #[test]
+        fn scaling_keeps_containment(
+            a in rect(),
+            b in rect(),
+            numerator in 0..20u32,
+            denominator in 1..20u32,
+        ) {
+            // the box around both holds a, so scaled it still holds a scaled
+            if let (Some(both), Some(scaled)) = (a.union(&b), a.scale(numerator, denominator)) {
+                let both = both.scale(numerator, denominator);
+                prop_assert!(both.is_none_or(|both| both.contains(&scaled)));
+            }
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use std::fmt;
+
+pub mod clock;
+pub mod credentials;
+pub mod crypto;
+pub mod fit;
+pub mod geometry;
+pub mod packing;
+pub mod quadtree;
+pub mod registry;
+pub mod user;
+
+// a Rectangle made through new, square or try_from always has an area that
+// fits in a u32, so area can't overflow. struct literals skip that check, so
+// checked_area and area_u64 are there for sizes that came from anywhere
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rectangle {
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RectangleError {
+    // width times height doesn't fit in a u32
+    AreaTooLarge { width: u64, height: u64 },
+    // a side doesn't fit in a u32 at all
+    SideTooLong(u64),
+}
+
+impl fmt::Display for RectangleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RectangleError::AreaTooLarge { width, height } => {
+                write!(f, "a {width}x{height} rectangle's area is too large")
+            }
+            RectangleError::SideTooLong(side) => write!(f, "a side of {side} is too long"),
+        }
+    }
+}
+
+impl std::error::Error for RectangleError {}
+
+impl Rectangle {
+    pub fn new(width: u32, height: u32) -> Result<Rectangle, RectangleError> {
+        let rectangle = Rectangle { width, height };
+        match rectangle.checked_area() {
+            Some(_) => Ok(rectangle),
+            None => Err(RectangleError::AreaTooLarge {
+                width: u64::from(width),
+                height: u64::from(height),
+            }),
+        }
+    }
+
+    // panics rather than wrapping if the area doesn't fit, which only a
+    // struct literal can cause
+    pub fn area(&self) -> u32 {
+        self.checked_area()
+            .expect("a rectangle's area should fit in a u32")
+    }
+
+    pub fn checked_area(&self) -> Option<u32> {
+        self.width.checked_mul(self.height)
+    }
+
+    // any u32 times any u32 fits in a u64
+    pub fn area_u64(&self) -> u64 {
+        u64::from(self.width) * u64::from(self.height)
+    }
+
+    pub fn width(&self) -> bool {
+        self.width > 0
+    }
+
+    pub fn can_hold(&self, other: &Rectangle) -> bool {
+        self.width > other.width && self.height > other.height
+    }
+}
+
+// we don't need to use separate impl blocks, but it is valid to do so
+// best practice is to keep all methods related to the struct in the same impl block
+impl Rectangle {
+    // don't need use `self` here because we are not using any instance data
+    // this is a static method, it does not require an instance of the struct to be called
+    // it is similar to a constructor in other languages
+    pub fn square(size: u32) -> Result<Self, RectangleError> {
+        // Self is a shorthand for Rectangle here
+        Self::new(size, size)
+    }
+}
+
+// for sizes worked out in wider types, like a layout's total width
+impl TryFrom<(u64, u64)> for Rectangle {
+    type Error = RectangleError;
+
+    fn try_from((width, height): (u64, u64)) -> Result<Rectangle, RectangleError> {
+        let side = |side: u64| u32::try_from(side).map_err(|_| RectangleError::SideTooLong(side));
+        Rectangle::new(side(width)?, side(height)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rectangle_area() {
+        let rect = Rectangle::new(30, 50).unwrap();
+        assert_eq!(rect.area(), 1500);
+        assert_eq!(rect.checked_area(), Some(1500));
+
+        // 65536 squared is one past u32::MAX
+        assert_eq!(
+            Rectangle::square(65_536).unwrap_err(),
+            RectangleError::AreaTooLarge {
+                width: 65_536,
+                height: 65_536
+            }
+        );
+        assert_eq!(Rectangle::square(65_535).unwrap().area(), 4_294_836_225);
+        assert_eq!(
+            Rectangle::try_from((1 << 32, 1)).unwrap_err().to_string(),
+            "a side of 4294967296 is too long"
+        );
+        assert_eq!(Rectangle::try_from((600, 400)).unwrap().area(), 240_000);
+
+        // a literal can still be too big, but the wide variant stays right
+        let huge = Rectangle {
+            width: u32::MAX,
+            height: u32::MAX,
+        };
+        assert_eq!(huge.checked_area(), None);
+        assert_eq!(huge.area_u64(), 18_446_744_065_119_617_025);
+    }
+}
@@ -1,8 +1,11 @@
+use struts::Rectangle;
+use struts::user::{Email, User, Username, build_user};
+
 fn main() {
-    // create a User
+    // create a User; emails and usernames are checked before they can go in one
     let user1 = build_user(
-        String::from("someone@example.com"),
-        String::from("someusername123"),
+        Email::parse("someone@example.com").expect("a valid email"),
+        Username::parse("someusername123").expect("a valid username"),
     );
 
     // print user1 details
@@ -13,7 +16,7 @@ fn main() {
 
     // create another user
     let user2 = User {
-        email: String::from("another@example.com"),
+        email: Email::parse("another@example.com").expect("a valid email"),
         ..user1 // using the struct update syntax to copy fields from user1
     };
     println!("---------------------------");
@@ -52,22 +55,7 @@ fn main() {
     example();
 }
 
-//Note that the entire instance must be mutable; Rust doesn’t allow us to mark only certain fields as mutable.
-struct User {
-    active: bool,
-    username: String,
-    email: String,
-    sign_in_count: u64,
-}
-
-fn build_user(email: String, username: String) -> User {
-    User {
-        active: true,
-        username, // because of the field init shorthand, we can use just `username` instead of `username: username`
-        email,    // same for email
-        sign_in_count: 1,
-    }
-}
+// User and build_user live in user.rs, with the Email and Username types their fields use.
 
 fn example() {
     let rect = Rectangle {
@@ -132,55 +120,45 @@ fn example() {
 
     println!("Does rect1 hold rect3? {}", rect1.can_hold(&rect3));
 
-    let square = Rectangle::square(10);
+    let square = Rectangle::square(10).expect("a small square");
     println!("The area of the square is {} square pixels.", square.area());
 
     println!("Does rect1 hold square? {}", rect1.can_hold(&square));
-}
-
-#[derive(Debug)]
-struct Rectangle {
-    width: u32,
-    height: u32,
-}
-
-impl Rectangle {
-    fn area(&self) -> u32 {
-        self.width * self.height
-    }
 
-    fn width(&self) -> bool {
-        self.width > 0
-    }
-
-    fn can_hold(&self, other: &Rectangle) -> bool {
-        self.width > other.width && self.height > other.height
+    // new checks the area fits, so area can't overflow
+    if let Err(error) = Rectangle::square(100_000) {
+        println!("can't make that square: {error}");
     }
 }
 
-// we don't need to use separate impl blocks, but it is valid to do so
-// best practice is to keep all methods related to the struct in the same impl block
-impl Rectangle {
-    // don't need use `self` here because we are not using any instance data
-    // this is a static method, it does not require an instance of the struct to be called
-    // it is similar to a constructor in other languages
-    fn square(size: u32) -> Self {
-        Self {
-            // Self is a shorthand for Rectangle here
-            width: size,
-            height: size,
-        }
-    }
+// these work in u64, where the product of two u32s always fits
+fn area(width: u32, height: u32) -> u64 {
+    u64::from(width) * u64::from(height)
 }
 
-fn area(width: u32, height: u32) -> u32 {
-    width * height
+fn rect_area(rectangle: (u32, u32)) -> u64 {
+    area(rectangle.0, rectangle.1)
 }
 
-fn rect_area(rectangle: (u32, u32)) -> u32 {
-    rectangle.0 * rectangle.1
+fn rect_struct_area(rectangle: &Rectangle) -> u64 {
+    rectangle.area_u64()
 }
 
-fn rect_struct_area(rectangle: &Rectangle) -> u32 {
-    rectangle.width * rectangle.height
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_area_helpers() {
+        let rect = Rectangle::new(30, 50).unwrap();
+        assert_eq!(rect_struct_area(&rect), 1500);
+
+        let huge = Rectangle {
+            width: u32::MAX,
+            height: u32::MAX,
+        };
+        assert_eq!(rect_struct_area(&huge), huge.area_u64());
+        assert_eq!(area(u32::MAX, 2), 8_589_934_590);
+        assert_eq!(rect_area((u32::MAX, 2)), 8_589_934_590);
+    }
 }
@@ -0,0 +1,610 @@
+use std::fmt;
+
+use crate::Rectangle;
+use crate::geometry::Rect;
+
+// how pieces are laid out within a container
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Heuristic {
+    // rows of pieces, each row as tall as its tallest piece. quick and
+    // predictable, which suits sprite sheets
+    Shelf,
+    // every cut goes right across the remaining space, so a panel saw can
+    // cut the pieces out
+    Guillotine,
+    // keeps every maximal free rectangle, which usually packs tightest
+    MaxRects,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PackOptions {
+    pub heuristic: Heuristic,
+    // whether pieces may be turned 90 degrees
+    pub allow_rotation: bool,
+    // the most containers to use, or None for as many as it takes
+    pub max_bins: Option<usize>,
+}
+
+impl Default for PackOptions {
+    fn default() -> PackOptions {
+        PackOptions {
+            heuristic: Heuristic::MaxRects,
+            allow_rotation: true,
+            max_bins: None,
+        }
+    }
+}
+
+// where one of the pieces went
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Placement {
+    // index into the pieces that were packed
+    pub item: usize,
+    // which container, counting from 0
+    pub bin: usize,
+    // as placed, so rotated pieces have width and height swapped
+    pub rect: Rect,
+    pub rotated: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Packing {
+    pub container: Rectangle,
+    // in the same order as the pieces
+    pub placements: Vec<Placement>,
+    // pieces that didn't go in: too big for the container, or out of bins
+    pub unplaced: Vec<usize>,
+    pub bins: usize,
+}
+
+impl Packing {
+    pub fn in_bin(&self, bin: usize) -> impl Iterator<Item = &Placement> {
+        self.placements
+            .iter()
+            .filter(move |placement| placement.bin == bin)
+    }
+
+    // the share of all the containers used covered by pieces
+    pub fn utilization(&self) -> f64 {
+        let used: u64 = self.placements.iter().map(|p| p.rect.area()).sum();
+        ratio(used, self.container.area_u64() * self.bins as u64)
+    }
+
+    pub fn bin_utilization(&self, bin: usize) -> f64 {
+        let used: u64 = self.in_bin(bin).map(|p| p.rect.area()).sum();
+        ratio(used, self.container.area_u64())
+    }
+}
+
+fn ratio(used: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        used as f64 / total as f64
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PackError {
+    EmptyContainer,
+    // placements are Rects, so the container has to fit on their grid
+    ContainerTooLarge { width: u32, height: u32 },
+}
+
+impl fmt::Display for PackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PackError::EmptyContainer => write!(f, "the container has no room in it"),
+            PackError::ContainerTooLarge { width, height } => {
+                write!(f, "a {width}x{height} container is too large to pack")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PackError {}
+
+// packs the pieces into as many copies of the container as it takes, or as
+// max_bins allows. bigger pieces go first, since they're the hard ones to fit
+pub fn pack(
+    container: &Rectangle,
+    items: &[Rectangle],
+    options: &PackOptions,
+) -> Result<Packing, PackError> {
+    if container.width == 0 || container.height == 0 {
+        return Err(PackError::EmptyContainer);
+    }
+    if i32::try_from(container.width).is_err() || i32::try_from(container.height).is_err() {
+        return Err(PackError::ContainerTooLarge {
+            width: container.width,
+            height: container.height,
+        });
+    }
+    let bounds = Rect::new(0, 0, container.width, container.height);
+    match options.heuristic {
+        Heuristic::Shelf => Ok(pack_with(bounds, items, options, ShelfBin::new)),
+        Heuristic::Guillotine => Ok(pack_with(bounds, items, options, GuillotineBin::new)),
+        Heuristic::MaxRects => Ok(pack_with(bounds, items, options, MaxRectsBin::new)),
+    }
+}
+
+// a spot a piece could go in one bin. lower scores are better fits
+struct Spot {
+    rect: Rect,
+    rotated: bool,
+    score: (i64, i64),
+    // which shelf or free rectangle, for the bin that found it
+    slot: usize,
+}
+
+trait Bin {
+    fn find(&self, width: i64, height: i64, allow_rotation: bool) -> Option<Spot>;
+    fn place(&mut self, spot: &Spot);
+}
+
+fn pack_with<B: Bin>(
+    bounds: Rect,
+    items: &[Rectangle],
+    options: &PackOptions,
+    new_bin: impl Fn(Rect) -> B,
+) -> Packing {
+    let mut order: Vec<usize> = (0..items.len()).collect();
+    order.sort_by_key(|&item| {
+        let (width, height) = (items[item].width, items[item].height);
+        match options.heuristic {
+            // shelves fill up by height, laid flat if they can be
+            Heuristic::Shelf if options.allow_rotation => {
+                std::cmp::Reverse((width.min(height), width.max(height)))
+            }
+            Heuristic::Shelf => std::cmp::Reverse((height, width)),
+            _ => std::cmp::Reverse((width.max(height), width.min(height))),
+        }
+    });
+
+    let mut bins: Vec<B> = Vec::new();
+    let mut placements = Vec::new();
+    let mut unplaced = Vec::new();
+    for item in order {
+        let (width, height) = (i64::from(items[item].width), i64::from(items[item].height));
+        // the best fit in any open bin, with ties going to the earliest
+        let mut best: Option<(usize, Spot)> = None;
+        for (index, bin) in bins.iter().enumerate() {
+            if let Some(spot) = bin.find(width, height, options.allow_rotation)
+                && best
+                    .as_ref()
+                    .is_none_or(|(_, best)| spot.score < best.score)
+            {
+                best = Some((index, spot));
+            }
+        }
+        if best.is_none() && options.max_bins.is_none_or(|max| bins.len() < max) {
+            let bin = new_bin(bounds);
+            if let Some(spot) = bin.find(width, height, options.allow_rotation) {
+                bins.push(bin);
+                best = Some((bins.len() - 1, spot));
+            }
+        }
+        match best {
+            Some((index, spot)) => {
+                bins[index].place(&spot);
+                placements.push(Placement {
+                    item,
+                    bin: index,
+                    rect: spot.rect,
+                    rotated: spot.rotated,
+                });
+            }
+            None => unplaced.push(item),
+        }
+    }
+
+    placements.sort_by_key(|placement| placement.item);
+    unplaced.sort_unstable();
+    Packing {
+        container: bounds.size().expect("a container's area fits in a u32"),
+        placements,
+        unplaced,
+        bins: bins.len(),
+    }
+}
+
+// the ways a piece can be turned, as (width, height, rotated)
+fn orientations(width: i64, height: i64, allow_rotation: bool) -> Vec<(i64, i64, bool)> {
+    let mut orientations = vec![(width, height, false)];
+    if allow_rotation && width != height {
+        orientations.push((height, width, true));
+    }
+    orientations
+}
+
+// like fitting with FitMode::INCLUSIVE: can_hold wants strictly more room
+// each way, which would leave a gap around every piece
+fn fits(space: &Rect, width: i64, height: i64) -> bool {
+    width <= i64::from(space.width) && height <= i64::from(space.height)
+}
+
+fn placed(left: i64, top: i64, width: i64, height: i64) -> Rect {
+    Rect::from_edges(left, top, left + width, top + height).expect("a piece inside the container")
+}
+
+struct Shelf {
+    top: i64,
+    height: i64,
+    // where the next piece on the shelf goes
+    right: i64,
+}
+
+struct ShelfBin {
+    bounds: Rect,
+    shelves: Vec<Shelf>,
+}
+
+impl ShelfBin {
+    fn new(bounds: Rect) -> ShelfBin {
+        ShelfBin {
+            bounds,
+            shelves: Vec::new(),
+        }
+    }
+
+    fn next_top(&self) -> i64 {
+        self.shelves
+            .last()
+            .map_or(self.bounds.top(), |shelf| shelf.top + shelf.height)
+    }
+}
+
+impl Bin for ShelfBin {
+    // an existing shelf wasting the least height, or else a new shelf as
+    // short as the piece can be laid
+    fn find(&self, width: i64, height: i64, allow_rotation: bool) -> Option<Spot> {
+        let mut best: Option<Spot> = None;
+        let mut consider = |spot: Spot| {
+            if best.as_ref().is_none_or(|best| spot.score < best.score) {
+                best = Some(spot);
+            }
+        };
+        for (slot, shelf) in self.shelves.iter().enumerate() {
+            for (width, height, rotated) in orientations(width, height, allow_rotation) {
+                if height <= shelf.height && shelf.right + width <= self.bounds.right() {
+                    consider(Spot {
+                        rect: placed(shelf.right, shelf.top, width, height),
+                        rotated,
+                        score: (0, shelf.height - height),
+                        slot,
+                    });
+                }
+            }
+        }
+        let top = self.next_top();
+        for (width, height, rotated) in orientations(width, height, allow_rotation) {
+            if width <= i64::from(self.bounds.width) && top + height <= self.bounds.bottom() {
+                consider(Spot {
+                    rect: placed(self.bounds.left(), top, width, height),
+                    rotated,
+                    score: (1, height),
+                    slot: self.shelves.len(),
+                });
+            }
+        }
+        best
+    }
+
+    fn place(&mut self, spot: &Spot) {
+        if spot.slot == self.shelves.len() {
+            self.shelves.push(Shelf {
+                top: spot.rect.top(),
+                height: i64::from(spot.rect.height),
+                right: spot.rect.left(),
+            });
+        }
+        self.shelves[spot.slot].right = spot.rect.right();
+    }
+}
+
+// free space as rectangles that don't overlap. each placement cuts its free
+// rectangle in two along the shorter leftover
+struct GuillotineBin {
+    free: Vec<Rect>,
+}
+
+impl GuillotineBin {
+    fn new(bounds: Rect) -> GuillotineBin {
+        GuillotineBin { free: vec![bounds] }
+    }
+}
+
+impl Bin for GuillotineBin {
+    // the free rectangle with the least area left over
+    fn find(&self, width: i64, height: i64, allow_rotation: bool) -> Option<Spot> {
+        let mut best: Option<Spot> = None;
+        for (slot, space) in self.free.iter().enumerate() {
+            for (width, height, rotated) in orientations(width, height, allow_rotation) {
+                if !fits(space, width, height) {
+                    continue;
+                }
+                let left_over = space.area() as i64 - width * height;
+                let short_side =
+                    (i64::from(space.width) - width).min(i64::from(space.height) - height);
+                let score = (left_over, short_side);
+                if best.as_ref().is_none_or(|best| score < best.score) {
+                    best = Some(Spot {
+                        rect: placed(space.left(), space.top(), width, height),
+                        rotated,
+                        score,
+                        slot,
+                    });
+                }
+            }
+        }
+        best
+    }
+
+    fn place(&mut self, spot: &Spot) {
+        let space = self.free.swap_remove(spot.slot);
+        let piece = spot.rect;
+        let right_over = space.right() - piece.right();
+        let below = space.bottom() - piece.bottom();
+        // the cut that runs the length of the longer leftover keeps it whole
+        let (right, bottom) = if right_over < below {
+            (
+                Rect::from_edges(piece.right(), space.top(), space.right(), piece.bottom()),
+                Rect::from_edges(space.left(), piece.bottom(), space.right(), space.bottom()),
+            )
+        } else {
+            (
+                Rect::from_edges(piece.right(), space.top(), space.right(), space.bottom()),
+                Rect::from_edges(space.left(), piece.bottom(), piece.right(), space.bottom()),
+            )
+        };
+        self.free.extend(
+            [right, bottom]
+                .into_iter()
+                .flatten()
+                .filter(|rect| !rect.is_empty()),
+        );
+    }
+}
+
+// free space as every maximal empty rectangle, which can overlap each other
+struct MaxRectsBin {
+    free: Vec<Rect>,
+}
+
+impl MaxRectsBin {
+    fn new(bounds: Rect) -> MaxRectsBin {
+        MaxRectsBin { free: vec![bounds] }
+    }
+}
+
+impl Bin for MaxRectsBin {
+    // the free rectangle whose shorter leftover side is shortest
+    fn find(&self, width: i64, height: i64, allow_rotation: bool) -> Option<Spot> {
+        let mut best: Option<Spot> = None;
+        for (slot, space) in self.free.iter().enumerate() {
+            for (width, height, rotated) in orientations(width, height, allow_rotation) {
+                if !fits(space, width, height) {
+                    continue;
+                }
+                let across = i64::from(space.width) - width;
+                let down = i64::from(space.height) - height;
+                let score = (across.min(down), across.max(down));
+                if best.as_ref().is_none_or(|best| score < best.score) {
+                    best = Some(Spot {
+                        rect: placed(space.left(), space.top(), width, height),
+                        rotated,
+                        score,
+                        slot,
+                    });
+                }
+            }
+        }
+        best
+    }
+
+    fn place(&mut self, spot: &Spot) {
+        let piece = spot.rect;
+        let mut free = Vec::with_capacity(self.free.len() + 4);
+        for space in &self.free {
+            if !space.overlaps(&piece) {
+                free.push(*space);
+                continue;
+            }
+            // what's left of the space on each side of the piece
+            let sides = [
+                Rect::from_edges(space.left(), space.top(), piece.left(), space.bottom()),
+                Rect::from_edges(piece.right(), space.top(), space.right(), space.bottom()),
+                Rect::from_edges(space.left(), space.top(), space.right(), piece.top()),
+                Rect::from_edges(space.left(), piece.bottom(), space.right(), space.bottom()),
+            ];
+            free.extend(sides.into_iter().flatten().filter(|side| !side.is_empty()));
+        }
+        // drop any free rectangle inside another, keeping one of any equal pair
+        let mut index = 0;
+        while index < free.len() {
+            let inside = free.iter().enumerate().any(|(other, rect)| {
+                other != index
+                    && rect.contains(&free[index])
+                    && (rect != &free[index] || other < index)
+            });
+            if inside {
+                free.swap_remove(index);
+            } else {
+                index += 1;
+            }
+        }
+        self.free = free;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    const HEURISTICS: [Heuristic; 3] =
+        [Heuristic::Shelf, Heuristic::Guillotine, Heuristic::MaxRects];
+
+    fn rectangle(width: u32, height: u32) -> Rectangle {
+        Rectangle::new(width, height).unwrap()
+    }
+
+    fn options(heuristic: Heuristic, allow_rotation: bool) -> PackOptions {
+        PackOptions {
+            heuristic,
+            allow_rotation,
+            max_bins: None,
+        }
+    }
+
+    // every piece is placed once or left out, at its own size, inside the
+    // container and clear of the others in its bin
+    fn check(packing: &Packing, items: &[Rectangle]) {
+        let bounds = Rect::new(0, 0, packing.container.width, packing.container.height);
+        let mut seen: Vec<usize> = packing.placements.iter().map(|p| p.item).collect();
+        seen.extend(&packing.unplaced);
+        seen.sort_unstable();
+        assert_eq!(seen, (0..items.len()).collect::<Vec<_>>());
+        for (index, placement) in packing.placements.iter().enumerate() {
+            let item = &items[placement.item];
+            let (width, height) = if placement.rotated {
+                (item.height, item.width)
+            } else {
+                (item.width, item.height)
+            };
+            assert_eq!(
+                (placement.rect.width, placement.rect.height),
+                (width, height)
+            );
+            assert!(placement.bin < packing.bins);
+            assert!(bounds.contains(&placement.rect), "{placement:?} sticks out");
+            for other in &packing.placements[index + 1..] {
+                assert!(
+                    other.bin != placement.bin || !other.rect.overlaps(&placement.rect),
+                    "{placement:?} overlaps {other:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_exact_fit() {
+        // four quarters fill the container, which can_hold alone wouldn't allow
+        let items = [
+            rectangle(50, 50),
+            rectangle(50, 50),
+            rectangle(50, 50),
+            rectangle(50, 50),
+        ];
+        for heuristic in HEURISTICS {
+            let packing = pack(&rectangle(100, 100), &items, &options(heuristic, false)).unwrap();
+            check(&packing, &items);
+            assert_eq!(packing.bins, 1, "{heuristic:?}");
+            assert_eq!(packing.utilization(), 1.0);
+        }
+    }
+
+    #[test]
+    fn test_rotation() {
+        // only goes in turned on its side
+        let items = [rectangle(10, 100)];
+        for heuristic in HEURISTICS {
+            let packing = pack(&rectangle(100, 10), &items, &options(heuristic, true)).unwrap();
+            check(&packing, &items);
+            assert!(packing.placements[0].rotated);
+            assert_eq!(packing.placements[0].rect, Rect::new(0, 0, 100, 10));
+
+            let packing = pack(&rectangle(100, 10), &items, &options(heuristic, false)).unwrap();
+            assert_eq!(packing.unplaced, [0]);
+            assert_eq!(packing.bins, 0);
+            assert_eq!(packing.utilization(), 0.0);
+        }
+    }
+
+    #[test]
+    fn test_bins() {
+        let items = [
+            rectangle(60, 60),
+            rectangle(60, 60),
+            rectangle(30, 30),
+            rectangle(200, 10),
+        ];
+        for heuristic in HEURISTICS {
+            let packing = pack(&rectangle(100, 100), &items, &options(heuristic, true)).unwrap();
+            check(&packing, &items);
+            // the small square tucks in beside the first big one
+            assert_eq!(packing.bins, 2, "{heuristic:?}");
+            assert_eq!(packing.unplaced, [3]);
+            assert_eq!(packing.placements[2].bin, 0, "{heuristic:?}");
+            assert_eq!(packing.in_bin(0).count(), 2);
+            assert_eq!(packing.bin_utilization(1), 0.36);
+            assert_eq!(packing.utilization(), (3600.0 * 2.0 + 900.0) / 20_000.0);
+
+            let limited = PackOptions {
+                max_bins: Some(1),
+                ..options(heuristic, true)
+            };
+            let packing = pack(&rectangle(100, 100), &items, &limited).unwrap();
+            check(&packing, &items);
+            assert_eq!(packing.unplaced, [1, 3]);
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        let options = PackOptions::default();
+        assert_eq!(
+            pack(&rectangle(0, 10), &[], &options),
+            Err(PackError::EmptyContainer)
+        );
+        assert_eq!(
+            pack(&rectangle(1 << 31, 1), &[], &options)
+                .unwrap_err()
+                .to_string(),
+            "a 2147483648x1 container is too large to pack"
+        );
+    }
+
+    #[test]
+    fn test_guillotine_cuts() {
+        // every free rectangle after a cut runs the full length of the cut
+        let mut bin = GuillotineBin::new(Rect::new(0, 0, 100, 100));
+        let spot = bin.find(30, 80, false).unwrap();
+        bin.place(&spot);
+        let mut free = bin.free.clone();
+        free.sort_by_key(|rect| (rect.x, rect.y));
+        assert_eq!(free, [Rect::new(0, 80, 30, 20), Rect::new(30, 0, 70, 100)]);
+    }
+
+    fn items() -> impl Strategy<Value = Vec<Rectangle>> {
+        prop::collection::vec((1..60u32, 1..60u32), 0..40)
+            .prop_map(|sizes| sizes.into_iter().map(|(w, h)| rectangle(w, h)).collect())
+    }
+
+    proptest! {
+        #[test]
+        fn packings_are_valid(
+            items in items(),
+            width in 1..120u32,
+            height in 1..120u32,
+            allow_rotation in any::<bool>(),
+            max_bins in prop::option::of(1..4usize),
+        ) {
+            for heuristic in HEURISTICS {
+                let options = PackOptions { heuristic, allow_rotation, max_bins };
+                let packing = pack(&rectangle(width, height), &items, &options).unwrap();
+                check(&packing, &items);
+                prop_assert!(max_bins.is_none_or(|max| packing.bins <= max));
+                prop_assert!(packing.utilization() <= 1.0);
+                // with no cap, only pieces too big for an empty container are left
+                if max_bins.is_none() {
+                    for &item in &packing.unplaced {
+                        let fits = |w: u32, h: u32| w <= width && h <= height;
+                        let (w, h) = (items[item].width, items[item].height);
+                        prop_assert!(!(fits(w, h) || allow_rotation && fits(h, w)));
+                    }
+                }
+            }
+        }
+    }
+}
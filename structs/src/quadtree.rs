@@ -0,0 +1,470 @@
+use std::collections::HashMap;
+
+use crate::geometry::{Point, Rect};
+
+// a node splits once it holds more than this many rectangles
+const MAX_ENTRIES: usize = 8;
+// and stops splitting this deep, so piles of identical rectangles don't
+// split forever
+const MAX_DEPTH: usize = 16;
+
+// names a rectangle in the tree. ids go up in the order rectangles were
+// inserted and aren't reused
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EntryId(u64);
+
+// a spatial index over rectangles, each with a value. every rectangle lives in
+// the smallest node that wholly holds it, so ones that straddle a split stay
+// higher up. rectangles outside the tree's bounds are kept at the root, which
+// works but is no quicker than a scan, so make the bounds cover the layout
+pub struct QuadTree<T> {
+    root: Node,
+    entries: HashMap<EntryId, (Rect, T)>,
+    next_id: u64,
+}
+
+struct Node {
+    bounds: Rect,
+    entries: Vec<(EntryId, Rect)>,
+    children: Option<Box<[Node; 4]>>,
+}
+
+impl<T> QuadTree<T> {
+    pub fn new(bounds: Rect) -> QuadTree<T> {
+        QuadTree {
+            root: Node::new(bounds),
+            entries: HashMap::new(),
+            next_id: 0,
+        }
+    }
+
+    pub fn bounds(&self) -> Rect {
+        self.root.bounds
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn insert(&mut self, rect: Rect, value: T) -> EntryId {
+        let id = EntryId(self.next_id);
+        self.next_id += 1;
+        self.root.insert(id, rect, 0);
+        self.entries.insert(id, (rect, value));
+        id
+    }
+
+    pub fn remove(&mut self, id: EntryId) -> Option<(Rect, T)> {
+        let (rect, value) = self.entries.remove(&id)?;
+        self.root.remove(id, &rect);
+        Some((rect, value))
+    }
+
+    pub fn get(&self, id: EntryId) -> Option<(&Rect, &T)> {
+        self.entries.get(&id).map(|(rect, value)| (rect, value))
+    }
+
+    // the rectangles covering a point, oldest first
+    pub fn at_point(&self, point: Point) -> Vec<EntryId> {
+        let mut found = Vec::new();
+        self.root.at_point(point, &mut found);
+        found.sort_unstable();
+        found
+    }
+
+    // the rectangles sharing some area with a region, oldest first. like
+    // Rect::overlaps, touching edges don't count
+    pub fn overlapping(&self, region: &Rect) -> Vec<EntryId> {
+        let mut found = Vec::new();
+        self.root.overlapping(region, &mut found);
+        found.sort_unstable();
+        found
+    }
+
+    // the rectangle closest to a point, 0 away if it covers it, with ties
+    // going to the oldest. empty rectangles cover nowhere, so they're never
+    // nearest
+    pub fn nearest(&self, point: Point) -> Option<EntryId> {
+        let mut best = None;
+        self.root.nearest(point, &mut best);
+        best.map(|(_, id)| id)
+    }
+}
+
+// the squared distance from a point to the nearest grid cell a rectangle
+// covers. differences fit in an i64, so their squares fit in a u128
+pub fn distance_squared(rect: &Rect, point: Point) -> u128 {
+    let away = |low: i64, high: i64, at: i64| {
+        // the cells covered run from low to high - 1
+        (low - at).max(at - (high - 1)).max(0).unsigned_abs()
+    };
+    let dx = u128::from(away(rect.left(), rect.right(), i64::from(point.x)));
+    let dy = u128::from(away(rect.top(), rect.bottom(), i64::from(point.y)));
+    dx * dx + dy * dy
+}
+
+impl Node {
+    fn new(bounds: Rect) -> Node {
+        Node {
+            bounds,
+            entries: Vec::new(),
+            children: None,
+        }
+    }
+
+    fn child_for(&mut self, rect: &Rect) -> Option<&mut Node> {
+        self.children
+            .as_deref_mut()?
+            .iter_mut()
+            .find(|child| child.bounds.contains(rect))
+    }
+
+    fn insert(&mut self, id: EntryId, rect: Rect, depth: usize) {
+        if let Some(child) = self.child_for(&rect) {
+            return child.insert(id, rect, depth + 1);
+        }
+        self.entries.push((id, rect));
+        if self.children.is_none()
+            && self.entries.len() > MAX_ENTRIES
+            && depth < MAX_DEPTH
+            && self.bounds.width >= 2
+            && self.bounds.height >= 2
+        {
+            self.split(depth);
+        }
+    }
+
+    fn split(&mut self, depth: usize) {
+        let bounds = self.bounds;
+        let middle_x = bounds.left() + i64::from(bounds.width / 2);
+        let middle_y = bounds.top() + i64::from(bounds.height / 2);
+        let quarter = |left, top, right, bottom| {
+            Node::new(Rect::from_edges(left, top, right, bottom).expect("a quarter of a Rect"))
+        };
+        self.children = Some(Box::new([
+            quarter(bounds.left(), bounds.top(), middle_x, middle_y),
+            quarter(middle_x, bounds.top(), bounds.right(), middle_y),
+            quarter(bounds.left(), middle_y, middle_x, bounds.bottom()),
+            quarter(middle_x, middle_y, bounds.right(), bounds.bottom()),
+        ]));
+        for (id, rect) in std::mem::take(&mut self.entries) {
+            match self.child_for(&rect) {
+                Some(child) => child.insert(id, rect, depth + 1),
+                None => self.entries.push((id, rect)),
+            }
+        }
+    }
+
+    // the same path insert took, since the tree only splits and merges whole
+    // nodes. merges children back once they're leaves that fit in one node
+    fn remove(&mut self, id: EntryId, rect: &Rect) {
+        match self.child_for(rect) {
+            Some(child) => child.remove(id, rect),
+            None => {
+                if let Some(index) = self.entries.iter().position(|(entry, _)| *entry == id) {
+                    self.entries.swap_remove(index);
+                }
+            }
+        }
+        if let Some(children) = &self.children {
+            let leaves = children.iter().all(|child| child.children.is_none());
+            let total = self.entries.len()
+                + children
+                    .iter()
+                    .map(|child| child.entries.len())
+                    .sum::<usize>();
+            if leaves && total <= MAX_ENTRIES {
+                let children = self.children.take().expect("children to merge");
+                for child in *children {
+                    self.entries.extend(child.entries);
+                }
+            }
+        }
+    }
+
+    fn children(&self) -> &[Node] {
+        self.children.as_deref().map_or(&[], |children| children)
+    }
+
+    fn at_point(&self, point: Point, found: &mut Vec<EntryId>) {
+        found.extend(
+            self.entries
+                .iter()
+                .filter(|(_, rect)| rect.contains_point(point))
+                .map(|(id, _)| *id),
+        );
+        for child in self.children() {
+            if child.bounds.contains_point(point) {
+                child.at_point(point, found);
+            }
+        }
+    }
+
+    fn overlapping(&self, region: &Rect, found: &mut Vec<EntryId>) {
+        found.extend(
+            self.entries
+                .iter()
+                .filter(|(_, rect)| rect.overlaps(region))
+                .map(|(id, _)| *id),
+        );
+        for child in self.children() {
+            if child.bounds.overlaps(region) {
+                child.overlapping(region, found);
+            }
+        }
+    }
+
+    // nothing inside a node is closer than the node itself, so children
+    // further away than the best so far can be skipped. nearer ones go first
+    // to find a good best early
+    fn nearest(&self, point: Point, best: &mut Option<(u128, EntryId)>) {
+        for (id, rect) in &self.entries {
+            if rect.is_empty() {
+                continue;
+            }
+            let candidate = (distance_squared(rect, point), *id);
+            if best.is_none_or(|best| candidate < best) {
+                *best = Some(candidate);
+            }
+        }
+        let mut children: Vec<(u128, &Node)> = self
+            .children()
+            .iter()
+            .map(|child| (distance_squared(&child.bounds, point), child))
+            .collect();
+        children.sort_by_key(|(distance, _)| *distance);
+        for (distance, child) in children {
+            // equal distances could still win a tie on age
+            if best.is_none_or(|(best, _)| distance <= best) {
+                child.nearest(point, best);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::*;
+    use proptest::prelude::*;
+
+    // what the tree should agree with, the slow way
+    fn scan<T>(tree: &QuadTree<T>, keep: impl Fn(&Rect) -> bool) -> Vec<EntryId> {
+        let mut found: Vec<EntryId> = tree
+            .entries
+            .iter()
+            .filter(|(_, (rect, _))| keep(rect))
+            .map(|(id, _)| *id)
+            .collect();
+        found.sort_unstable();
+        found
+    }
+
+    fn scan_nearest<T>(tree: &QuadTree<T>, point: Point) -> Option<EntryId> {
+        tree.entries
+            .iter()
+            .filter(|(_, (rect, _))| !rect.is_empty())
+            .map(|(id, (rect, _))| (distance_squared(rect, point), *id))
+            .min()
+            .map(|(_, id)| id)
+    }
+
+    #[test]
+    fn test_queries() {
+        let mut tree = QuadTree::new(Rect::new(0, 0, 100, 100));
+        let a = tree.insert(Rect::new(0, 0, 10, 10), "a");
+        let b = tree.insert(Rect::new(5, 5, 10, 10), "b");
+        let c = tree.insert(Rect::new(80, 80, 5, 5), "c");
+        // outside the bounds still works
+        let d = tree.insert(Rect::new(-50, 200, 10, 10), "d");
+        assert_eq!(tree.len(), 4);
+
+        assert_eq!(tree.at_point(Point::new(7, 7)), [a, b]);
+        assert_eq!(tree.at_point(Point::new(10, 10)), [b]);
+        assert_eq!(tree.at_point(Point::new(-45, 205)), [d]);
+        assert_eq!(tree.overlapping(&Rect::new(9, 9, 75, 75)), [a, b, c]);
+        // touching isn't overlapping
+        assert_eq!(tree.overlapping(&Rect::new(85, 85, 5, 5)), []);
+        assert_eq!(tree.nearest(Point::new(50, 50)), Some(c));
+        assert_eq!(tree.nearest(Point::new(7, 7)), Some(a));
+        assert_eq!(tree.nearest(Point::new(-100, 300)), Some(d));
+
+        assert_eq!(tree.remove(c), Some((Rect::new(80, 80, 5, 5), "c")));
+        assert_eq!(tree.remove(c), None);
+        assert_eq!(tree.get(c), None);
+        assert_eq!(tree.get(a), Some((&Rect::new(0, 0, 10, 10), &"a")));
+        assert_eq!(tree.nearest(Point::new(50, 50)), Some(b));
+        assert_eq!(
+            distance_squared(&Rect::new(0, 0, 10, 10), Point::new(12, 13)),
+            25
+        );
+    }
+
+    #[test]
+    fn test_split_and_merge() {
+        let mut tree = QuadTree::new(Rect::new(0, 0, 64, 64));
+        let ids: Vec<EntryId> = (0..40)
+            .map(|i| tree.insert(Rect::new(i, i, 1, 1), i))
+            .collect();
+        assert!(tree.root.children.is_some());
+        // one across the middle can't go down a level
+        let middle = tree.insert(Rect::new(30, 30, 4, 4), 99);
+        assert!(tree.root.entries.iter().any(|(id, _)| *id == middle));
+
+        for id in ids {
+            tree.remove(id);
+        }
+        assert!(tree.root.children.is_none());
+        assert_eq!(tree.at_point(Point::new(31, 31)), [middle]);
+    }
+
+    #[test]
+    fn test_identical_rectangles() {
+        // they never spread out, so splitting has to give up
+        let mut tree = QuadTree::new(Rect::new(0, 0, 1 << 20, 1 << 20));
+        for i in 0..100 {
+            tree.insert(Rect::new(7, 7, 1, 1), i);
+        }
+        assert_eq!(tree.at_point(Point::new(7, 7)).len(), 100);
+    }
+
+    // a small xorshift, so the benchmark is the same every run
+    fn random(state: &mut u64) -> u64 {
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        *state
+    }
+
+    // cargo test --release bench -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn bench_against_a_scan() {
+        const SIZE: u64 = 100_000;
+        const QUERIES: usize = 1_000;
+        let mut state = 0x2545_f491_4f6c_dd1d;
+        let mut coordinate = |below: u64| (random(&mut state) % below) as i32;
+
+        for count in [1_000, 10_000, 100_000] {
+            // the scan gets a plain list in id order, the fastest it could have
+            let mut tree = QuadTree::new(Rect::new(0, 0, SIZE as u32, SIZE as u32));
+            let mut list = Vec::new();
+            for i in 0..count {
+                let (x, y) = (coordinate(SIZE), coordinate(SIZE));
+                let (width, height) = (coordinate(500) as u32 + 1, coordinate(500) as u32 + 1);
+                let rect = Rect::new(x, y, width, height);
+                list.push((tree.insert(rect, i), rect));
+            }
+            let scan = |keep: &dyn Fn(&Rect) -> bool| -> Vec<EntryId> {
+                list.iter()
+                    .filter(|(_, rect)| keep(rect))
+                    .map(|(id, _)| *id)
+                    .collect()
+            };
+            let points: Vec<Point> = (0..QUERIES)
+                .map(|_| Point::new(coordinate(SIZE), coordinate(SIZE)))
+                .collect();
+            let regions: Vec<Rect> = points
+                .iter()
+                .map(|point| Rect::new(point.x, point.y, 2_000, 2_000))
+                .collect();
+
+            let time = |run: &dyn Fn(usize) -> Vec<EntryId>| {
+                let start = Instant::now();
+                let found: Vec<Vec<EntryId>> = (0..QUERIES).map(run).collect();
+                (start.elapsed(), found)
+            };
+            let report = |what: &str,
+                          indexed: (Duration, Vec<Vec<EntryId>>),
+                          scanned: (Duration, Vec<Vec<EntryId>>)| {
+                assert_eq!(indexed.1, scanned.1, "{what} disagrees with a scan");
+                println!(
+                    "{count:>7} rectangles, {what:<8} quadtree {:>10.2?}  scan {:>10.2?}  {:>6.1}x",
+                    indexed.0,
+                    scanned.0,
+                    scanned.0.as_secs_f64() / indexed.0.as_secs_f64()
+                );
+            };
+            report(
+                "point",
+                time(&|i| tree.at_point(points[i])),
+                time(&|i| scan(&|rect| rect.contains_point(points[i]))),
+            );
+            report(
+                "region",
+                time(&|i| tree.overlapping(&regions[i])),
+                time(&|i| scan(&|rect| rect.overlaps(&regions[i]))),
+            );
+            report(
+                "nearest",
+                time(&|i| tree.nearest(points[i]).into_iter().collect()),
+                time(&|i| {
+                    list.iter()
+                        .map(|(id, rect)| (distance_squared(rect, points[i]), *id))
+                        .min()
+                        .map(|(_, id)| id)
+                        .into_iter()
+                        .collect()
+                }),
+            );
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    enum Step {
+        Insert(Rect),
+        // an index into what's in the tree
+        Remove(usize),
+        Point(Point),
+        Region(Rect),
+    }
+
+    fn rect() -> impl Strategy<Value = Rect> {
+        prop_oneof![
+            (-20..80i32, -20..80i32, 0..30u32, 0..30u32),
+            // anywhere, to hit the root's out of bounds list
+            (any::<i32>(), any::<i32>(), any::<u32>(), any::<u32>()),
+        ]
+        .prop_map(|(x, y, width, height)| Rect::new(x, y, width, height))
+    }
+
+    fn step() -> impl Strategy<Value = Step> {
+        prop_oneof![
+            4 => rect().prop_map(Step::Insert),
+            2 => any::<usize>().prop_map(Step::Remove),
+            1 => (-30..90i32, -30..90i32).prop_map(|(x, y)| Step::Point(Point::new(x, y))),
+            1 => rect().prop_map(Step::Region),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn agrees_with_a_scan(steps in prop::collection::vec(step(), 0..200)) {
+            let mut tree = QuadTree::new(Rect::new(0, 0, 64, 64));
+            let mut ids = Vec::new();
+            for step in steps {
+                match step {
+                    Step::Insert(rect) => ids.push(tree.insert(rect, ())),
+                    Step::Remove(index) if !ids.is_empty() => {
+                        let id = ids.swap_remove(index % ids.len());
+                        prop_assert!(tree.remove(id).is_some());
+                    }
+                    Step::Remove(_) => {}
+                    Step::Point(point) => {
+                        prop_assert_eq!(tree.at_point(point), scan(&tree, |rect| rect.contains_point(point)));
+                        prop_assert_eq!(tree.nearest(point), scan_nearest(&tree, point));
+                    }
+                    Step::Region(region) => {
+                        prop_assert_eq!(tree.overlapping(&region), scan(&tree, |rect| rect.overlaps(&region)));
+                    }
+                }
+                prop_assert_eq!(tree.len(), ids.len());
+            }
+        }
+    }
+}
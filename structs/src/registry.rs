@@ -0,0 +1,386 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::clock::Timestamp;
+use crate::user::{Email, User, UserError, Username};
+
+// bumped whenever the saved layout changes; older files are still read
+pub const SCHEMA_VERSION: u64 = 1;
+
+#[derive(Debug)]
+pub enum RegistryError {
+    DuplicateUsername(String),
+    DuplicateEmail(String),
+    UnknownUser(String),
+    Inactive(String),
+    Io(io::Error),
+    Json(serde_json::Error),
+    // a file from a newer version of this program
+    UnsupportedVersion(u64),
+    // a file that parses but doesn't hold what we wrote
+    Corrupt(String),
+    Invalid(UserError),
+}
+
+impl fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegistryError::DuplicateUsername(name) => {
+                write!(f, "the username '{name}' is taken")
+            }
+            RegistryError::DuplicateEmail(email) => {
+                write!(f, "there is already a user with the email {email}")
+            }
+            RegistryError::UnknownUser(name) => write!(f, "there is no user called '{name}'"),
+            RegistryError::Inactive(name) => write!(f, "the user '{name}' is deactivated"),
+            RegistryError::Io(error) => error.fmt(f),
+            RegistryError::Json(error) => write!(f, "bad JSON: {error}"),
+            RegistryError::UnsupportedVersion(version) => write!(
+                f,
+                "the file is schema version {version}, this program reads up to {SCHEMA_VERSION}"
+            ),
+            RegistryError::Corrupt(reason) => write!(f, "the file is damaged: {reason}"),
+            RegistryError::Invalid(error) => write!(f, "a saved user is invalid: {error}"),
+        }
+    }
+}
+
+impl Error for RegistryError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            RegistryError::Io(error) => Some(error),
+            RegistryError::Json(error) => Some(error),
+            RegistryError::Invalid(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for RegistryError {
+    fn from(error: io::Error) -> RegistryError {
+        RegistryError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for RegistryError {
+    fn from(error: serde_json::Error) -> RegistryError {
+        RegistryError::Json(error)
+    }
+}
+
+impl From<UserError> for RegistryError {
+    fn from(error: UserError) -> RegistryError {
+        RegistryError::Invalid(error)
+    }
+}
+
+// every user, with usernames and emails unique ignoring case
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UserRegistry {
+    users: Vec<User>,
+}
+
+impl UserRegistry {
+    pub fn new() -> UserRegistry {
+        UserRegistry { users: Vec::new() }
+    }
+
+    pub fn register(&mut self, user: User) -> Result<&User, RegistryError> {
+        if self.get(user.username.as_str()).is_some() {
+            return Err(RegistryError::DuplicateUsername(user.username.to_string()));
+        }
+        if self.users.iter().any(|other| other.email == user.email) {
+            return Err(RegistryError::DuplicateEmail(user.email.to_string()));
+        }
+        self.users.push(user);
+        Ok(self.users.last().unwrap())
+    }
+
+    pub fn users(&self) -> &[User] {
+        &self.users
+    }
+
+    pub fn len(&self) -> usize {
+        self.users.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.users.is_empty()
+    }
+
+    pub fn get(&self, username: &str) -> Option<&User> {
+        self.users
+            .iter()
+            .find(|user| user.username.as_str().eq_ignore_ascii_case(username))
+    }
+
+    // ignoring case, the same as comparing Emails
+    pub fn find_by_email(&self, email: &str) -> Option<&User> {
+        self.users
+            .iter()
+            .find(|user| user.email.as_str().eq_ignore_ascii_case(email))
+    }
+
+    fn get_mut(&mut self, username: &str) -> Result<&mut User, RegistryError> {
+        self.users
+            .iter_mut()
+            .find(|user| user.username.as_str().eq_ignore_ascii_case(username))
+            .ok_or(RegistryError::UnknownUser(String::from(username)))
+    }
+
+    // counts a sign-in and when it was, refused for a deactivated user
+    pub fn sign_in(&mut self, username: &str, at: Timestamp) -> Result<&User, RegistryError> {
+        let user = self.get_mut(username)?;
+        if !user.active {
+            return Err(RegistryError::Inactive(user.username.to_string()));
+        }
+        user.sign_in_count += 1;
+        user.last_seen = Some(at);
+        Ok(user)
+    }
+
+    // deactivating an inactive user, or reactivating an active one, is fine
+    pub fn deactivate(&mut self, username: &str) -> Result<&User, RegistryError> {
+        let user = self.get_mut(username)?;
+        user.active = false;
+        Ok(user)
+    }
+
+    pub fn reactivate(&mut self, username: &str) -> Result<&User, RegistryError> {
+        let user = self.get_mut(username)?;
+        user.active = true;
+        Ok(user)
+    }
+
+    pub fn to_json(&self) -> String {
+        to_pretty_json(&self.saved())
+    }
+
+    // what to_json writes, for files that hold more alongside the users
+    pub(crate) fn saved(&self) -> SavedRegistry {
+        SavedRegistry {
+            schema_version: SCHEMA_VERSION,
+            users: self
+                .users
+                .iter()
+                .map(|user| SavedUser {
+                    username: user.username.to_string(),
+                    email: user.email.to_string(),
+                    active: user.active,
+                    sign_in_count: user.sign_in_count,
+                    last_seen: user.last_seen.map(Timestamp::secs),
+                })
+                .collect(),
+        }
+    }
+
+    // reads what to_json wrote, checking every user again on the way in. the
+    // version is looked at before anything else, since a newer file may be
+    // laid out differently. anything else in the file is left for whoever
+    // wrote it, like the credentials Accounts keeps
+    pub fn from_json(text: &str) -> Result<UserRegistry, RegistryError> {
+        let root: Value = serde_json::from_str(text)?;
+        let version = root
+            .get("schema_version")
+            .and_then(Value::as_u64)
+            .ok_or(corrupt("no schema_version"))?;
+        if version > SCHEMA_VERSION {
+            return Err(RegistryError::UnsupportedVersion(version));
+        }
+        let saved: SavedRegistry = serde_json::from_value(root)?;
+        let mut registry = UserRegistry::new();
+        for user in saved.users {
+            registry.register(User {
+                username: Username::parse(&user.username).map_err(UserError::from)?,
+                email: Email::parse(&user.email).map_err(UserError::from)?,
+                active: user.active,
+                sign_in_count: user.sign_in_count,
+                last_seen: user.last_seen.map(Timestamp::from_secs),
+            })?;
+        }
+        Ok(registry)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<UserRegistry, RegistryError> {
+        UserRegistry::from_json(&fs::read_to_string(path)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        save_replacing(path.as_ref(), &self.to_json())
+    }
+}
+
+// two-space indents and a newline at the end
+pub(crate) fn to_pretty_json(value: &impl Serialize) -> String {
+    let mut text =
+        serde_json::to_string_pretty(value).expect("strings and numbers always serialize");
+    text.push('\n');
+    text
+}
+
+// writes a temporary file next to the real one and renames it over, so
+// a crash part way through leaves the old file whole
+pub(crate) fn save_replacing(path: &Path, text: &str) -> io::Result<()> {
+    let mut temporary = path.as_os_str().to_owned();
+    temporary.push(".tmp");
+    fs::write(&temporary, text)?;
+    fs::rename(&temporary, path)
+}
+
+// the file as written, before the users in it are checked
+#[derive(Serialize, Deserialize)]
+pub(crate) struct SavedRegistry {
+    schema_version: u64,
+    users: Vec<SavedUser>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedUser {
+    username: String,
+    email: String,
+    active: bool,
+    sign_in_count: u64,
+    last_seen: Option<u64>,
+}
+
+pub(crate) fn corrupt(reason: &str) -> RegistryError {
+    RegistryError::Corrupt(String::from(reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::process;
+
+    fn registry() -> UserRegistry {
+        let mut registry = UserRegistry::new();
+        registry
+            .register(User::new("someone@example.com", "someusername123").unwrap())
+            .unwrap();
+        registry
+            .register(User::new("another@example.com", "another").unwrap())
+            .unwrap();
+        registry
+    }
+
+    #[test]
+    fn test_unique_usernames_and_emails() {
+        let mut registry = registry();
+        let error = registry
+            .register(User::new("third@example.com", "SomeUsername123").unwrap())
+            .unwrap_err();
+        assert_eq!(error.to_string(), "the username 'SomeUsername123' is taken");
+        let error = registry
+            .register(User::new("Another@EXAMPLE.com", "third").unwrap())
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "there is already a user with the email Another@example.com"
+        );
+        assert_eq!(registry.len(), 2);
+        assert!(registry.find_by_email("another@example.com").is_some());
+    }
+
+    #[test]
+    fn test_sign_ins_and_deactivation() {
+        let mut registry = registry();
+        let at = Timestamp::from_secs(1_700_000_000);
+        let user = registry.sign_in("someusername123", at).unwrap();
+        assert_eq!((user.sign_in_count, user.last_seen), (2, Some(at)));
+
+        registry.deactivate("SOMEUSERNAME123").unwrap();
+        let error = registry.sign_in("someusername123", at.plus_secs(60));
+        assert!(matches!(error, Err(RegistryError::Inactive(_))));
+        assert_eq!(registry.get("someusername123").unwrap().sign_in_count, 2);
+
+        registry.reactivate("someusername123").unwrap();
+        let user = registry
+            .sign_in("someusername123", at.plus_secs(60))
+            .unwrap();
+        assert_eq!(user.sign_in_count, 3);
+        assert!(matches!(
+            registry.sign_in("nobody", at),
+            Err(RegistryError::UnknownUser(_))
+        ));
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let mut registry = registry();
+        registry
+            .sign_in("another", Timestamp::from_secs(1_700_000_000))
+            .unwrap();
+        registry.deactivate("someusername123").unwrap();
+        let json = registry.to_json();
+        assert_eq!(
+            json,
+            r#"{
+  "schema_version": 1,
+  "users": [
+    {
+      "username": "someusername123",
+      "email": "someone@example.com",
+      "active": false,
+      "sign_in_count": 1,
+      "last_seen": null
+    },
+    {
+      "username": "another",
+      "email": "another@example.com",
+      "active": true,
+      "sign_in_count": 2,
+      "last_seen": 1700000000
+    }
+  ]
+}
+"#
+        );
+        assert_eq!(UserRegistry::from_json(&json).unwrap(), registry);
+
+        let path = env::temp_dir().join(format!("struts-registry-{}.json", process::id()));
+        registry.save(&path).unwrap();
+        let loaded = UserRegistry::load(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.unwrap(), registry);
+    }
+
+    #[test]
+    fn test_load_errors() {
+        let error = |text| UserRegistry::from_json(text).unwrap_err().to_string();
+        assert_eq!(
+            error(r#"{"schema_version": 2, "users": []}"#),
+            "the file is schema version 2, this program reads up to 1"
+        );
+        assert_eq!(
+            error(r#"{"users": []}"#),
+            "the file is damaged: no schema_version"
+        );
+        assert_eq!(
+            error(r#"{"schema_version": 1, "users": [{"username": "root"}]}"#),
+            "bad JSON: missing field `email`"
+        );
+        assert_eq!(
+            error(
+                r#"{"schema_version": 1, "users": [{"username": "root", "email": "a@b.co",
+                    "active": true, "sign_in_count": 0}]}"#
+            ),
+            "a saved user is invalid: the username 'root' is reserved"
+        );
+        assert_eq!(
+            error("{"),
+            "bad JSON: EOF while parsing an object at line 1 column 1"
+        );
+        // nesting deep enough to overflow a naive recursive parser is refused
+        assert_eq!(
+            error(&"[".repeat(100_000)),
+            "bad JSON: recursion limit exceeded at line 1 column 128"
+        );
+    }
+}
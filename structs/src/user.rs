@@ -0,0 +1,395 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::clock::Timestamp;
+
+// names nobody can sign up as, compared ignoring case
+pub const RESERVED_USERNAMES: [&str; 12] = [
+    "admin",
+    "administrator",
+    "anonymous",
+    "api",
+    "help",
+    "moderator",
+    "null",
+    "root",
+    "support",
+    "system",
+    "webmaster",
+    "www",
+];
+
+pub const USERNAME_MIN: usize = 3;
+pub const USERNAME_MAX: usize = 32;
+
+// the limits from RFC 5321 for the whole address and the part before the @
+pub const EMAIL_MAX: usize = 254;
+pub const LOCAL_PART_MAX: usize = 64;
+const DOMAIN_LABEL_MAX: usize = 63;
+
+// characters allowed before the @ besides letters and digits
+const LOCAL_PART_SYMBOLS: &str = "!#$%&'*+/=?^_`{|}~.-";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EmailError {
+    Empty,
+    TooLong(usize),
+    MissingAt,
+    EmptyLocalPart,
+    LocalPartTooLong(usize),
+    BadLocalChar(char),
+    // a dot at either end of the local part, or two in a row
+    BadDots,
+    BadDomain(String),
+}
+
+impl fmt::Display for EmailError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EmailError::Empty => write!(f, "the email address is empty"),
+            EmailError::TooLong(len) => write!(
+                f,
+                "the email address is {len} characters, the most is {EMAIL_MAX}"
+            ),
+            EmailError::MissingAt => write!(f, "the email address needs exactly one @"),
+            EmailError::EmptyLocalPart => write!(f, "there is nothing before the @"),
+            EmailError::LocalPartTooLong(len) => write!(
+                f,
+                "the part before the @ is {len} characters, the most is {LOCAL_PART_MAX}"
+            ),
+            EmailError::BadLocalChar(c) => {
+                write!(f, "'{c}' is not allowed before the @")
+            }
+            EmailError::BadDots => write!(
+                f,
+                "the part before the @ can't start or end with a dot or have two in a row"
+            ),
+            EmailError::BadDomain(domain) => {
+                write!(f, "'{domain}' is not a valid domain")
+            }
+        }
+    }
+}
+
+impl Error for EmailError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UsernameError {
+    TooShort(usize),
+    TooLong(usize),
+    BadChar(char),
+    // must start with a letter
+    BadStart,
+    Reserved(String),
+}
+
+impl fmt::Display for UsernameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UsernameError::TooShort(len) => write!(
+                f,
+                "the username is {len} characters, it needs at least {USERNAME_MIN}"
+            ),
+            UsernameError::TooLong(len) => write!(
+                f,
+                "the username is {len} characters, the most is {USERNAME_MAX}"
+            ),
+            UsernameError::BadChar(c) => write!(
+                f,
+                "'{c}' is not allowed in a username, only letters, digits, _ and -"
+            ),
+            UsernameError::BadStart => write!(f, "the username must start with a letter"),
+            UsernameError::Reserved(name) => write!(f, "the username '{name}' is reserved"),
+        }
+    }
+}
+
+impl Error for UsernameError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UserError {
+    Email(EmailError),
+    Username(UsernameError),
+}
+
+impl fmt::Display for UserError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UserError::Email(error) => error.fmt(f),
+            UserError::Username(error) => error.fmt(f),
+        }
+    }
+}
+
+impl Error for UserError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            UserError::Email(error) => Some(error),
+            UserError::Username(error) => Some(error),
+        }
+    }
+}
+
+impl From<EmailError> for UserError {
+    fn from(error: EmailError) -> UserError {
+        UserError::Email(error)
+    }
+}
+
+impl From<UsernameError> for UserError {
+    fn from(error: UsernameError) -> UserError {
+        UserError::Username(error)
+    }
+}
+
+// an email address that has passed Email::parse. the domain is kept in
+// lowercase; the part before the @ is kept as typed, so mail goes where it
+// was typed. two emails that differ only in case are still the same address,
+// both here and in the registry, since that's how mail servers treat them
+#[derive(Debug, Clone)]
+pub struct Email(String);
+
+impl PartialEq for Email {
+    fn eq(&self, other: &Email) -> bool {
+        self.0.eq_ignore_ascii_case(&other.0)
+    }
+}
+
+impl Eq for Email {}
+
+impl std::hash::Hash for Email {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.0.to_ascii_lowercase().hash(state);
+    }
+}
+
+impl Email {
+    pub fn parse(text: &str) -> Result<Email, EmailError> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Err(EmailError::Empty);
+        }
+        let len = text.chars().count();
+        if len > EMAIL_MAX {
+            return Err(EmailError::TooLong(len));
+        }
+        let Some((local, domain)) = text.split_once('@') else {
+            return Err(EmailError::MissingAt);
+        };
+        if domain.contains('@') {
+            return Err(EmailError::MissingAt);
+        }
+        check_local_part(local)?;
+        let domain = domain.to_ascii_lowercase();
+        if !is_domain(&domain) {
+            return Err(EmailError::BadDomain(domain));
+        }
+        Ok(Email(format!("{local}@{domain}")))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn domain(&self) -> &str {
+        self.0.rsplit_once('@').map_or("", |(_, domain)| domain)
+    }
+}
+
+impl fmt::Display for Email {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(&self.0)
+    }
+}
+
+fn check_local_part(local: &str) -> Result<(), EmailError> {
+    if local.is_empty() {
+        return Err(EmailError::EmptyLocalPart);
+    }
+    let len = local.chars().count();
+    if len > LOCAL_PART_MAX {
+        return Err(EmailError::LocalPartTooLong(len));
+    }
+    if let Some(c) = local
+        .chars()
+        .find(|c| !c.is_ascii_alphanumeric() && !LOCAL_PART_SYMBOLS.contains(*c))
+    {
+        return Err(EmailError::BadLocalChar(c));
+    }
+    if local.starts_with('.') || local.ends_with('.') || local.contains("..") {
+        return Err(EmailError::BadDots);
+    }
+    Ok(())
+}
+
+// at least two labels of letters, digits and inner hyphens, ending in an
+// alphabetic top level domain
+fn is_domain(domain: &str) -> bool {
+    let labels: Vec<&str> = domain.split('.').collect();
+    let label_ok = |label: &&str| {
+        (1..=DOMAIN_LABEL_MAX).contains(&label.len())
+            && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+    };
+    labels.len() >= 2
+        && labels.iter().all(label_ok)
+        && labels
+            .last()
+            .is_some_and(|tld| tld.len() >= 2 && tld.chars().all(|c| c.is_ascii_alphabetic()))
+}
+
+// a username that has passed Username::parse: 3 to 32 ascii letters,
+// digits, underscores and hyphens, starting with a letter, and not reserved
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Username(String);
+
+impl Username {
+    pub fn parse(text: &str) -> Result<Username, UsernameError> {
+        let text = text.trim();
+        let len = text.chars().count();
+        if len < USERNAME_MIN {
+            return Err(UsernameError::TooShort(len));
+        }
+        if len > USERNAME_MAX {
+            return Err(UsernameError::TooLong(len));
+        }
+        if let Some(c) = text
+            .chars()
+            .find(|c| !c.is_ascii_alphanumeric() && *c != '_' && *c != '-')
+        {
+            return Err(UsernameError::BadChar(c));
+        }
+        if !text.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            return Err(UsernameError::BadStart);
+        }
+        if RESERVED_USERNAMES
+            .iter()
+            .any(|reserved| reserved.eq_ignore_ascii_case(text))
+        {
+            return Err(UsernameError::Reserved(String::from(text)));
+        }
+        Ok(Username(String::from(text)))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Username {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(&self.0)
+    }
+}
+
+// the fields stay public so struct update syntax still works, but only
+// validated emails and usernames fit in them
+//Note that the entire instance must be mutable; Rust doesn’t allow us to mark only certain fields as mutable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct User {
+    pub active: bool,
+    pub username: Username,
+    pub email: Email,
+    pub sign_in_count: u64,
+    // the last sign-in the registry saw
+    pub last_seen: Option<Timestamp>,
+}
+
+impl User {
+    // checks both, reporting the email first
+    pub fn new(email: &str, username: &str) -> Result<User, UserError> {
+        Ok(build_user(Email::parse(email)?, Username::parse(username)?))
+    }
+}
+
+pub fn build_user(email: Email, username: Username) -> User {
+    User {
+        active: true,
+        username, // because of the field init shorthand, we can use just `username` instead of `username: username`
+        email,    // same for email
+        sign_in_count: 1,
+        last_seen: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_emails() {
+        let email = Email::parse(" Some.One+tag@Example.COM ").unwrap();
+        assert_eq!(email.as_str(), "Some.One+tag@example.com");
+        assert_eq!(email.domain(), "example.com");
+        assert!(Email::parse("a@mail.example-site.org").is_ok());
+        // kept as typed, but the same address whatever the case
+        assert_eq!(email, Email::parse("some.one+TAG@example.com").unwrap());
+
+        let error = |text| Email::parse(text).unwrap_err();
+        assert_eq!(error(""), EmailError::Empty);
+        assert_eq!(error("someone.example.com"), EmailError::MissingAt);
+        assert_eq!(error("a@b@example.com"), EmailError::MissingAt);
+        assert_eq!(error("@example.com"), EmailError::EmptyLocalPart);
+        assert_eq!(error("some one@example.com"), EmailError::BadLocalChar(' '));
+        assert_eq!(error(".someone@example.com"), EmailError::BadDots);
+        assert_eq!(error("some..one@example.com"), EmailError::BadDots);
+        assert_eq!(
+            error("someone@localhost"),
+            EmailError::BadDomain(String::from("localhost"))
+        );
+        assert_eq!(
+            error("someone@-example.com"),
+            EmailError::BadDomain(String::from("-example.com"))
+        );
+        assert_eq!(
+            error("someone@example.c0m"),
+            EmailError::BadDomain(String::from("example.c0m"))
+        );
+        let long = format!("{}@example.com", "a".repeat(65));
+        assert_eq!(error(&long), EmailError::LocalPartTooLong(65));
+        let longer = format!("a@{}.com", "b".repeat(300));
+        assert_eq!(error(&longer), EmailError::TooLong(306));
+    }
+
+    #[test]
+    fn test_usernames() {
+        assert_eq!(
+            Username::parse("someusername123").unwrap().as_str(),
+            "someusername123"
+        );
+        assert!(Username::parse("Jo_-9").is_ok());
+
+        let error = |text| Username::parse(text).unwrap_err();
+        assert_eq!(error("jo"), UsernameError::TooShort(2));
+        let long = "a".repeat(33);
+        assert_eq!(error(&long), UsernameError::TooLong(33));
+        assert_eq!(error("jo.smith"), UsernameError::BadChar('.'));
+        assert_eq!(error("9lives"), UsernameError::BadStart);
+        assert_eq!(error("_jo"), UsernameError::BadStart);
+        assert_eq!(
+            error("Admin"),
+            UsernameError::Reserved(String::from("Admin"))
+        );
+    }
+
+    #[test]
+    fn test_new_user() {
+        let user = User::new("someone@example.com", "someusername123").unwrap();
+        assert!(user.active);
+        assert_eq!(user.sign_in_count, 1);
+        assert_eq!(user.email.to_string(), "someone@example.com");
+
+        // the email is checked first
+        assert_eq!(
+            User::new("nobody", "root").unwrap_err(),
+            UserError::Email(EmailError::MissingAt)
+        );
+        assert_eq!(
+            User::new("someone@example.com", "root")
+                .unwrap_err()
+                .to_string(),
+            "the username 'root' is reserved"
+        );
+    }
+}